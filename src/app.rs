@@ -1,25 +1,44 @@
 //! This module provides the front end application through the [`App`] type
-use std::time::Duration;
+use std::{
+    collections::{BTreeSet, HashMap},
+    path::PathBuf,
+    time::{Duration, Instant},
+};
 
 use async_std::stream::{self, StreamExt};
-use crossterm::event::{self, Event, EventStream, KeyCode, KeyEvent};
+use crossterm::event::{
+    self, DisableMouseCapture, EnableMouseCapture, Event, EventStream, KeyCode, KeyEvent,
+    KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
 use futures::future::FutureExt;
 use layout::Flex;
 use ratatui::{
     prelude::*,
-    widgets::{Block, BorderType, Paragraph, Row, Table, TableState},
+    widgets::{Block, BorderType, Cell, Paragraph, Row, Table, TableState},
     DefaultTerminal,
 };
 use thiserror::Error;
 
 use crate::{
-    config::{Config, ConfigError},
-    storage::{Storage, StorageLoadError, StorageRunError, Transaction, TransactionFilter, User},
+    clipboard::{self, CopyMethod},
+    config::{Config, ConfigError, Keybindings, Theme},
+    export::{self, ExportError},
+    format,
+    storage::{
+        self, DateRange, LegacyData, Session, Storage, StorageLoadError, StorageRunError,
+        Transaction, TransactionFilter, TransactionStatus, User,
+    },
     CursoredString,
 };
 
+mod big_text;
 pub mod popups;
-use popups::{AddTransaction, CreateUser, FilterResults, Popup, PopupHandler};
+use popups::{
+    About, AddTransaction, ApplyRecurring, CombinePilots, ConfirmDelete, ConfirmLogout,
+    ConfirmPurge, CreateUser, EnterPin, FilterResults, FirstRun, Help, MergeUser, MigrateLegacy,
+    Notice, PickUser, Popup, PopupHandler, QuickAdd, ReasonCodes, SetPin, SetType,
+    SplitTransaction, Summary, SwitchVault, TransactionDetails, TransferFunds,
+};
 
 const MANTRA_INTRO: &str = r"  __       __   ______   __    __        __  ________  _______    ______
  /  \     /  | /      \ /  \  /  |      /  |/        |/       \  /      \ 
@@ -44,13 +63,50 @@ pub struct AppData {
     config: Config,
     storage: Storage,
     current_user: Option<User>,
+    /// The current user's open session, which new transactions are tagged with
+    session: Option<Session>,
     transactions: Vec<Transaction>,
     transaction_filters: Vec<TransactionFilter>,
+    /// Earlier sets of filters, most recent last, stepped back through with 'z'
+    filter_undo: Vec<Vec<TransactionFilter>>,
+    /// Sets of filters stepped back from, stepped forward through again with 'Z' until the filters are next changed
+    filter_redo: Vec<Vec<TransactionFilter>>,
     table_state: TableState,
+    /// Ids of the transactions marked for bulk actions
+    selected_ids: BTreeSet<i32>,
     status_text: String,
+    /// How many transactions match the table's filters, kept up to date by [`AppData::update_table`]
+    total_rows: i64,
+    /// Sum of the values of the transactions matching the table's filters, kept up to date by [`AppData::update_table`]
+    filtered_total: i64,
+    /// Position among every row matching the table's filters of the first one in `transactions`,
+    /// which only holds a window of them around the highlighted row
+    window_start: usize,
+    /// The current user's balance across all of their transactions, kept up to date by [`AppData::update_table`]
+    balance: i32,
+    /// Net change to the current user's balance so far today in the configured timezone, kept up to date alongside `balance`
+    today_net: i32,
+    /// Show datetimes in UTC rather than the configured timezone
+    show_utc: bool,
+    /// Show the soft deleted transactions instead of the live ones, for restoring them
+    show_deleted: bool,
+    /// Names of the other pilots whose transactions are shown alongside the current user's by their id,
+    /// along with the current user's own. Empty unless the table is combining pilots
+    combined_pilots: HashMap<i32, String>,
+    layout: LayoutCache,
     popup: Option<Popup>,
 }
 
+/// Areas from the last drawn frame, for event handlers that depend on what's on screen.
+/// Cleared on resize until the next frame is drawn
+#[derive(Default)]
+pub struct LayoutCache {
+    /// Area of the table's rows, excluding its borders and header
+    pub table_rows: Rect,
+    /// Inner area of the open popup's message field, if it has one
+    pub message: Option<Rect>,
+}
+
 /// Error that occurred at App initialization
 #[derive(Error, Debug)]
 pub enum AppInitError {
@@ -60,6 +116,8 @@ pub enum AppInitError {
     StorageLoad(#[from] StorageLoadError),
     #[error(transparent)]
     StorageRun(#[from] StorageRunError),
+    #[error(transparent)]
+    App(#[from] AppError),
 }
 
 /// Error that occurred while [`App`] is running
@@ -68,9 +126,15 @@ pub enum AppError {
     #[error(transparent)]
     Io(#[from] std::io::Error),
     #[error(transparent)]
+    StorageLoad(#[from] StorageLoadError),
+    #[error(transparent)]
     StorageRun(#[from] StorageRunError),
     #[error(transparent)]
     ParseInt(#[from] std::num::ParseIntError),
+    #[error(transparent)]
+    Export(#[from] ExportError),
+    #[error(transparent)]
+    Config(#[from] ConfigError),
 }
 
 /// Modes of [`App`]
@@ -79,8 +143,15 @@ pub enum AppMode {
     Intro { animation_progress: usize },
     /// User login prompt, internal field for username currently being typed
     UserLogin(CursoredString),
+    /// Deliberate creation of a new user, with the name being typed and why it was last rejected
+    NewUser {
+        name: CursoredString,
+        error: Option<String>,
+    },
     /// Table with log entires for the current user
     LogTable,
+    /// Only the current user's balance, in large figures for showing on a stream, with when it was last read
+    BigBalance { last_refresh: Instant },
     /// App is in the process of closing
     Quitting,
 }
@@ -88,51 +159,90 @@ pub enum AppMode {
 impl App {
     const DURATION_PER_FRAME: Duration = Duration::from_millis(1000 / 60);
 
-    /// Initialize a new App, starting with the intro animation then into a login screen
-    pub async fn init() -> Result<Self, AppInitError> {
-        let config = Config::load_or_create();
-        let storage = Storage::load_or_create().await?;
+    /// Initialize a new App on the given vault, or the default one if None,
+    /// starting with the intro animation, unless the config skips it, then into a login screen
+    pub async fn init(vault: Option<&str>) -> Result<Self, AppInitError> {
+        // checked before loading, which writes the defaults out
+        let config_existed = Config::exists()?;
+        let (mut config, recovery) = Config::load_or_recover().await?;
+        let status_text = config.check_keybindings().unwrap_or_default();
+        let storage = Storage::load_or_create(vault).await?;
+        let mut legacy = Vec::new();
+        for data in LegacyData::find(&config.legacy_apps) {
+            if !storage.is_migrated(&data.app).await? {
+                legacy.push(data);
+            }
+        }
+        let mut popup = MigrateLegacy::new(legacy).map(Popup::MigrateLegacy);
+        let last_version = About::record_version();
+        let new_version = last_version.as_deref() != Some(About::VERSION);
+        if let Some(about) = About::after_update(&storage, last_version.as_deref()) {
+            popup = Some(Popup::About(about.then(popup)));
+        }
+        if !config_existed && !storage.has_users().await? {
+            popup = Some(Popup::FirstRun(FirstRun::new(&config).then(popup)));
+        }
+        if let Some(recovery) = recovery {
+            popup = Some(Popup::Notice(
+                Notice::new("Config Error", recovery.notice()).then(popup),
+            ));
+        }
+        // turning the intro off wins over only showing it for a new version
+        let mode = if config.show_intro && (new_version || !config.intro_once_per_version) {
+            AppMode::Intro {
+                animation_progress: 0,
+            }
+        } else {
+            AppMode::UserLogin(Default::default())
+        };
         Ok(App {
             data: AppData {
-                config: config.await?,
+                config,
                 transactions: vec![],
                 transaction_filters: vec![],
+                filter_undo: vec![],
+                filter_redo: vec![],
                 storage,
                 current_user: None,
+                session: None,
                 table_state: TableState::default(),
-                status_text: String::new(),
-                popup: None,
-            },
-            mode: AppMode::Intro {
-                animation_progress: 0,
+                selected_ids: BTreeSet::new(),
+                status_text,
+                total_rows: 0,
+                filtered_total: 0,
+                window_start: 0,
+                balance: 0,
+                today_net: 0,
+                show_utc: false,
+                show_deleted: false,
+                combined_pilots: HashMap::new(),
+                layout: LayoutCache::default(),
+                popup,
             },
+            mode,
         })
     }
 
-    /// Initialize App with a given username, skipping the login screen
-    pub async fn init_with_username(username: String) -> Result<Self, AppInitError> {
-        let config = Config::load_or_create();
-        let storage = Storage::load_or_create().await?;
+    /// Initialize App with a given username, skipping the login screen.
+    /// A pilot with a PIN is still asked for it before they're logged in
+    pub async fn init_with_username(
+        username: String,
+        vault: Option<&str>,
+    ) -> Result<Self, AppInitError> {
+        let mut app = Self::init(vault).await?;
         let username = username.to_lowercase();
-        storage.create_user(&username).await?;
-        let user = storage.get_user(&username).await?;
-        Ok(App {
-            data: AppData {
-                config: config.await?,
-                transactions: storage
-                    .get_transactions(vec![TransactionFilter::UserId(vec![user.get_id()])])
-                    .await?,
-                transaction_filters: vec![],
-                storage,
-                current_user: Some(user),
-                table_state: TableState::default(),
-                status_text: String::new(),
-                popup: None,
-            },
-            mode: AppMode::Intro {
-                animation_progress: 0,
-            },
-        })
+        app.data.storage.create_user(&username).await?;
+        let user = app.data.storage.get_user(&username).await?;
+        if user.has_pin() {
+            app.data.popup = Some(Popup::EnterPin(EnterPin::new(user, &app.data.config)));
+            return Ok(app);
+        }
+        app.data.popup = app.data.login(user).await?;
+        // the intro moves on to the table itself once a user is logged in
+        if let AppMode::UserLogin(_) = app.mode {
+            app.mode = AppMode::LogTable;
+        }
+        Ok(app)
     }
 
     /// UI for the app, separating based on mode and displaying any popups on top of the current window
@@ -142,14 +252,27 @@ impl App {
                 self.data.play_intro(frame, animation_progress)
             }
             AppMode::LogTable => self.data.display_log(frame),
-            AppMode::UserLogin(username) => {
-                AppData::user_login(username, frame, self.data.popup.is_some())
-            }
+            AppMode::BigBalance { .. } => self.data.display_big_balance(frame),
+            AppMode::UserLogin(username) => AppData::user_login(
+                username,
+                frame,
+                self.data.popup.is_some(),
+                &self.data.config.theme,
+            ),
+            AppMode::NewUser { name, error } => AppData::new_user(
+                name,
+                error.as_deref(),
+                frame,
+                self.data.popup.is_some(),
+                &self.data.config.theme,
+            ),
             AppMode::Quitting => (),
         }
 
+        self.data.layout.message = None;
         if let Some(popup) = &mut self.data.popup {
-            popup.render_to_frame(frame.area(), frame);
+            popup.render_to_frame(frame.area(), frame, &self.data.config.theme);
+            self.data.layout.message = popup.message_area();
         }
     }
 
@@ -157,13 +280,13 @@ impl App {
     /// running the frame drawing asynchronously
     ///
     /// ```rust,no_run
-    /// use mantra::app::App;
+    /// use mantra_lancer::app::App;
     ///
     /// #[async_std::main]
     /// async fn main() -> anyhow::Result<()> {
-    ///     let mut terminal = ratatui::init();
+    ///     let terminal = ratatui::init();
     ///
-    ///     let mut app = App::init().await?;
+    ///     let app = App::init(None).await?;
     ///     let app_result = app.run(terminal).await;
     ///
     ///     ratatui::restore();
@@ -171,16 +294,37 @@ impl App {
     /// }
     /// ```
     pub async fn run(mut self, mut terminal: DefaultTerminal) -> Result<(), AppError> {
+        let mouse = self.data.config.mouse;
+        if mouse {
+            crossterm::execute!(terminal.backend_mut(), EnableMouseCapture)?;
+        }
+        let result = self.event_loop(&mut terminal).await;
+        if mouse {
+            crossterm::execute!(terminal.backend_mut(), DisableMouseCapture)?;
+        }
+        result?;
+        Ok(self.data.storage.close().await?)
+    }
+
+    /// Draws frames and handles events until the app quits
+    async fn event_loop(&mut self, terminal: &mut DefaultTerminal) -> Result<(), AppError> {
         let mut events = EventStream::new();
         let mut interval = stream::interval(Self::DURATION_PER_FRAME);
 
         while !matches!(self.mode, AppMode::Quitting) {
             futures::select_biased! {
-                _ = interval.next().fuse() => {terminal.draw(|frame| self.ui(frame))?;},
+                _ = interval.next().fuse() => {
+                    self.tick().await?;
+                    terminal.draw(|frame| self.ui(frame))?;
+                },
                 maybe_event = events.next().fuse() => {
                     match maybe_event {
                         Some(Ok(event)) => {
                             self.handle_event(&event).await?;
+                            // redraw straight away rather than showing a mangled frame until the next tick
+                            if let Event::Resize(..) = event {
+                                terminal.draw(|frame| self.ui(frame))?;
+                            }
                         }
                         Some(Err(e)) => return Err(e)?,
                         None => break,
@@ -191,7 +335,34 @@ impl App {
         Ok(())
     }
 
+    /// Keeps anything shown live up to date between events, such as the big balance
+    async fn tick(&mut self) -> Result<(), AppError> {
+        if let AppMode::BigBalance { last_refresh } = &mut self.mode {
+            let refresh = Duration::from_secs(self.data.config.big_balance_refresh_secs);
+            if last_refresh.elapsed() >= refresh {
+                // changes can come from another instance on the same database
+                let user_id = self.data.current_user.as_ref().map(|v| v.get_id()).unwrap();
+                self.data.balance = self
+                    .data
+                    .storage
+                    .balance_as_of(
+                        user_id,
+                        storage::now_utc(),
+                        self.data.config.pending_in_balance,
+                    )
+                    .await?;
+                *last_refresh = Instant::now();
+            }
+        }
+        Ok(())
+    }
+
+    /// Passes an event to the open popup, or to the current mode if there isn't one
     async fn handle_event(&mut self, event: &Event) -> Result<(), AppError> {
+        if let Event::Resize(..) = event {
+            // cached areas no longer match the screen until it's redrawn, which `run` does immediately
+            self.data.layout = LayoutCache::default();
+        }
         // popups grab all key events
         if let Some(popup) = self.data.popup.take() {
             self.data.popup = popup.handle_event(self, event).await?;
@@ -210,30 +381,327 @@ impl App {
                     AppMode::UserLogin(username) => {
                         self.data.run_user_login(username, *key).await?
                     }
+                    AppMode::NewUser { name, error } => {
+                        self.data.run_new_user(name, error, *key).await?
+                    }
                     AppMode::LogTable => self.data.run_table(*key).await?,
+                    AppMode::BigBalance { .. } => match key.code {
+                        KeyCode::Esc | KeyCode::Char(Keybindings::BIG_BALANCE) => {
+                            // only the balance was kept up to date
+                            self.data.update_table().await?;
+                            Some(AppMode::LogTable)
+                        }
+                        _ => None,
+                    },
                     AppMode::Quitting => None,
                 };
                 if let Some(mode) = new_state {
                     self.mode = mode
                 }
             }
+        } else if let Event::Mouse(mouse) = event {
+            if let AppMode::LogTable = self.mode {
+                self.data.run_table_mouse(*mouse).await?;
+            }
         }
         Ok(())
     }
 }
 
 impl AppData {
+    /// How many earlier sets of filters are kept to step back to
+    const FILTER_HISTORY_DEPTH: usize = 50;
+    /// How many rows the table loads at a time when `max_display_rows` isn't set
+    const TABLE_PAGE_SIZE: usize = 500;
+
+    /// How many rows the table loads at a time, the window being moved once the highlighted row nears either end of it
+    fn page_size(&self) -> usize {
+        self.config
+            .max_display_rows
+            .map_or(Self::TABLE_PAGE_SIZE, |rows| rows as usize)
+            .max(1)
+    }
+
     /// Updates the table from the DB, done after making any changes
     pub async fn update_table(&mut self) -> Result<(), AppError> {
+        let user_id = self.current_user.as_ref().map(|v| v.get_id()).unwrap();
+        let filters = self.table_filters();
+        self.total_rows = self.storage.count_transactions(&filters).await?;
+        self.filtered_total = self.storage.sum_transactions(&filters).await?;
+        // rows may have gone since the window was loaded, so it mustn't start past the last one
+        let total = usize::try_from(self.total_rows).unwrap_or_default();
+        self.window_start = self.window_start.min(total.saturating_sub(1));
+        let rows = self
+            .storage
+            .get_transactions(filters, Some((self.window_start, self.page_size())))
+            .await?;
+        self.transactions = rows.transactions;
+        if rows.failed > 0 {
+            // a gap in the ledger should never go unexplained
+            self.status_text = format!(
+                "Warning: {} transactions couldn't be read and are missing from the table",
+                rows.failed
+            );
+        }
+        self.balance = self
+            .storage
+            .balance_as_of(user_id, storage::now_utc(), self.config.pending_in_balance)
+            .await?;
+        let today = time::OffsetDateTime::now_utc()
+            .to_offset(self.config.timezone)
+            .date();
+        self.today_net = self
+            .storage
+            .net_for_range(user_id, DateRange::day(today, self.config.timezone))
+            .await?;
+        Ok(())
+    }
+
+    /// Moves the window of loaded rows to centre on the highlighted row once it's near either end
+    /// and there are more rows past that end, keeping the same rows highlighted and on screen
+    async fn follow_selection(&mut self) -> Result<(), AppError> {
+        let Some(selected) = self.table_state.selected() else {
+            return Ok(());
+        };
+        let len = self.transactions.len();
+        let total = usize::try_from(self.total_rows).unwrap_or_default();
+        let page_size = self.page_size();
+        let margin = page_size / 4;
+        let near_end = selected + margin >= len && self.window_start + len < total;
+        let near_start = selected < margin && self.window_start > 0;
+        if !(near_end || near_start) {
+            return Ok(());
+        }
+        let highlighted = self.window_start + selected.min(len.saturating_sub(1));
+        let top = self.window_start + self.table_state.offset();
+        self.window_start = highlighted.saturating_sub(page_size / 2);
+        self.update_table().await?;
+        self.table_state
+            .select(Some(highlighted.saturating_sub(self.window_start)));
+        *self.table_state.offset_mut() = top.saturating_sub(self.window_start);
+        Ok(())
+    }
+
+    /// Replaces the table's filters and refreshes it, remembering the old ones to step back to if they changed
+    pub async fn set_filters(&mut self, filters: Vec<TransactionFilter>) -> Result<(), AppError> {
+        if filters != self.transaction_filters {
+            self.window_start = 0;
+            let previous = std::mem::replace(&mut self.transaction_filters, filters);
+            if self.filter_undo.len() == Self::FILTER_HISTORY_DEPTH {
+                self.filter_undo.remove(0);
+            }
+            self.filter_undo.push(previous);
+            self.filter_redo.clear();
+        }
+        self.update_table().await
+    }
+
+    /// Steps the filters back to the previous set, or forward again if `redo`, refreshing the table
+    async fn step_filters(&mut self, redo: bool) -> Result<(), AppError> {
+        let (from, to) = if redo {
+            (&mut self.filter_redo, &mut self.filter_undo)
+        } else {
+            (&mut self.filter_undo, &mut self.filter_redo)
+        };
+        let Some(filters) = from.pop() else {
+            self.status_text = String::from(if redo {
+                "No filter changes to redo"
+            } else {
+                "No filter changes to undo"
+            });
+            return Ok(());
+        };
+        to.push(std::mem::replace(&mut self.transaction_filters, filters));
+        self.window_start = 0;
+        self.status_text = format!(
+            "{} filter change, {} filters applied",
+            if redo { "Redid" } else { "Undid" },
+            self.transaction_filters.len()
+        );
+        self.update_table().await
+    }
+
+    /// Soft deletes the transaction, saying which it was by the given description
+    async fn delete_transaction(
+        &mut self,
+        trans_id: i32,
+        description: &str,
+    ) -> Result<(), AppError> {
+        self.storage
+            .remove_transactions(TransactionFilter::Id(vec![trans_id]))
+            .await?;
+        self.status_text =
+            format!("Deleted \"{description}\", press 'D' to see deleted transactions");
+        self.update_table().await
+    }
+
+    /// Tags a newly added transaction with the open session, if there is one
+    pub async fn tag_session(&self, trans_id: i32) -> Result<(), AppError> {
+        if let Some(session) = &self.session {
+            self.storage.set_session(&[trans_id], session.id).await?;
+        }
+        Ok(())
+    }
+
+    /// Describes the open session for the status bar, empty when there isn't one
+    fn session_text(&self) -> String {
+        match &self.session {
+            Some(session) => format!(
+                "Session #{} since {}",
+                session.number,
+                format::format_datetime(
+                    session
+                        .started_at
+                        .assume_utc()
+                        .to_offset(self.config.timezone),
+                    self.config.locale,
+                )
+            ),
+            None => String::new(),
+        }
+    }
+
+    /// Filters selecting the transactions shown in the table, being the current user's that match the filters applied
+    fn table_filters(&self) -> Vec<TransactionFilter> {
+        let user_id = self.current_user.as_ref().map(|v| v.get_id()).unwrap();
         let mut filters = Vec::with_capacity(self.transaction_filters.len() + 1);
-        filters.push(TransactionFilter::UserId(vec![self
-            .current_user
-            .as_ref()
-            .map(|v| v.get_id())
-            .unwrap()]));
+        let mut user_ids = vec![user_id];
+        user_ids.extend(self.combined_pilots.keys().filter(|&&id| id != user_id));
+        filters.push(TransactionFilter::UserId(user_ids));
         // TODO: This is not ideal, maybe we could have separate OwnedFilters and RefFilters types
         filters.extend(self.transaction_filters.iter().cloned());
-        self.transactions = self.storage.get_transactions(filters).await?;
+        if self.show_deleted {
+            filters.push(TransactionFilter::Deleted);
+        }
+        filters
+    }
+
+    /// Filters selecting only the current user's transactions of those shown in the table, for actions that change them
+    fn own_table_filters(&self) -> Vec<TransactionFilter> {
+        let user_id = self.current_user.as_ref().map(|v| v.get_id()).unwrap();
+        let mut filters = self.table_filters();
+        filters[0] = TransactionFilter::UserId(vec![user_id]);
+        filters
+    }
+
+    /// Whether the key would change the highlighted transaction while it's another pilot's, as can be shown in the combined view.
+    /// Bulk actions only count when nothing is selected, since only the current pilot's transactions can be selected
+    fn changes_other_pilot(&self, code: KeyCode) -> bool {
+        let Some(transaction) = self.highlighted_transaction() else {
+            return false;
+        };
+        if self
+            .current_user
+            .as_ref()
+            .is_none_or(|user| user.get_id() == transaction.user_id)
+        {
+            return false;
+        }
+        let keys = &self.config.keybindings;
+        match code {
+            KeyCode::Char(c) if keys.changing_highlighted().contains(&c) => true,
+            KeyCode::Char(c) if keys.changing_selection().contains(&c) => {
+                self.selected_ids.is_empty()
+            }
+            _ => false,
+        }
+    }
+
+    /// Exports the current user's transactions matching the given filters to CSV and JSON
+    async fn export(&self, filters: &[TransactionFilter]) -> Result<[PathBuf; 2], AppError> {
+        let user = self.current_user.as_ref().unwrap();
+        let mut query_filters = Vec::with_capacity(filters.len() + 1);
+        query_filters.push(TransactionFilter::UserId(vec![user.get_id()]));
+        query_filters.extend(filters.iter().cloned());
+        Ok(export::export_files(
+            user,
+            self.storage.stream_transactions(query_filters),
+            filters,
+            self.config.timezone,
+        )
+        .await?)
+    }
+
+    /// The transaction highlighted in the table, if any
+    pub fn highlighted_transaction(&self) -> Option<&Transaction> {
+        self.table_state.selected().and_then(|index| {
+            self.transactions
+                .get(index.min(self.transactions.len().saturating_sub(1)))
+        })
+    }
+
+    /// Ids to apply a bulk action to, being the selected transactions or the highlighted one if none are selected
+    pub fn bulk_ids(&self) -> Vec<i32> {
+        if self.selected_ids.is_empty() {
+            self.highlighted_transaction()
+                .map(|transaction| vec![transaction.trans_id])
+                .unwrap_or_default()
+        } else {
+            self.selected_ids.iter().copied().collect()
+        }
+    }
+
+    /// Sets the current user and loads their table.
+    /// Returns a popup offering any recurring entries that were missed since they last logged in
+    pub async fn login(&mut self, user: User) -> Result<Option<Popup>, AppError> {
+        self.status_text = format!(
+            "Logged in as '{}'",
+            self.config.display_name(user.get_name())
+        );
+        let pending = self
+            .storage
+            .pending_recurring(user.get_id(), storage::now_utc())
+            .await?;
+        self.session = self.storage.active_session(user.get_id()).await?;
+        self.current_user = Some(user);
+        self.update_table().await?;
+        Ok((!pending.is_empty())
+            .then(|| Popup::ApplyRecurring(ApplyRecurring::new(pending, &self.config))))
+    }
+
+    /// Clears the current user and everything shown for them
+    pub fn logout(&mut self) {
+        self.current_user = None;
+        self.session = None;
+        self.transactions = vec![];
+        // another user's filters shouldn't be stepped back into
+        self.filter_undo.clear();
+        self.filter_redo.clear();
+        self.selected_ids.clear();
+        self.show_deleted = false;
+        self.combined_pilots.clear();
+        self.window_start = 0;
+    }
+
+    /// Shows the transactions of the given pilots, by id and shown name, alongside the current user's
+    async fn combine_pilots(&mut self, pilots: Vec<(i32, String)>) -> Result<(), AppError> {
+        let current = self.current_user.as_ref().unwrap();
+        let current = (
+            current.get_id(),
+            self.config.display_name(current.get_name()).into_owned(),
+        );
+        self.combined_pilots = pilots.into_iter().chain([current]).collect();
+        self.selected_ids.clear();
+        self.window_start = 0;
+        self.table_state.select(Some(0));
+        self.status_text = format!(
+            "Showing the transactions of {} pilots, press 'C' to show only yours",
+            self.combined_pilots.len()
+        );
+        self.update_table().await
+    }
+
+    /// Swaps to another vault's database, logging out as its users are entirely separate
+    pub async fn switch_vault(&mut self, storage: Storage) -> Result<(), AppError> {
+        self.logout();
+        self.transaction_filters.clear();
+        self.status_text = match storage.vault() {
+            Some(vault) => format!("Opened vault '{vault}'"),
+            None => String::from("Opened the default vault"),
+        };
+        std::mem::replace(&mut self.storage, storage)
+            .close()
+            .await?;
         Ok(())
     }
 
@@ -265,50 +733,266 @@ impl AppData {
         frame.render_widget(instruct_text, instruct_area);
     }
 
+    /// Displays only the current user's balance in the given [`Frame`], as large as it can be read from afar
+    pub fn display_big_balance(&self, frame: &mut Frame) {
+        let amount = self.config.amount_format.format(self.balance);
+        let figures = big_text::big_text(&amount);
+        let style = if self.balance < 0 {
+            Style::new().fg(self.config.theme.expense)
+        } else {
+            Style::new().fg(self.config.theme.income)
+        };
+        let mut lines: Vec<Line> = figures
+            .into_iter()
+            .map(|line| Line::from(line).style(style))
+            .collect();
+        lines.push(Line::default());
+        lines.push(Line::from(self.config.currency.long.as_str()).bold());
+        if let Some(user) = &self.current_user {
+            let width = usize::from(frame.area().width);
+            let name = self.config.display_name(user.get_name());
+            lines.push(Line::from(format::truncate(&name, width).into_owned()).dim());
+        }
+
+        let [area] = Layout::vertical([Constraint::Length(lines.len() as u16)])
+            .flex(Flex::Center)
+            .areas(frame.area());
+        frame.render_widget(Paragraph::new(lines).alignment(Alignment::Center), area);
+        let [_, hint_area] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(frame.area());
+        frame.render_widget(
+            Paragraph::new("Esc: back to the table")
+                .alignment(Alignment::Right)
+                .dark_gray(),
+            hint_area,
+        );
+    }
+
     /// Displays the log in the given [`Frame`]
     pub fn display_log(&mut self, frame: &mut Frame) {
+        // the combined view also shows whose each transaction is, and the deleted view when each was deleted
+        let combined = !self.combined_pilots.is_empty();
         let widths = [
             Constraint::Fill(1),
             Constraint::Fill(3),
             Constraint::Fill(1),
+            Constraint::Fill(if combined { 1 } else { 0 }),
+            Constraint::Fill(if self.show_deleted { 1 } else { 0 }),
         ];
 
+        let (timezone, datetime_header) = if self.show_utc {
+            (time::UtcOffset::UTC, "Date/Time (UTC)")
+        } else {
+            (self.config.timezone, "Date/Time")
+        };
+
+        // ages are a difference of UTC datetimes, which is the same in any timezone
+        let now = storage::now_utc();
+        let recent_within = self
+            .config
+            .recent_within_mins
+            .map(|mins| time::Duration::minutes(mins.into()));
+        let stale_after = self
+            .config
+            .stale_after_days
+            .map(|days| time::Duration::days(days.into()));
+
         // create the iterator of rows from App's vector of transactions
-        let rows = self.transactions.iter().map(|trans| {
+        let rows = self.transactions.iter().enumerate().map(|(index, trans)| {
+            let deleted_at = match trans.deleted_at {
+                Some(deleted_at) if self.show_deleted => format::format_datetime(
+                    deleted_at.assume_utc().to_offset(timezone),
+                    self.config.locale,
+                ),
+                _ => String::new(),
+            };
+            let pilot = match self.combined_pilots.get(&trans.user_id) {
+                Some(name) => name.clone(),
+                None => String::new(),
+            };
+            // selected rows keep their own color throughout
+            let value_style = match trans.value.signum() {
+                _ if self.selected_ids.contains(&trans.trans_id) => Style::new(),
+                1 => Style::new().fg(self.config.theme.income),
+                -1 => Style::new().fg(self.config.theme.expense),
+                _ => Style::new(),
+            };
             Row::new([
-                trans.value.to_string(),
-                trans.msg.clone(),
-                trans
-                    .datetime
-                    .assume_utc()
-                    .to_offset(self.config.timezone)
-                    .format(time::macros::format_description!(
-                        "[year]-[month]-[day] [hour]:[minute]"
-                    ))
-                    .unwrap(),
+                Cell::from(self.config.amount_format.format(trans.value)).style(value_style),
+                Cell::from(trans.msg.clone()),
+                Cell::from(format::format_datetime(
+                    trans.datetime.assume_utc().to_offset(timezone),
+                    self.config.locale,
+                )),
+                Cell::from(pilot),
+                Cell::from(deleted_at),
             ])
+            .style({
+                // the stripe is only a background, so selection and the highlight show over it
+                // counted from the first matching row, so the stripes don't swap as the window moves
+                let style = if self.config.row_stripes && (self.window_start + index) % 2 == 1 {
+                    Style::new().bg(self.config.stripe_color)
+                } else {
+                    Style::new()
+                };
+                // age only sets the text color, which selection takes over
+                let age = now - trans.datetime;
+                let style = if self.selected_ids.contains(&trans.trans_id) {
+                    style.bold().fg(self.config.theme.selected)
+                } else if recent_within
+                    .is_some_and(|recent| (time::Duration::ZERO..=recent).contains(&age))
+                {
+                    style.bold().fg(self.config.theme.recent)
+                } else if stale_after.is_some_and(|stale| age > stale) {
+                    style.fg(self.config.theme.stale)
+                } else {
+                    style
+                };
+                match trans.status {
+                    TransactionStatus::Pending => style.dim(),
+                    TransactionStatus::Cleared => style,
+                }
+            })
         });
 
         // styling and layout
         let block = Block::bordered()
-            .border_style(Style::new().white())
-            .title("MAN/TRA");
+            .border_style(self.config.theme.border)
+            .title(match (self.storage.vault(), self.show_deleted) {
+                (Some(vault), true) => format!("MAN/TRA [{vault}] (deleted)"),
+                (Some(vault), false) => format!("MAN/TRA [{vault}]"),
+                (None, true) => String::from("MAN/TRA (deleted)"),
+                (None, false) => String::from("MAN/TRA"),
+            });
         let [table_area, status_area] =
             Layout::vertical([Constraint::Fill(1), Constraint::Length(3)]).areas(frame.area());
+        let currency = self.config.currency.display_name(table_area.width);
 
         // create table with currency, note, and date+time columns
         let table_widget = Table::new(rows, widths)
             .block(block)
             .header(
-                Row::new([self.config.currency.long.as_str(), "Note", "Date/Time"]).underlined(),
+                Row::new([
+                    currency,
+                    "Note",
+                    datetime_header,
+                    if combined { "Pilot" } else { "" },
+                    if self.show_deleted { "Deleted" } else { "" },
+                ])
+                .underlined(),
             )
-            .highlight_style(Style::new().black().on_white());
+            .highlight_style(self.config.theme.highlight());
 
         frame.render_stateful_widget(&table_widget, table_area, &mut self.table_state);
+        // rows start below the border and the header
+        let [_, rows_area] = Layout::vertical([Constraint::Length(1), Constraint::Fill(1)])
+            .areas(table_area.inner(layout::Margin::new(1, 1)));
+        self.layout.table_rows = rows_area;
+        if self.transactions.is_empty() {
+            // an empty table looks broken, so hint at what to do next
+            let hint = if self.show_deleted {
+                String::from("No deleted transactions — press 'D' to go back")
+            } else if self.transaction_filters.is_empty() {
+                format!(
+                    "No transactions — press '{}' to add one",
+                    self.config.keybindings.add
+                )
+            } else {
+                format!(
+                    "No results for current filters — press '{}' to edit",
+                    self.config.keybindings.filter
+                )
+            };
+            let [hint_area] = Layout::vertical([Constraint::Length(1)])
+                .flex(Flex::Center)
+                .areas(table_area);
+            frame.render_widget(
+                Paragraph::new(hint).alignment(Alignment::Center).dim(),
+                hint_area,
+            );
+        }
+        // the balance is always shown beside the transient status messages
+        let [status_area, balance_area] = Layout::horizontal([
+            Constraint::Fill(1),
+            Constraint::Length(if self.config.show_balance { 30 } else { 0 }),
+        ])
+        .areas(status_area);
         frame.render_widget(
-            Paragraph::new(self.status_text.clone()).block(Block::bordered().title("Status")),
+            Paragraph::new(self.status_text.clone()).block(
+                Block::bordered()
+                    .title("Status")
+                    .title(Line::from(self.session_text()).bold())
+                    .title(Line::from(self.position_text()).right_aligned()),
+            ),
             status_area,
         );
+        if self.config.show_balance {
+            let mut balance_block = Block::bordered().title("Balance").title_bottom(format!(
+                "Today {}",
+                self.config.amount_format.format(self.today_net)
+            ));
+            // the balance covers everything, so what the filters leave is totalled separately
+            if !self.transaction_filters.is_empty() || self.show_deleted {
+                balance_block = balance_block.title(
+                    Line::from(format!(
+                        "Filtered {}",
+                        self.config.amount_format.format_wide(self.filtered_total)
+                    ))
+                    .right_aligned(),
+                );
+            }
+            frame.render_widget(
+                Paragraph::new(format!(
+                    "{} {}",
+                    self.config.amount_format.format(self.balance),
+                    currency
+                ))
+                .block(balance_block),
+                balance_area,
+            );
+        }
+    }
+
+    /// Describes which rows are on screen in the table as drawn, such as "rows 1-20 of 45, page 1/3",
+    /// counting from the first row matching the filters rather than the first loaded
+    fn position_text(&self) -> String {
+        if self.transactions.is_empty() {
+            return String::new();
+        }
+        let page_size = usize::from(self.layout.table_rows.height.max(1));
+        let total = usize::try_from(self.total_rows).unwrap_or_default();
+        let first = self.window_start + self.table_state.offset() + 1;
+        let last = (first - 1 + page_size).min(self.window_start + self.transactions.len());
+        format!(
+            "rows {first}-{last} of {total}, page {}/{}",
+            last.div_ceil(page_size),
+            total.div_ceil(page_size).max(1)
+        )
+    }
+
+    /// Handle mouse input for the table, scrolling with the wheel and highlighting clicked rows
+    pub async fn run_table_mouse(&mut self, mouse: MouseEvent) -> Result<(), AppError> {
+        match mouse.kind {
+            MouseEventKind::ScrollDown => {
+                self.table_state.select_next();
+                self.follow_selection().await?;
+            }
+            MouseEventKind::ScrollUp => {
+                self.table_state.select_previous();
+                self.follow_selection().await?;
+            }
+            MouseEventKind::Down(MouseButton::Left) => {
+                let rows = self.layout.table_rows;
+                if rows.contains(Position::new(mouse.column, mouse.row)) {
+                    let index = self.table_state.offset() + (mouse.row - rows.y) as usize;
+                    if index < self.transactions.len() {
+                        self.table_state.select(Some(index));
+                    }
+                }
+            }
+            _ => (),
+        }
+        Ok(())
     }
 
     /// Handle input for the user login prompt
@@ -325,18 +1009,26 @@ impl AppData {
             KeyCode::Right => {
                 username.left();
             }
+            KeyCode::Home => username.home(),
+            KeyCode::End => username.end(),
+            KeyCode::Tab => {
+                let users = self.storage.list_users().await?;
+                self.popup = Some(Popup::PickUser(PickUser::new(users, &self.config)));
+            }
             KeyCode::Enter if !username.is_empty() => {
                 // try to get the user from DB, if this fails show the new user popup
                 let username = username.to_lowercase();
                 match self.storage.get_user(&username).await {
+                    Ok(user) if user.has_pin() => {
+                        self.popup = Some(Popup::EnterPin(EnterPin::new(user, &self.config)))
+                    }
                     Ok(user) => {
-                        self.status_text = format!("Logged in as '{}'", user.get_name());
-                        self.current_user = Some(user);
-                        self.update_table().await?;
+                        self.popup = self.login(user).await?;
                         return Ok(Some(AppMode::LogTable));
                     }
                     Err(StorageRunError::RecordMissing) => {
-                        self.popup = Some(Popup::CreateUser(CreateUser::new(username)))
+                        self.popup =
+                            Some(Popup::CreateUser(CreateUser::new(username, &self.config)))
                     }
                     Err(e) => return Err(e.into()),
                 }
@@ -345,7 +1037,55 @@ impl AppData {
             KeyCode::Delete => username.remove_ahead(),
             KeyCode::Insert => username.inserting = !username.inserting,
             KeyCode::Esc => return Ok(Some(AppMode::Quitting)),
-            KeyCode::Char(c) if !c.is_whitespace() => username.insert(c),
+            KeyCode::Char('n') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                return Ok(Some(AppMode::NewUser {
+                    name: Default::default(),
+                    error: None,
+                }));
+            }
+            KeyCode::Char(c) if !c.is_whitespace() => {
+                username.insert(c);
+            }
+            _ => (),
+        }
+        Ok(None)
+    }
+
+    /// Handle input for creating a new user, logging straight in once created
+    pub async fn run_new_user(
+        &mut self,
+        name: &mut CursoredString,
+        error: &mut Option<String>,
+        key: KeyEvent,
+    ) -> Result<Option<AppMode>, AppError> {
+        match key.code {
+            KeyCode::Left => name.right(),
+            KeyCode::Right => name.left(),
+            KeyCode::Enter => {
+                let name = name.to_lowercase();
+                if let Err(e) = User::validate_name(&name) {
+                    *error = Some(e.to_string());
+                    return Ok(None);
+                }
+                match self.storage.get_user(&name).await {
+                    Ok(_) => *error = Some(format!("User '{name}' already exists")),
+                    Err(StorageRunError::RecordMissing) => {
+                        self.storage.create_user(&name).await?;
+                        let user = self.storage.get_user(&name).await?;
+                        self.popup = self.login(user).await?;
+                        return Ok(Some(AppMode::LogTable));
+                    }
+                    Err(e) => return Err(e.into()),
+                }
+            }
+            KeyCode::Backspace => name.remove_behind(),
+            KeyCode::Delete => name.remove_ahead(),
+            KeyCode::Insert => name.inserting = !name.inserting,
+            KeyCode::Esc => return Ok(Some(AppMode::UserLogin(Default::default()))),
+            KeyCode::Char(c) => {
+                *error = None;
+                name.insert(c);
+            }
             _ => (),
         }
         Ok(None)
@@ -353,44 +1093,431 @@ impl AppData {
 
     /// Handles input for the table mode
     pub async fn run_table(&mut self, key: KeyEvent) -> Result<Option<AppMode>, AppError> {
-        match key.code {
-            KeyCode::Down => self.table_state.select_next(),
-            KeyCode::Up => self.table_state.select_previous(),
-            KeyCode::Esc => return Ok(Some(AppMode::Quitting)),
-            KeyCode::Char('q') => {
+        match self.config.nav_key(key.code) {
+            KeyCode::Down => {
+                self.table_state.select_next();
+                self.follow_selection().await?;
+            }
+            KeyCode::Up => {
+                self.table_state.select_previous();
+                self.follow_selection().await?;
+            }
+            KeyCode::PageDown => {
+                let page = self.layout.table_rows.height.max(1);
+                self.table_state.scroll_down_by(page);
+                self.follow_selection().await?;
+            }
+            KeyCode::PageUp => {
+                let page = self.layout.table_rows.height.max(1);
+                self.table_state.scroll_up_by(page);
+                self.follow_selection().await?;
+            }
+            KeyCode::Char(c) if c == self.config.quit_key => {
                 return Ok(Some(AppMode::Quitting));
             }
-            KeyCode::Char('o') => {
-                self.current_user = None;
-                self.transactions = vec![];
+            KeyCode::Esc if self.config.esc_quits => return Ok(Some(AppMode::Quitting)),
+            // backs out of whatever is in effect, one thing at a time
+            KeyCode::Esc => {
+                if !self.selected_ids.is_empty() {
+                    self.selected_ids.clear();
+                } else if self.show_deleted {
+                    self.show_deleted = false;
+                    self.window_start = 0;
+                    self.table_state.select(Some(0));
+                    self.update_table().await?;
+                } else {
+                    self.status_text = format!("Press '{}' to quit", self.config.quit_key);
+                }
+            }
+            code if self.changes_other_pilot(code) => {
+                self.status_text = String::from("Only your own transactions can be changed");
+            }
+            KeyCode::Char(Keybindings::SELECT) => {
+                if let Some(transaction) = self.highlighted_transaction() {
+                    let id = transaction.trans_id;
+                    if !self.selected_ids.remove(&id) {
+                        self.selected_ids.insert(id);
+                    }
+                }
+            }
+            KeyCode::Char(c) if c == self.config.keybindings.set_type => {
+                let ids = self.bulk_ids();
+                if ids.is_empty() {
+                    self.status_text = String::from("No transactions to change");
+                } else {
+                    self.popup = Some(Popup::SetType(SetType::new(ids)));
+                }
+            }
+            KeyCode::Char(c) if c == self.config.keybindings.toggle_status => {
+                let ids = self.bulk_ids();
+                // everything ends up with the opposite of the highlighted row's status
+                let status = self
+                    .highlighted_transaction()
+                    .map_or(TransactionStatus::Pending, |transaction| {
+                        transaction.status.toggled()
+                    });
+                if ids.is_empty() {
+                    self.status_text = String::from("No transactions to change");
+                } else {
+                    let changed = self.storage.set_status(&ids, status).await?;
+                    self.status_text = format!(
+                        "Marked {changed} transactions {}",
+                        status.to_string().to_lowercase()
+                    );
+                    self.update_table().await?;
+                }
+            }
+            // popups grab every key so nothing typed in one can be lost here, but a selection can
+            KeyCode::Char(c)
+                if c == self.config.keybindings.logout
+                    && !self.selected_ids.is_empty()
+                    && !self.config.fast_mode =>
+            {
+                self.popup = Some(Popup::ConfirmLogout(ConfirmLogout::new(
+                    self.selected_ids.len(),
+                )));
+            }
+            KeyCode::Char(c) if c == self.config.keybindings.logout => {
+                self.logout();
                 return Ok(Some(AppMode::UserLogin(Default::default())));
             }
-            KeyCode::Char('a') => {
-                self.popup = Some(Popup::AddTransaction(AddTransaction::default()));
+            KeyCode::Char(c) if c == self.config.keybindings.add => {
+                let reasons = self.storage.reason_codes().await?;
+                self.popup = Some(Popup::AddTransaction(
+                    AddTransaction::new(&self.config).with_reasons(reasons),
+                ));
+            }
+            KeyCode::Char(c) if c == self.config.keybindings.delete && self.show_deleted => {
+                self.status_text = String::from("Already deleted, press 'R' to restore instead");
+            }
+            KeyCode::Char(c) if c == self.config.keybindings.delete => {
+                // nothing to delete when the table is empty
+                if let Some(transaction) = self.highlighted_transaction() {
+                    let trans_id = transaction.trans_id;
+                    let description = format!(
+                        "{} | {}",
+                        self.config.amount_format.format(transaction.value),
+                        transaction.msg
+                    );
+                    if self.config.fast_mode {
+                        self.delete_transaction(trans_id, &description).await?;
+                    } else {
+                        self.popup = Some(Popup::ConfirmDelete(ConfirmDelete::new(
+                            trans_id,
+                            description,
+                        )));
+                    }
+                }
+            }
+            KeyCode::Char(Keybindings::SHOW_DELETED) => {
+                self.show_deleted = !self.show_deleted;
+                // the selection only makes sense for the rows it was made on
+                self.selected_ids.clear();
+                self.window_start = 0;
+                self.table_state.select(Some(0));
+                self.update_table().await?;
             }
-            KeyCode::Char('d') => {
-                if let Some(index) = self.table_state.selected() {
-                    let index = index.clamp(0, self.transactions.len() - 1);
-                    let transaction = &self.transactions[index];
-                    self.storage
-                        .remove_transactions(TransactionFilter::Id(vec![transaction.trans_id]))
+            KeyCode::Char(Keybindings::RESTORE) if self.show_deleted => {
+                let ids = self.bulk_ids();
+                if ids.is_empty() {
+                    self.status_text = String::from("No transactions to restore");
+                } else {
+                    let restored = self
+                        .storage
+                        .restore_transactions(TransactionFilter::Id(ids))
                         .await?;
-                    self.status_text =
-                        format!("Deleted \"{} | {}\"", transaction.value, transaction.msg);
-                    self.update_table().await?
+                    self.status_text = format!("Restored {restored} transactions");
+                    self.selected_ids.clear();
+                    self.update_table().await?;
                 }
             }
-            KeyCode::Char('f') => {
-                self.popup = Some(Popup::FilterResults(FilterResults::new(std::mem::take(
-                    &mut self.transaction_filters,
-                ))))
+            KeyCode::Char(Keybindings::REPEAT) => {
+                let user_id = self.current_user.as_ref().map(|v| v.get_id()).unwrap();
+                match self.storage.latest_transaction(user_id).await? {
+                    Some(latest) => {
+                        let trans_id = self
+                            .storage
+                            .add_transaction_at(
+                                user_id,
+                                latest.value,
+                                latest.transaction_type,
+                                &latest.msg,
+                                storage::now_utc(),
+                            )
+                            .await?;
+                        self.storage.set_status(&[trans_id], latest.status).await?;
+                        self.tag_session(trans_id).await?;
+                        self.storage
+                            .set_reason(&[trans_id], latest.reason_id)
+                            .await?;
+                        self.status_text = format!(
+                            "Repeated \"{} | {}\"",
+                            self.config.amount_format.format(latest.value),
+                            latest.msg
+                        );
+                        self.update_table().await?;
+                    }
+                    None => self.status_text = String::from("No transaction to repeat"),
+                }
+            }
+            KeyCode::Char(Keybindings::SESSION) => {
+                let user_id = self.current_user.as_ref().map(|v| v.get_id()).unwrap();
+                match self.session.take() {
+                    Some(session) => {
+                        self.storage.end_session(session.id).await?;
+                        self.status_text = format!("Ended session #{}", session.number);
+                    }
+                    None => {
+                        let session = self.storage.start_session(user_id).await?;
+                        self.status_text = format!(
+                            "Started session #{}, new transactions are tagged with it until 'G' ends it",
+                            session.number
+                        );
+                        self.session = Some(session);
+                    }
+                }
+            }
+            KeyCode::Char(Keybindings::PURGE) if self.show_deleted => {
+                let ids = self.bulk_ids();
+                if ids.is_empty() {
+                    self.status_text = String::from("No transactions to purge");
+                } else {
+                    self.popup = Some(Popup::ConfirmPurge(ConfirmPurge::new(ids)));
+                }
+            }
+            KeyCode::Char(Keybindings::SPLIT) if !self.show_deleted => {
+                if let Some(transaction) = self.highlighted_transaction() {
+                    if transaction.transfer_id.is_some() {
+                        self.status_text = String::from("Transfers can't be split");
+                    } else {
+                        self.popup = Some(Popup::SplitTransaction(SplitTransaction::new(
+                            transaction,
+                            &self.config.amount_format,
+                        )));
+                    }
+                }
+            }
+            KeyCode::Enter => {
+                if let Some(transaction) = self.highlighted_transaction() {
+                    let reason = match transaction.reason_id {
+                        Some(reason_id) => self
+                            .storage
+                            .reason_codes()
+                            .await?
+                            .into_iter()
+                            .find(|reason| reason.id == reason_id)
+                            .map(|reason| reason.name),
+                        None => None,
+                    };
+                    let notes = self.storage.transaction_notes(transaction.trans_id).await?;
+                    let user_id = self.current_user.as_ref().map(|v| v.get_id()).unwrap();
+                    let mut details =
+                        TransactionDetails::new(transaction, reason, notes, &self.config);
+                    // another pilot's transaction can be read in the combined view, but not added to
+                    if transaction.user_id != user_id {
+                        details = details.read_only();
+                    }
+                    self.popup = Some(Popup::TransactionDetails(details));
+                }
+            }
+            KeyCode::Char(c) if c == self.config.keybindings.filter => {
+                self.popup = Some(Popup::FilterResults(FilterResults::new(
+                    self.transaction_filters.clone(),
+                )))
+            }
+            KeyCode::Char(Keybindings::BIG_BALANCE) => {
+                self.update_table().await?;
+                return Ok(Some(AppMode::BigBalance {
+                    last_refresh: Instant::now(),
+                }));
+            }
+            KeyCode::Char(c) if c == self.config.keybindings.copy_total => {
+                let total = self.storage.sum_transactions(&self.table_filters()).await?;
+                let amount = self.config.amount_format.format_wide(total);
+                self.status_text = match clipboard::copy(&amount) {
+                    Ok(CopyMethod::Tool(_)) => format!("Copied the filtered total {amount}"),
+                    Ok(CopyMethod::Terminal) => format!(
+                        "Sent the filtered total {amount} to the terminal's clipboard, if it supports that"
+                    ),
+                    Err(error) => format!("Couldn't copy the filtered total {amount}: {error}"),
+                };
+            }
+            KeyCode::Char(c) if c == self.config.keybindings.undo_filters => {
+                self.step_filters(false).await?
+            }
+            KeyCode::Char(Keybindings::REDO_FILTERS) => self.step_filters(true).await?,
+            KeyCode::Char(Keybindings::HELP) => {
+                self.popup = Some(Popup::Help(Help::new(&self.config)))
+            }
+            KeyCode::Char(c) if c == self.config.keybindings.edit => {
+                let reasons = self.storage.reason_codes().await?;
+                if let Some(transaction) = self.highlighted_transaction() {
+                    self.popup = Some(Popup::AddTransaction(
+                        AddTransaction::edit(transaction, &self.config).with_reasons(reasons),
+                    ));
+                }
+            }
+            KeyCode::Char(c) if c == self.config.keybindings.quick_add => {
+                self.popup = Some(Popup::QuickAdd(QuickAdd::new()))
+            }
+            KeyCode::Char(c) if c == self.config.keybindings.pin => {
+                self.popup = Some(Popup::SetPin(SetPin::new()))
+            }
+            KeyCode::Char(Keybindings::MERGE) => {
+                self.popup = Some(Popup::MergeUser(MergeUser::new()))
+            }
+            KeyCode::Char(Keybindings::TRANSFER) => {
+                self.popup = Some(Popup::TransferFunds(TransferFunds::new()))
+            }
+            KeyCode::Char(Keybindings::VAULT) => {
+                self.popup = Some(Popup::SwitchVault(SwitchVault::load(&self.storage)?));
+            }
+            KeyCode::Char(c) if c == self.config.keybindings.reasons => {
+                self.popup = Some(Popup::ReasonCodes(ReasonCodes::load(self).await?));
+            }
+            KeyCode::Char(c) if c == self.config.keybindings.utc => self.show_utc = !self.show_utc,
+            KeyCode::Char(c) if c == self.config.keybindings.version => {
+                self.popup = Some(Popup::About(About::new(&self.storage)))
+            }
+            KeyCode::Char(c) if c == self.config.keybindings.export => {
+                let [csv_path, json_path] = self.export(&self.transaction_filters).await?;
+                self.status_text = format!(
+                    "Exported to {} and {}",
+                    csv_path.display(),
+                    json_path.display()
+                );
+            }
+            KeyCode::Char(Keybindings::COMBINE) if !self.combined_pilots.is_empty() => {
+                self.combined_pilots.clear();
+                self.selected_ids.clear();
+                self.window_start = 0;
+                self.table_state.select(Some(0));
+                self.status_text = String::from("Showing only your transactions");
+                self.update_table().await?;
+            }
+            KeyCode::Char(Keybindings::COMBINE) => {
+                let user_id = self.current_user.as_ref().map(|v| v.get_id()).unwrap();
+                let users = self.storage.list_users().await?;
+                self.popup = Some(Popup::CombinePilots(CombinePilots::new(
+                    users,
+                    user_id,
+                    &self.config,
+                )));
+            }
+            KeyCode::Char(Keybindings::EXPORT_SELECTION) => {
+                let (filters, exported) = if self.selected_ids.is_empty() {
+                    (
+                        self.transaction_filters.clone(),
+                        String::from("Nothing selected, exported the filtered view"),
+                    )
+                } else {
+                    (
+                        vec![TransactionFilter::Id(
+                            self.selected_ids.iter().copied().collect(),
+                        )],
+                        format!("Exported {} selected", self.selected_ids.len()),
+                    )
+                };
+                let [csv_path, json_path] = self.export(&filters).await?;
+                self.status_text = format!(
+                    "{exported} to {} and {}",
+                    csv_path.display(),
+                    json_path.display()
+                );
+            }
+            KeyCode::Char(c) if c == self.config.keybindings.summary => {
+                let user_id = self.current_user.as_ref().map(|v| v.get_id()).unwrap();
+                let balance = self
+                    .storage
+                    .balance_as_of(user_id, storage::now_utc(), self.config.pending_in_balance)
+                    .await?;
+                let type_totals = self
+                    .storage
+                    .totals_by_type(user_id, self.config.pending_in_balance)
+                    .await?;
+                self.popup = Some(Popup::Summary(Summary::new(
+                    balance,
+                    self.today_net,
+                    type_totals,
+                    &self.config,
+                )));
             }
             _ => (),
         }
         Ok(None)
     }
 
-    pub fn user_login(username: &CursoredString, frame: &mut Frame, hide_cursor: bool) {
+    /// Renders the login prompt, with the typed name scrolled to stay inside its box
+    ///
+    /// ```
+    /// use mantra_lancer::{app::AppData, config::Theme, CursoredString};
+    /// use ratatui::{backend::TestBackend, Terminal};
+    ///
+    /// let mut name = CursoredString::from("x".repeat(200));
+    /// name.end();
+    /// let mut terminal = Terminal::new(TestBackend::new(60, 12))?;
+    /// terminal.draw(|frame| AppData::user_login(&name, frame, false, &Theme::default()))?;
+    ///
+    /// let screen = terminal.backend().to_string();
+    /// let shown: usize = screen.lines().map(|line| line.matches('x').count()).sum();
+    /// assert!(shown > 0 && shown < 200);
+    /// // nothing is drawn past the box's right border
+    /// for line in screen.lines() {
+    ///     let outside = line.rfind('│').map_or(line, |border| &line[border..]);
+    ///     assert!(!outside.contains('x'), "{line}");
+    /// }
+    /// // and the cursor, at the end of the name, stays inside the field
+    /// let cursor = terminal.get_cursor_position()?;
+    /// let row: Vec<char> = screen.lines().nth(cursor.y.into()).unwrap().chars().collect();
+    /// let borders: Vec<usize> = (0..row.len()).filter(|&i| row[i] == '│').collect();
+    /// // each line is quoted, which shifts it along by one
+    /// let cursor_x = usize::from(cursor.x) + 1;
+    /// assert_eq!(row[cursor_x - 1], 'x');
+    /// assert!(cursor_x < borders[borders.len() - 2]);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn user_login(
+        username: &CursoredString,
+        frame: &mut Frame,
+        hide_cursor: bool,
+        theme: &Theme,
+    ) {
+        Self::name_prompt(
+            "Login",
+            "Tab to pick a pilot, Ctrl+N to create one",
+            username,
+            frame,
+            hide_cursor,
+            theme,
+        );
+    }
+
+    pub fn new_user(
+        name: &CursoredString,
+        error: Option<&str>,
+        frame: &mut Frame,
+        hide_cursor: bool,
+        theme: &Theme,
+    ) {
+        Self::name_prompt(
+            "New Pilot",
+            error.unwrap_or("Letters, numbers, '-' and '_'"),
+            name,
+            frame,
+            hide_cursor,
+            theme,
+        );
+    }
+
+    /// Renders a box prompting for a username, with a hint along the bottom
+    fn name_prompt(
+        title: &str,
+        hint: &str,
+        username: &CursoredString,
+        frame: &mut Frame,
+        hide_cursor: bool,
+        theme: &Theme,
+    ) {
         const USERNAME_HEIGHT: u16 = 1;
         const BORDER_SIZE: u16 = 1;
 
@@ -400,24 +1527,86 @@ impl AppData {
         let [area] = Layout::horizontal([Constraint::Percentage(40)])
             .flex(Flex::Center)
             .areas(area);
-        let block = Block::bordered().title("Login");
+        let block = Block::bordered()
+            .title(title)
+            .title_bottom(Line::from(hint).dim());
         frame.render_widget(block, area);
         let area = area.inner(Margin::new(BORDER_SIZE, BORDER_SIZE));
         let [username_area] =
             Layout::vertical([Constraint::Length(USERNAME_HEIGHT + BORDER_SIZE * 2)]).areas(area);
 
-        let username_field = Block::bordered()
-            .title("Username")
-            .style(Style::default().bg(Color::LightYellow).fg(Color::Black));
+        let username_field = Block::bordered().title("Username").style(theme.active());
 
-        let username_text = Paragraph::new(username.as_str()).block(username_field);
+        // names longer than the box scroll along with the cursor so it never leaves the box
+        let (visible, cursor) = username.visible(usize::from(
+            username_area.width.saturating_sub(2 * BORDER_SIZE),
+        ));
+        let username_text = Paragraph::new(visible).block(username_field);
         if !hide_cursor {
             frame.set_cursor_position(Position::new(
-                username_area.x + username.index as u16 + 1,
-                username_area.y + 1,
+                username_area.x + cursor as u16 + BORDER_SIZE,
+                username_area.y + BORDER_SIZE,
             ));
         }
 
         frame.render_widget(username_text, username_area);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::KeyEvent;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    use super::*;
+    use crate::testing::{lock_config, temp_storage, test_home};
+
+    fn press(c: char) -> Event {
+        Event::Key(KeyEvent::from(KeyCode::Char(c)))
+    }
+
+    #[async_std::test]
+    async fn delete_with_nothing_to_delete_does_nothing() -> Result<(), Box<dyn std::error::Error>>
+    {
+        let _config = lock_config().await;
+        std::fs::write(
+            test_home().join("config.yaml"),
+            "show_intro: false\nlegacy_apps: []\n",
+        )?;
+        let mut app = App::init_with_username(String::from("pilot"), Some("empty-delete")).await?;
+        let mut terminal = Terminal::new(TestBackend::new(100, 24))?;
+
+        // deleting from an empty table doesn't even ask
+        app.handle_event(&press('d')).await?;
+        terminal.draw(|frame| app.ui(frame))?;
+        let screen = terminal.backend().to_string();
+        assert!(screen.contains("No transactions"));
+        assert!(!screen.contains("Delete it?"));
+
+        // and neither does removing a filter when there are none
+        app.handle_event(&press('f')).await?;
+        app.handle_event(&press('d')).await?;
+        terminal.draw(|frame| app.ui(frame))?;
+        assert!(terminal
+            .backend()
+            .to_string()
+            .contains("Filter Transactions"));
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn username_with_a_pin_still_asks_for_it() -> Result<(), Box<dyn std::error::Error>> {
+        let _config = lock_config().await;
+        std::fs::write(
+            test_home().join("config.yaml"),
+            "show_intro: false\nlegacy_apps: []\n",
+        )?;
+        let (storage, pilot) = temp_storage("username-with-pin").await;
+        storage.set_pin(pilot, Some("1234")).await?;
+
+        let app = App::init_with_username(String::from("pilot"), Some("username-with-pin")).await?;
+        assert!(app.data.current_user.is_none());
+        assert!(matches!(app.data.popup, Some(Popup::EnterPin(_))));
+        Ok(())
+    }
+}
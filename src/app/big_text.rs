@@ -0,0 +1,45 @@
+//! Large block lettering for figures that need to be read from a distance, such as on a stream
+
+/// How many lines tall each glyph is
+pub const HEIGHT: usize = 5;
+
+/// The lines of the given text in large block figures. Digits, signs, separators, and spaces are drawn large,
+/// anything else, such as a currency symbol, is kept as it is in the middle line
+pub fn big_text(text: &str) -> [String; HEIGHT] {
+    let mut lines: [String; HEIGHT] = Default::default();
+    for (i, c) in text.chars().enumerate() {
+        for (row, line) in lines.iter_mut().enumerate() {
+            if i > 0 {
+                line.push(' ');
+            }
+            match glyph(c) {
+                Some(glyph) => line.push_str(glyph[row]),
+                None if row == HEIGHT / 2 => line.push(c),
+                None => line.push(' '),
+            }
+        }
+    }
+    lines
+}
+
+/// Rows of a character's glyph, if it has one
+fn glyph(c: char) -> Option<[&'static str; HEIGHT]> {
+    Some(match c {
+        '0' => [" ███ ", "█   █", "█   █", "█   █", " ███ "],
+        '1' => ["  █  ", " ██  ", "  █  ", "  █  ", " ███ "],
+        '2' => [" ███ ", "█   █", "  ██ ", " █   ", "█████"],
+        '3' => ["████ ", "    █", " ███ ", "    █", "████ "],
+        '4' => ["█   █", "█   █", "█████", "    █", "    █"],
+        '5' => ["█████", "█    ", "████ ", "    █", "████ "],
+        '6' => [" ███ ", "█    ", "████ ", "█   █", " ███ "],
+        '7' => ["█████", "    █", "   █ ", "  █  ", "  █  "],
+        '8' => [" ███ ", "█   █", " ███ ", "█   █", " ███ "],
+        '9' => [" ███ ", "█   █", " ████", "    █", " ███ "],
+        '-' => ["    ", "    ", "████", "    ", "    "],
+        '+' => ["     ", "  █  ", "█████", "  █  ", "     "],
+        '.' => [" ", " ", " ", " ", "█"],
+        ',' => ["  ", "  ", "  ", " █", "█ "],
+        ' ' => ["  ", "  ", "  ", "  ", "  "],
+        _ => return None,
+    })
+}
@@ -1,24 +1,94 @@
 //! Handler for popups
-use crossterm::event::Event;
+use crossterm::event::{Event, MouseButton, MouseEvent, MouseEventKind};
 use enum_dispatch::enum_dispatch;
 use ratatui::prelude::*;
 
 use super::{App, AppError};
+use crate::config::Theme;
 
+mod about;
+pub use about::*;
 mod add_transaction;
 pub use add_transaction::*;
+mod apply_recurring;
+pub use apply_recurring::*;
+mod combine_pilots;
+pub use combine_pilots::*;
+mod confirm_batch_reason;
+pub use confirm_batch_reason::*;
+mod confirm_delete;
+pub use confirm_delete::*;
+mod confirm_logout;
+pub use confirm_logout::*;
+mod confirm_purge;
+pub use confirm_purge::*;
 mod create_user;
 pub use create_user::*;
 mod filter_results;
 pub use filter_results::*;
+mod first_run;
+pub use first_run::*;
+mod help;
+pub use help::*;
+mod merge_user;
+pub use merge_user::*;
+mod migrate_legacy;
+pub use migrate_legacy::*;
+mod notice;
+pub use notice::*;
+mod pick_user;
+pub use pick_user::*;
+mod pin;
+pub use pin::*;
+mod quick_add;
+pub use quick_add::*;
+mod reason_codes;
+pub use reason_codes::*;
+mod set_type;
+pub use set_type::*;
+mod split_transaction;
+pub use split_transaction::*;
+mod summary;
+pub use summary::*;
+mod switch_vault;
+pub use switch_vault::*;
+mod transaction_details;
+pub use transaction_details::*;
+mod transfer;
+pub use transfer::*;
 
 /// Types of popup that can be displayed
 #[enum_dispatch(PopupHandler)]
 pub enum Popup {
+    About,
     AddTransaction,
+    CombinePilots,
+    ConfirmBatchReason,
+    ConfirmDelete,
+    ConfirmEdit,
+    ConfirmLarge,
+    ConfirmLogout,
+    ConfirmPurge,
+    ApplyRecurring,
     CreateUser,
     FilterResults,
+    FirstRun,
     AddFilter,
+    Help,
+    Summary,
+    EnterPin,
+    PickUser,
+    SetPin,
+    SetType,
+    QuickAdd,
+    MergeUser,
+    MigrateLegacy,
+    Notice,
+    ReasonCodes,
+    SplitTransaction,
+    SwitchVault,
+    TransactionDetails,
+    TransferFunds,
 }
 
 #[enum_dispatch]
@@ -27,7 +97,40 @@ pub(crate) trait PopupHandler {
     async fn handle_event(self, app: &mut App, event: &Event) -> Result<Option<Popup>, AppError>;
 
     /// Handles the rendering of the popup to the given [`Frame`]
-    fn render_to_frame(&mut self, area: Rect, frame: &mut Frame)
+    fn render_to_frame(&mut self, area: Rect, frame: &mut Frame, theme: &Theme)
     where
         Self: Sized;
+
+    /// Inner area of the popup's message field as last rendered, if it has one
+    fn message_area(&self) -> Option<Rect> {
+        None
+    }
+}
+
+/// Whether the event is a left click inside the given area
+pub(crate) fn clicked(event: &Event, area: Rect) -> bool {
+    matches!(
+        event,
+        Event::Mouse(MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column,
+            row,
+            ..
+        }) if area.contains(Position::new(*column, *row))
+    )
+}
+
+/// Which of the "No"/"Yes" tabs was clicked, given the bordered area they were rendered to
+pub(crate) fn yes_no_clicked(event: &Event, area: Rect) -> Option<bool> {
+    // tabs are drawn as " No │ Yes " inside the border
+    let inner = area.inner(layout::Margin::new(1, 1));
+    let no = Rect::new(inner.x, inner.y, 4, 1).intersection(inner);
+    let yes = Rect::new(inner.x + 5, inner.y, 5, 1).intersection(inner);
+    if clicked(event, no) {
+        Some(false)
+    } else if clicked(event, yes) {
+        Some(true)
+    } else {
+        None
+    }
 }
@@ -0,0 +1,118 @@
+use crossterm::event::{self, Event, KeyCode};
+use ratatui::{
+    layout::{Constraint, Flex, Layout},
+    prelude::*,
+    widgets::{Block, Clear, Paragraph},
+    Frame,
+};
+
+use crate::{
+    app::{App, AppError},
+    config::Theme,
+    storage::Storage,
+};
+
+use super::{Popup, PopupHandler};
+
+/// Popup showing which version is running, for bug reports and for noticing an update
+pub struct About {
+    /// Version that ran last, if it was different to this one
+    updated_from: Option<String>,
+    vault: Option<String>,
+    then: Option<Box<Popup>>,
+}
+
+impl About {
+    pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+    /// Create popup for the given storage's vault
+    pub fn new(storage: &Storage) -> Self {
+        Self {
+            updated_from: None,
+            vault: storage.vault().map(str::to_owned),
+            then: None,
+        }
+    }
+
+    /// Records this version as the last one run, returning the version recorded before it, if any.
+    /// Failing to record the version only means it's treated as new again next time
+    pub fn record_version() -> Option<String> {
+        let path = crate::dirs::app_dirs()
+            .place_state_file("last_version")
+            .ok()?;
+        let last = std::fs::read_to_string(&path)
+            .ok()
+            .map(|last| last.trim().to_owned());
+        if last.as_deref() != Some(Self::VERSION) {
+            let _ = std::fs::write(&path, Self::VERSION);
+        }
+        last
+    }
+
+    /// The popup to show once if the given last version run, from [`About::record_version`], isn't this one.
+    /// Nothing is shown on a fresh install
+    pub fn after_update(storage: &Storage, last_version: Option<&str>) -> Option<Self> {
+        let last = last_version.filter(|last| *last != Self::VERSION)?;
+        Some(Self {
+            updated_from: Some(last.to_owned()),
+            ..Self::new(storage)
+        })
+    }
+
+    /// Shows the given popup once this one is closed
+    pub fn then(mut self, popup: Option<Popup>) -> Self {
+        self.then = popup.map(Box::new);
+        self
+    }
+}
+
+impl PopupHandler for About {
+    async fn handle_event(self, _app: &mut App, event: &Event) -> Result<Option<Popup>, AppError> {
+        if let Event::Key(key) = event {
+            if key.kind == event::KeyEventKind::Press {
+                if let KeyCode::Enter | KeyCode::Esc = key.code {
+                    return Ok(self.then.map(|popup| *popup));
+                }
+            }
+        }
+        Ok(Some(Popup::About(self)))
+    }
+
+    fn render_to_frame(&mut self, area: Rect, frame: &mut Frame, _theme: &Theme) {
+        const TEXT_HEIGHT: u16 = 5;
+        const BORDER_SIZE: u16 = 1;
+
+        let [area] = Layout::vertical([Constraint::Length(TEXT_HEIGHT + 2 * BORDER_SIZE)])
+            .flex(Flex::Center)
+            .areas(area);
+        let [area] = Layout::horizontal([Constraint::Percentage(40)])
+            .flex(Flex::Center)
+            .areas(area);
+        let block = Block::bordered()
+            .title("About")
+            .title_bottom(Line::from("Enter to close").dim().right_aligned());
+
+        let updated = match &self.updated_from {
+            Some(last) => Line::from(format!("Updated from v{last} since the last run"))
+                .black()
+                .on_light_yellow(),
+            None => Line::default(),
+        };
+        let text = vec![
+            Line::from(format!("MAN/TRA v{}", Self::VERSION)).bold(),
+            updated,
+            Line::from(format!(
+                "Vault: {}",
+                self.vault.as_deref().unwrap_or("default")
+            )),
+            Line::from(format!("Files: {}", crate::dirs::app_dirs())),
+            Line::from(format!(
+                "Report bugs at {}/issues",
+                env!("CARGO_PKG_REPOSITORY")
+            ))
+            .dim(),
+        ];
+        frame.render_widget(Clear, area);
+        frame.render_widget(Paragraph::new(text).block(block), area);
+    }
+}
@@ -1,29 +1,83 @@
-use crossterm::event::{self, Event, KeyCode};
+use crossterm::event::{self, Event, KeyCode, KeyModifiers};
 use num_derive::FromPrimitive;
 use num_traits::FromPrimitive;
 use ratatui::{
     layout::Flex,
     prelude::{Rect, *},
     widgets::{Block, Clear, Paragraph, Tabs, Wrap},
+    Frame,
 };
 use strum::{EnumCount, VariantNames};
-use text::ToText;
 
-use crate::CursoredString;
 use crate::{
     app::{App, AppError},
-    storage::TransactionType,
+    config::{AmountSteps, Config, Theme},
+    format::AmountFormatter,
+    storage::{self, Cadence, ReasonCode, Transaction, TransactionType},
 };
+use crate::{parse_amount, AmountParseError, CursoredString};
 
-use super::{Popup, PopupHandler};
+use super::{clicked, yes_no_clicked, Popup, PopupHandler};
 
 /// Handles the creation of new transactions
 #[derive(Default)]
 pub struct AddTransaction {
     pub trans_type: TransactionType,
     pub amount: i32,
+    /// Amount typed by the user, replacing `amount` once it's parsed
+    pub amount_input: CursoredString,
+    pub amount_error: Option<AmountParseError>,
     pub msg: CursoredString,
+    /// Set when submitting without a message for a type that requires one
+    pub msg_missing: bool,
+    /// Recent messages starting with what's been typed, most recent first, if autocomplete is enabled
+    pub suggestions: Vec<String>,
+    /// Id of the chosen reason code
+    pub reason: Option<i32>,
+    /// Reason codes to pick from
+    pub reasons: Vec<ReasonCode>,
+    pub repeat: Option<Cadence>,
+    /// Only set when entering amounts as credit/debit, in which case `amount` is the magnitude
+    pub direction: Option<Direction>,
     pub selected_field: AddTransactionField,
+    pub amount_format: AmountFormatter,
+    pub amount_steps: AmountSteps,
+    /// Inner area of the message field as last rendered
+    pub msg_area: Rect,
+    /// Area of the submit button as last rendered, for mouse clicks
+    pub submit_area: Rect,
+    /// Id of the transaction being edited, if not creating a new one
+    pub editing: Option<i32>,
+    /// Stay open after submitting, cleared for the next entry
+    pub keep_open: bool,
+    /// How many transactions have been added since the popup was opened
+    pub added: u32,
+}
+
+/// Popup showing how editing a transaction changes it, only saving once confirmed
+pub struct ConfirmEdit {
+    editor: AddTransaction,
+    trans_id: i32,
+    /// The name, old value, and new value of each field that changed
+    changes: Vec<(&'static str, String, String)>,
+    should_update: bool,
+    /// Area of the Yes/No choice as last rendered, for mouse clicks
+    question_area: Rect,
+}
+
+/// Popup asking before adding a transaction larger than `large_transaction_threshold`, in case of a mistyped amount
+pub struct ConfirmLarge {
+    editor: AddTransaction,
+    should_add: bool,
+    /// Area of the Yes/No choice as last rendered, for mouse clicks
+    question_area: Rect,
+}
+
+/// Whether a transaction adds to or takes from the balance
+#[derive(Clone, Copy, PartialEq, Eq, VariantNames)]
+pub enum Direction {
+    Credit,
+    Debit,
 }
 
 /// Selectable fields for [`AddTransaction`]
@@ -32,8 +86,11 @@ pub struct AddTransaction {
 pub enum AddTransactionField {
     #[default]
     TransactionType = 0,
+    Reason,
+    Direction,
     Amount,
     Message,
+    Repeat,
     Submit,
 }
 
@@ -57,156 +114,573 @@ impl AddTransactionField {
     }
 }
 
+impl AddTransaction {
+    /// Creates an empty transaction, using credit/debit entry if the config asks for it
+    pub fn new(config: &Config) -> Self {
+        Self {
+            direction: config.credit_debit.then_some(Direction::Credit),
+            amount_format: config.amount_format.clone(),
+            amount_steps: config.amount_steps.clone(),
+            keep_open: config.keep_add_open,
+            msg: CursoredString::new().with_max_len(config.max_message_len),
+            ..Default::default()
+        }
+    }
+
+    /// Opens an existing transaction for editing, entering its amount as the config asks
+    pub fn edit(transaction: &Transaction, config: &Config) -> Self {
+        let mut popup = Self {
+            trans_type: transaction.transaction_type,
+            amount: transaction.value,
+            msg: CursoredString::from(transaction.msg.clone()).with_max_len(config.max_message_len),
+            reason: transaction.reason_id,
+            editing: Some(transaction.trans_id),
+            ..Self::new(config)
+        };
+        if popup.direction.is_some() && transaction.value < 0 {
+            popup.amount = transaction.value.saturating_neg();
+            popup.direction = Some(Direction::Debit);
+        }
+        popup
+    }
+
+    /// Sets the reason codes that can be picked from
+    pub fn with_reasons(mut self, reasons: Vec<ReasonCode>) -> Self {
+        self.reasons = reasons;
+        self
+    }
+
+    /// Whether a field is hidden, being the direction when entering signed amounts and the repeat when editing
+    fn hides(&self, field: AddTransactionField) -> bool {
+        match field {
+            AddTransactionField::Reason => self.reasons.is_empty(),
+            AddTransactionField::Direction => self.direction.is_none(),
+            AddTransactionField::Repeat => self.editing.is_some(),
+            _ => false,
+        }
+    }
+
+    /// Switch the selected field to the next one, skipping hidden fields
+    fn next_field(&mut self) {
+        self.selected_field.next();
+        while self.hides(self.selected_field) {
+            self.selected_field.next();
+        }
+    }
+
+    /// Switch the selected field to the previous one, skipping hidden fields
+    fn prev_field(&mut self) {
+        self.selected_field.prev();
+        while self.hides(self.selected_field) {
+            self.selected_field.prev();
+        }
+    }
+
+    /// Flips the sign of the amount, or swaps between credit and debit
+    fn flip_sign(&mut self) {
+        match self.direction {
+            Some(Direction::Credit) => self.direction = Some(Direction::Debit),
+            Some(Direction::Debit) => self.direction = Some(Direction::Credit),
+            None => self.amount = self.amount.saturating_neg(),
+        }
+    }
+
+    /// Adjusts the amount, keeping it as a positive magnitude when entering credit/debit
+    fn step_amount(&mut self, step: i32) {
+        self.amount = self.amount.saturating_add(step);
+        if self.direction.is_some() {
+            self.amount = self.amount.max(0);
+        }
+    }
+
+    /// Parses any typed amount into `amount`, returning false and keeping the input if it's invalid
+    fn commit_amount(&mut self) -> bool {
+        if self.amount_input.is_empty() {
+            return true;
+        }
+        match parse_amount(&self.amount_input) {
+            Ok(amount) => {
+                self.amount = amount;
+                if self.direction.is_some() && amount < 0 {
+                    self.amount = amount.saturating_neg();
+                    self.direction = Some(Direction::Debit);
+                }
+                self.amount_input = CursoredString::new();
+                self.amount_error = None;
+                true
+            }
+            Err(error) => {
+                self.amount_error = Some(error);
+                false
+            }
+        }
+    }
+
+    /// Whether the amount field is selected and being typed into
+    fn typing_amount(&self) -> bool {
+        self.selected_field == AddTransactionField::Amount && !self.amount_input.is_empty()
+    }
+
+    /// The signed value to store for this transaction
+    fn value(&self) -> i32 {
+        match self.direction {
+            Some(Direction::Debit) => -self.amount,
+            _ => self.amount,
+        }
+    }
+
+    /// Adds the transaction, and its recurring rule if it repeats, closing the popup.
+    /// When editing, this instead shows what would change before anything is saved, and large amounts are asked about first
+    async fn submit(mut self, app: &mut App) -> Result<Option<Popup>, AppError> {
+        if self.msg.trim().is_empty() && app.data.config.require_message.contains(&self.trans_type)
+        {
+            self.msg_missing = true;
+            self.selected_field = AddTransactionField::Message;
+            return Ok(Some(Popup::AddTransaction(self)));
+        }
+        if let Some(trans_id) = self.editing {
+            // compare against what's stored now rather than what the table showed
+            let before = app.data.storage.get_transaction(trans_id).await?;
+            let confirm = ConfirmEdit::new(self, &before);
+            if confirm.changes.is_empty() {
+                app.data.status_text = String::from("No changes to save");
+                return Ok(None);
+            }
+            return Ok(Some(Popup::ConfirmEdit(confirm)));
+        }
+        let threshold = app.data.config.large_transaction_threshold;
+        if !app.data.config.fast_mode
+            && threshold.is_some_and(|threshold| self.value().unsigned_abs() > threshold)
+        {
+            return Ok(Some(Popup::ConfirmLarge(ConfirmLarge {
+                editor: self,
+                should_add: false,
+                question_area: Rect::default(),
+            })));
+        }
+        self.insert(app).await
+    }
+
+    /// Adds the transaction, and its recurring rule if it repeats, without any more checks.
+    /// Closes the popup unless it's kept open, in which case it's cleared for the next entry
+    async fn insert(mut self, app: &mut App) -> Result<Option<Popup>, AppError> {
+        let amount = self.value();
+        let user_id = app.data.current_user.as_ref().map(|v| v.get_id()).unwrap();
+        let now = storage::now_utc();
+        let trans_id = app
+            .data
+            .storage
+            .add_transaction_at(user_id, amount, self.trans_type, &self.msg.buf, now)
+            .await?;
+        if self.reason.is_some() {
+            app.data
+                .storage
+                .set_reason(&[trans_id], self.reason)
+                .await?;
+        }
+        app.data.tag_session(trans_id).await?;
+
+        app.data.status_text = match self.repeat {
+            Some(cadence) => {
+                app.data
+                    .storage
+                    .add_recurring_rule(
+                        user_id,
+                        amount,
+                        self.trans_type,
+                        &self.msg.buf,
+                        cadence,
+                        now,
+                    )
+                    .await?;
+                format!("Added transaction, repeating {cadence}")
+            }
+            None => String::from("Added transaction"),
+        };
+        app.data.update_table().await?;
+        if self.keep_open {
+            self.added += 1;
+            self.clear_entry();
+            return Ok(Some(Popup::AddTransaction(self)));
+        }
+        Ok(None)
+    }
+
+    /// Empties the amount and message for the next entry, keeping the type, reason, and direction.
+    /// The repeat is cleared too, so a recurring rule isn't added again by accident
+    fn clear_entry(&mut self) {
+        self.amount = 0;
+        self.amount_input = CursoredString::new();
+        self.amount_error = None;
+        self.msg = CursoredString::new().with_max_len(self.msg.max_len);
+        self.msg_missing = false;
+        self.suggestions.clear();
+        self.repeat = None;
+        self.selected_field = AddTransactionField::Amount;
+    }
+
+    /// Looks up recent messages starting with the typed message
+    async fn update_suggestions(&mut self, app: &App) -> Result<(), AppError> {
+        const SUGGESTION_LIMIT: u32 = 3;
+
+        self.suggestions.clear();
+        if self.msg.is_empty() {
+            return Ok(());
+        }
+        let user_id = app.data.current_user.as_ref().map(|v| v.get_id()).unwrap();
+        self.suggestions = app
+            .data
+            .storage
+            .recent_messages(user_id, &self.msg, SUGGESTION_LIMIT)
+            .await?;
+        self.suggestions
+            .retain(|suggestion| suggestion.as_str() != self.msg.as_str());
+        Ok(())
+    }
+
+    /// Replaces the message with the most recent suggestion, leaving the cursor at the end
+    fn accept_suggestion(&mut self) {
+        if let Some(suggestion) = self.suggestions.first() {
+            // taken whole even past the limit, as it was saved before the limit was set
+            self.msg = CursoredString::from(suggestion.clone()).with_max_len(self.msg.max_len);
+            self.msg.end();
+            self.msg_missing = false;
+            self.suggestions.clear();
+        }
+    }
+
+    /// Steps through the reason codes, with no reason coming before all of them
+    fn cycle_reason(&mut self, step: i32) {
+        let index = self
+            .reason
+            .and_then(|id| self.reasons.iter().position(|reason| reason.id == id))
+            .map_or(0, |index| index as i32 + 1);
+        let index = (index + step).rem_euclid(self.reasons.len() as i32 + 1);
+        self.reason = (index > 0).then(|| self.reasons[index as usize - 1].id);
+    }
+
+    /// The name of the chosen reason code
+    fn reason_name(&self) -> Option<&str> {
+        let id = self.reason?;
+        self.reasons
+            .iter()
+            .find(|reason| reason.id == id)
+            .map(|reason| reason.name.as_str())
+    }
+
+    /// Steps through the repeat options, with `None` coming before every [`Cadence`]
+    fn cycle_repeat(&mut self, step: i32) {
+        let index = self.repeat.map_or(0, |cadence| cadence as i32 + 1);
+        let index = (index + step).rem_euclid(<Cadence as EnumCount>::COUNT as i32 + 1);
+        self.repeat = Cadence::from_repr(index - 1);
+    }
+}
+
 impl PopupHandler for AddTransaction {
     async fn handle_event(
         mut self,
         app: &mut App,
         event: &Event,
     ) -> Result<Option<Popup>, AppError> {
+        if clicked(event, self.submit_area) {
+            if !self.commit_amount() {
+                self.selected_field = AddTransactionField::Amount;
+                return Ok(Some(Popup::AddTransaction(self)));
+            }
+            return self.submit(app).await;
+        }
         if let Event::Key(key) = event {
             if key.kind == event::KeyEventKind::Press {
-                match key.code {
+                // letters are typed into the message and amount rather than used to navigate
+                let code = match self.selected_field {
+                    AddTransactionField::Message => key.code,
+                    _ if self.typing_amount() => key.code,
+                    _ => app.data.config.nav_key(key.code),
+                };
+                // the typed amount must be valid before anything else is done with it
+                let uses_amount = matches!(
+                    code,
+                    KeyCode::Up
+                        | KeyCode::Down
+                        | KeyCode::Left
+                        | KeyCode::Right
+                        | KeyCode::Enter
+                        | KeyCode::F(2)
+                        | KeyCode::Char('-')
+                );
+                if uses_amount
+                    && self.selected_field == AddTransactionField::Amount
+                    && !self.commit_amount()
+                {
+                    return Ok(Some(Popup::AddTransaction(self)));
+                }
+                match code {
                     KeyCode::Up => {
-                        self.selected_field.prev();
+                        self.prev_field();
                     }
                     KeyCode::Down => {
-                        self.selected_field.next();
+                        self.next_field();
                     }
                     KeyCode::Left => match self.selected_field {
                         AddTransactionField::Amount => {
-                            self.amount -= crate::value_from_modifiers(key.modifiers);
+                            self.step_amount(-self.amount_steps.step(key.modifiers));
                         }
+                        AddTransactionField::Direction => self.flip_sign(),
                         AddTransactionField::Message => self.msg.right(),
                         AddTransactionField::TransactionType => {
                             self.trans_type = self.trans_type.prev()
                         }
+                        AddTransactionField::Reason => self.cycle_reason(-1),
+                        AddTransactionField::Repeat => self.cycle_repeat(-1),
                         _ => (),
                     },
                     KeyCode::Right => match self.selected_field {
                         AddTransactionField::Amount => {
-                            self.amount += crate::value_from_modifiers(key.modifiers);
+                            self.step_amount(self.amount_steps.step(key.modifiers));
                         }
+                        AddTransactionField::Direction => self.flip_sign(),
                         AddTransactionField::Message => self.msg.left(),
                         AddTransactionField::TransactionType => {
                             self.trans_type = self.trans_type.next()
                         }
+                        AddTransactionField::Reason => self.cycle_reason(1),
+                        AddTransactionField::Repeat => self.cycle_repeat(1),
                         _ => (),
                     },
                     KeyCode::Enter => match self.selected_field {
-                        AddTransactionField::Submit => {
-                            let AddTransaction {
-                                trans_type,
-                                amount,
-                                msg,
-                                ..
-                            } = self;
-                            app.data
-                                .storage
-                                .add_transaction(
-                                    app.data.current_user.as_ref().map(|v| v.get_id()).unwrap(),
-                                    amount,
-                                    trans_type,
-                                    &msg.buf,
-                                )
-                                .await?;
-
-                            app.data.status_text = String::from("Added transaction");
-                            app.data.update_table().await?;
-                            return Ok(None);
-                        }
-                        _ => self.selected_field.next(),
+                        AddTransactionField::Submit => return self.submit(app).await,
+                        _ => self.next_field(),
+                    },
+                    KeyCode::Backspace => match self.selected_field {
+                        AddTransactionField::Message => self.msg.remove_behind(),
+                        AddTransactionField::Amount => self.amount_input.remove_behind(),
+                        _ => (),
                     },
-                    KeyCode::Backspace => {
+                    KeyCode::Delete => match self.selected_field {
+                        AddTransactionField::Message => self.msg.remove_ahead(),
+                        AddTransactionField::Amount => self.amount_input.remove_ahead(),
+                        _ => (),
+                    },
+                    KeyCode::Insert => {
                         if let AddTransactionField::Message = self.selected_field {
-                            self.msg.remove_behind()
+                            self.msg.inserting = !self.msg.inserting
                         }
                     }
-                    KeyCode::Delete => {
+                    KeyCode::Home => {
                         if let AddTransactionField::Message = self.selected_field {
-                            self.msg.remove_ahead()
+                            self.msg.home()
                         }
                     }
-                    KeyCode::Insert => {
+                    KeyCode::End => {
                         if let AddTransactionField::Message = self.selected_field {
-                            self.msg.inserting = !self.msg.inserting
+                            self.msg.end()
                         }
                     }
-                    KeyCode::Esc => return Ok(None),
-                    KeyCode::Char(c) => {
+                    KeyCode::Tab => {
                         if let AddTransactionField::Message = self.selected_field {
-                            self.msg.insert(c)
+                            self.accept_suggestion()
+                        }
+                    }
+                    KeyCode::Esc => return Ok(None),
+                    KeyCode::F(2) => {
+                        if let AddTransactionField::Amount = self.selected_field {
+                            self.flip_sign()
                         }
                     }
+                    KeyCode::F(3) if self.editing.is_none() => self.keep_open = !self.keep_open,
+                    KeyCode::Char('z')
+                        if self.selected_field == AddTransactionField::Message
+                            && key.modifiers.contains(KeyModifiers::CONTROL) =>
+                    {
+                        self.msg.undo()
+                    }
+                    KeyCode::Char('y')
+                        if self.selected_field == AddTransactionField::Message
+                            && key.modifiers.contains(KeyModifiers::CONTROL) =>
+                    {
+                        self.msg.redo()
+                    }
+                    KeyCode::Char('k')
+                        if self.selected_field == AddTransactionField::Message
+                            && key.modifiers.contains(KeyModifiers::CONTROL) =>
+                    {
+                        self.msg.kill_to_end()
+                    }
+                    KeyCode::Char('u')
+                        if self.selected_field == AddTransactionField::Message
+                            && key.modifiers.contains(KeyModifiers::CONTROL) =>
+                    {
+                        self.msg.clear()
+                    }
+                    KeyCode::Char(c) => match self.selected_field {
+                        AddTransactionField::Message => {
+                            self.msg_missing = false;
+                            if !self.msg.insert(c) {
+                                if let Some(max_len) = self.msg.max_len {
+                                    app.data.status_text =
+                                        format!("Messages can be at most {max_len} characters");
+                                }
+                            }
+                        }
+                        AddTransactionField::Amount if c == '-' => self.flip_sign(),
+                        AddTransactionField::Amount if !c.is_whitespace() => {
+                            self.amount_error = None;
+                            self.amount_input.insert(c);
+                        }
+                        _ => (),
+                    },
                     _ => (),
                 }
+                if self.selected_field == AddTransactionField::Message
+                    && app.data.config.autocomplete
+                {
+                    self.update_suggestions(app).await?;
+                }
             }
         }
         Ok(Some(Popup::AddTransaction(self)))
     }
 
-    fn render_to_frame(&mut self, area: Rect, frame: &mut Frame)
+    fn render_to_frame(&mut self, area: Rect, frame: &mut Frame, theme: &Theme)
     where
         Self: Sized,
     {
+        let reason_text = format!("< {} >", self.reason_name().unwrap_or("None"));
         let Self {
             trans_type,
             amount,
+            amount_input,
+            amount_error,
             msg,
+            msg_missing,
+            suggestions,
+            reason: _,
+            reasons,
+            repeat,
+            direction,
             selected_field,
+            amount_format,
+            amount_steps,
+            msg_area: cached_msg_area,
+            submit_area: cached_submit_area,
+            editing,
+            keep_open,
+            added,
         } = self;
 
         const TYPE_HEIGHT: u16 = 1;
+        const REASON_HEIGHT: u16 = 1;
         const AMOUNT_HEIGHT: u16 = 1;
         const MSG_HEIGHT: u16 = 3;
+        const REPEAT_HEIGHT: u16 = 1;
         const SUBMIT_HEIGHT: u16 = 1;
         const BORDER_SIZE: u16 = 1;
         const SUBMIT_TEXT: &str = "Submit";
+        // nothing to pick a reason from without any reason codes
+        let reason_height = if reasons.is_empty() {
+            0
+        } else {
+            REASON_HEIGHT + BORDER_SIZE * 2
+        };
+        // the direction field is hidden unless entering credit/debit
+        let direction_height = if direction.is_some() {
+            1 + BORDER_SIZE * 2
+        } else {
+            0
+        };
+        // repeating only applies to new transactions
+        let repeat_height = if editing.is_none() {
+            REPEAT_HEIGHT + BORDER_SIZE * 2
+        } else {
+            0
+        };
 
         let [area] = Layout::vertical([Constraint::Length(
-            TYPE_HEIGHT + AMOUNT_HEIGHT + MSG_HEIGHT + 10 * BORDER_SIZE,
+            TYPE_HEIGHT
+                + reason_height
+                + direction_height
+                + AMOUNT_HEIGHT
+                + MSG_HEIGHT
+                + repeat_height
+                + SUBMIT_HEIGHT
+                + 10 * BORDER_SIZE,
         )])
         .flex(Flex::Center)
         .areas(area);
         let [area] = Layout::horizontal([Constraint::Percentage(40)])
             .flex(Flex::Center)
             .areas(area);
-        let block = Block::bordered().title("Add Transaction");
+        let mut block = Block::bordered().title(if editing.is_some() {
+            "Edit Transaction"
+        } else {
+            "Add Transaction"
+        });
+        // keeping open only applies to adding, an edit always closes once saved
+        if editing.is_none() {
+            if *keep_open {
+                block = block
+                    .title(Line::from(format!("{added} added")).right_aligned())
+                    .title_bottom(Line::from("F3: close after submitting").dim());
+            } else {
+                block = block.title_bottom(Line::from("F3: keep open after submitting").dim());
+            }
+        }
         frame.render_widget(Clear, area);
         frame.render_widget(block, area);
         let area = area.inner(Margin::new(BORDER_SIZE, BORDER_SIZE));
-        let [type_area, amount_area, msg_area, submit_area] = Layout::vertical([
-            Constraint::Length(TYPE_HEIGHT + BORDER_SIZE * 2),
-            Constraint::Length(AMOUNT_HEIGHT + BORDER_SIZE * 2),
-            Constraint::Length(MSG_HEIGHT + BORDER_SIZE * 2),
-            Constraint::Length(SUBMIT_HEIGHT + BORDER_SIZE * 2),
-        ])
-        .areas(area);
+        let [type_area, reason_area, direction_area, amount_area, msg_area, repeat_area, submit_area] =
+            Layout::vertical([
+                Constraint::Length(TYPE_HEIGHT + BORDER_SIZE * 2),
+                Constraint::Length(reason_height),
+                Constraint::Length(direction_height),
+                Constraint::Length(AMOUNT_HEIGHT + BORDER_SIZE * 2),
+                Constraint::Length(MSG_HEIGHT + BORDER_SIZE * 2),
+                Constraint::Length(repeat_height),
+                Constraint::Length(SUBMIT_HEIGHT + BORDER_SIZE * 2),
+            ])
+            .areas(area);
+
+        *cached_msg_area = msg_area.inner(Margin::new(BORDER_SIZE, BORDER_SIZE));
 
         let mut type_field = Block::bordered().title("Type");
+        let mut reason_field = Block::bordered().title("Reason");
+        let mut direction_field = Block::bordered().title("Direction");
         let mut amount_field = Block::bordered().title("Amount");
         let mut msg_field = Block::bordered().title("Message");
+        let mut repeat_field = Block::bordered().title("Repeat");
         let mut submit_field = Block::bordered();
 
-        let active_style = Style::default().bg(Color::LightYellow).fg(Color::Black);
+        let active_style = theme.active();
 
         {
             use AddTransactionField::*;
             match selected_field {
                 TransactionType => type_field = type_field.style(active_style),
-                Amount => amount_field = amount_field.style(active_style),
+                Reason => reason_field = reason_field.style(active_style),
+                Direction => direction_field = direction_field.style(active_style),
+                Amount => {
+                    // the modifiers aren't shown anywhere else, so list what each one steps by
+                    amount_field = amount_field
+                        .style(active_style)
+                        .title_bottom(Line::from(amount_steps.hint()).dim());
+                    if !amount_input.is_empty() {
+                        frame.set_cursor_position(Position::new(
+                            amount_area.x + amount_input.cursor_index() as u16 + 1,
+                            amount_area.y + 1,
+                        ));
+                    }
+                }
                 Message => {
                     msg_field = msg_field.style(active_style);
-                    let inner_area = msg_area.inner(Margin {
-                        horizontal: 1,
-                        vertical: 1,
-                    });
-                    let mapped_index = (msg.cursor_index() as u16)
+                    let inner_area = *cached_msg_area;
+                    let mapped_index = (msg.cursor_index().min(msg.char_len()) as u16)
                         .clamp(0, inner_area.width * inner_area.height - 1);
                     frame.set_cursor_position(Position::new(
                         msg_area.x + mapped_index % inner_area.width + 1,
                         msg_area.y + mapped_index / inner_area.width + 1,
                     ));
                 }
+                Repeat => repeat_field = repeat_field.style(active_style),
                 Submit => submit_field = submit_field.style(active_style),
             };
         }
@@ -214,24 +688,270 @@ impl PopupHandler for AddTransaction {
         let type_text = Tabs::new(<TransactionType as VariantNames>::VARIANTS.iter().copied())
             .select(*trans_type as usize)
             .block(type_field);
-        let amount_text = Paragraph::new(amount.to_text()).block(amount_field);
+        let direction_text = Tabs::new(<Direction as VariantNames>::VARIANTS.iter().copied())
+            .select(direction.map_or(0, |direction| direction as usize))
+            .block(direction_field);
+        if let Some(error) = amount_error {
+            amount_field = amount_field.title(Line::from(error.to_string()).red());
+        }
+        let amount_text = if amount_input.is_empty() {
+            Paragraph::new(amount_format.format(*amount))
+        } else {
+            Paragraph::new(amount_input.as_str())
+        }
+        .block(amount_field);
+        if *msg_missing {
+            msg_field = msg_field.title(Line::from(format!("Required for {trans_type}")).red());
+        }
+        if *selected_field == AddTransactionField::Message && !suggestions.is_empty() {
+            msg_field = msg_field
+                .title_bottom(Line::from(format!("Tab: {}", suggestions.join(" | "))).dim());
+        }
         let msg_text = Paragraph::new(msg.as_str())
             .wrap(Wrap { trim: false })
             .block(msg_field);
+        let repeat_text = Tabs::new(
+            ["Never"]
+                .into_iter()
+                .chain(<Cadence as VariantNames>::VARIANTS.iter().copied()),
+        )
+        .select(repeat.map_or(0, |cadence| cadence as usize + 1))
+        .block(repeat_field);
         let submit_text = Paragraph::new(SUBMIT_TEXT)
             .block(submit_field)
             .alignment(Alignment::Center);
 
         frame.render_widget(type_text, type_area);
+        if !reasons.is_empty() {
+            frame.render_widget(
+                Paragraph::new(reason_text)
+                    .alignment(Alignment::Center)
+                    .block(reason_field),
+                reason_area,
+            );
+        }
+        if direction.is_some() {
+            frame.render_widget(direction_text, direction_area);
+        }
         frame.render_widget(amount_text, amount_area);
         frame.render_widget(msg_text, msg_area);
-        frame.render_widget(
-            submit_text,
-            Layout::horizontal([Constraint::Length(
-                SUBMIT_TEXT.len() as u16 + BORDER_SIZE * 2,
-            )])
+        if editing.is_none() {
+            frame.render_widget(repeat_text, repeat_area);
+        }
+        *cached_submit_area = Layout::horizontal([Constraint::Length(
+            SUBMIT_TEXT.len() as u16 + BORDER_SIZE * 2,
+        )])
+        .flex(Flex::Center)
+        .areas::<1>(submit_area)[0];
+        frame.render_widget(submit_text, *cached_submit_area)
+    }
+    fn message_area(&self) -> Option<Rect> {
+        Some(self.msg_area)
+    }
+}
+
+impl ConfirmEdit {
+    /// Create popup comparing the edited fields against the transaction as it's currently stored
+    fn new(editor: AddTransaction, before: &Transaction) -> Self {
+        let mut changes = Vec::new();
+        if editor.trans_type != before.transaction_type {
+            changes.push((
+                "Type",
+                before.transaction_type.to_string(),
+                editor.trans_type.to_string(),
+            ));
+        }
+        if editor.value() != before.value {
+            changes.push((
+                "Amount",
+                editor.amount_format.format(before.value),
+                editor.amount_format.format(editor.value()),
+            ));
+        }
+        if editor.msg.as_str() != before.msg {
+            changes.push(("Message", before.msg.clone(), editor.msg.to_string()));
+        }
+        if editor.reason != before.reason_id {
+            let name = |id: Option<i32>| {
+                id.and_then(|id| editor.reasons.iter().find(|reason| reason.id == id))
+                    .map_or(String::from("None"), |reason| reason.name.clone())
+            };
+            changes.push(("Reason", name(before.reason_id), name(editor.reason)));
+        }
+        Self {
+            editor,
+            trans_id: before.trans_id,
+            changes,
+            should_update: true,
+            question_area: Rect::default(),
+        }
+    }
+}
+
+impl PopupHandler for ConfirmEdit {
+    async fn handle_event(
+        mut self,
+        app: &mut App,
+        event: &Event,
+    ) -> Result<Option<Popup>, AppError> {
+        let mut confirmed = false;
+        if let Some(choice) = yes_no_clicked(event, self.question_area) {
+            self.should_update = choice;
+            confirmed = true;
+        } else if let Event::Key(key) = event {
+            if key.kind == event::KeyEventKind::Press {
+                match app.data.config.nav_key(key.code) {
+                    KeyCode::Left | KeyCode::BackTab | KeyCode::Right | KeyCode::Tab => {
+                        self.should_update = !self.should_update;
+                    }
+                    KeyCode::Enter => confirmed = true,
+                    // back to editing rather than losing the changes
+                    KeyCode::Esc => return Ok(Some(Popup::AddTransaction(self.editor))),
+                    _ => (),
+                }
+            }
+        }
+        if confirmed {
+            if !self.should_update {
+                return Ok(Some(Popup::AddTransaction(self.editor)));
+            }
+            app.data
+                .storage
+                .update_transaction(
+                    self.trans_id,
+                    self.editor.value(),
+                    self.editor.trans_type,
+                    &self.editor.msg,
+                )
+                .await?;
+            app.data
+                .storage
+                .set_reason(&[self.trans_id], self.editor.reason)
+                .await?;
+            app.data.status_text = String::from("Updated transaction");
+            app.data.update_table().await?;
+            return Ok(None);
+        }
+        Ok(Some(Popup::ConfirmEdit(self)))
+    }
+
+    fn render_to_frame(&mut self, area: Rect, frame: &mut Frame, theme: &Theme) {
+        const QUESTION_HEIGHT: u16 = 1;
+        const BORDER_SIZE: u16 = 1;
+        let changes_height = self.changes.len() as u16;
+
+        let [area] = Layout::vertical([Constraint::Length(
+            changes_height + QUESTION_HEIGHT + 6 * BORDER_SIZE,
+        )])
+        .flex(Flex::Center)
+        .areas(area);
+        let [area] = Layout::horizontal([Constraint::Percentage(40)])
             .flex(Flex::Center)
-            .areas::<1>(submit_area)[0],
-        )
+            .areas(area);
+        let block = Block::bordered().title("Confirm Edit");
+        frame.render_widget(Clear, area);
+        frame.render_widget(block, area);
+        let area = area.inner(Margin::new(BORDER_SIZE, BORDER_SIZE));
+        let [changes_area, question_area] = Layout::vertical([
+            Constraint::Length(changes_height + BORDER_SIZE * 2),
+            Constraint::Length(QUESTION_HEIGHT + BORDER_SIZE * 2),
+        ])
+        .areas(area);
+
+        let changes = self.changes.iter().map(|(field, before, after)| {
+            Line::from_iter([
+                Span::from(format!("{field}: ")),
+                Span::from(before.as_str()).red().crossed_out(),
+                Span::from(" -> "),
+                Span::from(after.as_str()).green(),
+            ])
+        });
+        let changes_text =
+            Paragraph::new(Text::from_iter(changes)).block(Block::bordered().title("Changes"));
+
+        let question_field = Block::bordered()
+            .title("Save changes?")
+            .style(theme.active());
+        let question_text = Tabs::new(["No", "Yes"])
+            .select(self.should_update as usize)
+            .block(question_field);
+
+        frame.render_widget(changes_text, changes_area);
+        frame.render_widget(question_text, question_area);
+        self.question_area = question_area;
+    }
+}
+
+impl PopupHandler for ConfirmLarge {
+    async fn handle_event(
+        mut self,
+        app: &mut App,
+        event: &Event,
+    ) -> Result<Option<Popup>, AppError> {
+        let mut confirmed = false;
+        if let Some(choice) = yes_no_clicked(event, self.question_area) {
+            self.should_add = choice;
+            confirmed = true;
+        } else if let Event::Key(key) = event {
+            if key.kind == event::KeyEventKind::Press {
+                match app.data.config.nav_key(key.code) {
+                    KeyCode::Left | KeyCode::BackTab | KeyCode::Right | KeyCode::Tab => {
+                        self.should_add = !self.should_add;
+                    }
+                    KeyCode::Enter => confirmed = true,
+                    // back to editing so the amount can be fixed
+                    KeyCode::Esc => return Ok(Some(Popup::AddTransaction(self.editor))),
+                    _ => (),
+                }
+            }
+        }
+        if confirmed {
+            if !self.should_add {
+                return Ok(Some(Popup::AddTransaction(self.editor)));
+            }
+            return self.editor.insert(app).await;
+        }
+        Ok(Some(Popup::ConfirmLarge(self)))
+    }
+
+    fn render_to_frame(&mut self, area: Rect, frame: &mut Frame, theme: &Theme) {
+        const WARNING_HEIGHT: u16 = 2;
+        const QUESTION_HEIGHT: u16 = 1;
+        const BORDER_SIZE: u16 = 1;
+
+        let [area] = Layout::vertical([Constraint::Length(
+            WARNING_HEIGHT + QUESTION_HEIGHT + 4 * BORDER_SIZE,
+        )])
+        .flex(Flex::Center)
+        .areas(area);
+        let [area] = Layout::horizontal([Constraint::Percentage(40)])
+            .flex(Flex::Center)
+            .areas(area);
+        let block = Block::bordered().title("Large Transaction");
+        frame.render_widget(Clear, area);
+        frame.render_widget(block, area);
+        let area = area.inner(Margin::new(BORDER_SIZE, BORDER_SIZE));
+        let [warning_area, question_area] = Layout::vertical([
+            Constraint::Length(WARNING_HEIGHT),
+            Constraint::Length(QUESTION_HEIGHT + BORDER_SIZE * 2),
+        ])
+        .areas(area);
+
+        let warning = Paragraph::new(format!(
+            "{} {} is a lot, is the amount right?",
+            self.editor.amount_format.format(self.editor.value()),
+            self.editor.trans_type
+        ))
+        .wrap(Wrap { trim: true })
+        .yellow();
+
+        let question_field = Block::bordered().title("Add it?").style(theme.active());
+        let question_text = Tabs::new(["No", "Yes"])
+            .select(self.should_add as usize)
+            .block(question_field);
+
+        frame.render_widget(warning, warning_area);
+        frame.render_widget(question_text, question_area);
+        self.question_area = question_area;
     }
 }
@@ -0,0 +1,138 @@
+use crossterm::event::{self, Event, KeyCode};
+use ratatui::{
+    layout::{Constraint, Flex, Layout, Margin},
+    prelude::*,
+    widgets::{Block, Clear, Row, Table, TableState, Tabs},
+    Frame,
+};
+use time::UtcOffset;
+
+use crate::{
+    app::{App, AppError},
+    config::{Config, Theme},
+    format::{self, AmountFormatter, Locale},
+    storage::PendingRecurring,
+};
+
+use super::{yes_no_clicked, Popup, PopupHandler};
+
+/// Popup previewing recurring entries that were missed since the last login, offering to insert them all at once
+pub struct ApplyRecurring {
+    pending: Vec<PendingRecurring>,
+    timezone: UtcOffset,
+    amount_format: AmountFormatter,
+    locale: Option<Locale>,
+    table_state: TableState,
+    should_apply: bool,
+    /// Area of the Yes/No choice as last rendered, for mouse clicks
+    question_area: Rect,
+}
+
+impl ApplyRecurring {
+    /// Create popup with 'Yes' selected by default, showing dates and amounts as the config describes
+    pub fn new(pending: Vec<PendingRecurring>, config: &Config) -> Self {
+        Self {
+            pending,
+            timezone: config.timezone,
+            amount_format: config.amount_format.clone(),
+            locale: config.locale,
+            table_state: TableState::default(),
+            should_apply: true,
+            question_area: Rect::default(),
+        }
+    }
+}
+
+impl PopupHandler for ApplyRecurring {
+    async fn handle_event(
+        mut self,
+        app: &mut App,
+        event: &Event,
+    ) -> Result<Option<Popup>, AppError> {
+        let mut confirmed = false;
+        if let Some(choice) = yes_no_clicked(event, self.question_area) {
+            self.should_apply = choice;
+            confirmed = true;
+        } else if let Event::Key(key) = event {
+            if key.kind == event::KeyEventKind::Press {
+                match key.code {
+                    KeyCode::Up => self.table_state.select_previous(),
+                    KeyCode::Down => self.table_state.select_next(),
+                    KeyCode::Left | KeyCode::BackTab | KeyCode::Right | KeyCode::Tab => {
+                        self.should_apply = !self.should_apply;
+                    }
+                    KeyCode::Enter => confirmed = true,
+                    KeyCode::Esc => return Ok(None),
+                    _ => (),
+                }
+            }
+        }
+        if confirmed {
+            if self.should_apply {
+                let applied = app.data.storage.apply_recurring(&self.pending).await?;
+                app.data.status_text = format!("Added {applied} recurring transactions");
+                app.data.update_table().await?;
+            }
+            return Ok(None);
+        }
+        Ok(Some(Popup::ApplyRecurring(self)))
+    }
+
+    fn render_to_frame(&mut self, area: Rect, frame: &mut Frame, theme: &Theme) {
+        const LIST_HEIGHT: u16 = 7;
+        const QUESTION_HEIGHT: u16 = 1;
+        const BORDER_SIZE: u16 = 1;
+
+        let [area] = Layout::vertical([Constraint::Length(
+            LIST_HEIGHT + QUESTION_HEIGHT + 6 * BORDER_SIZE,
+        )])
+        .flex(Flex::Center)
+        .areas(area);
+        let [area] = Layout::horizontal([Constraint::Percentage(40)])
+            .flex(Flex::Center)
+            .areas(area);
+        let block = Block::bordered().title("Recurring Transactions");
+        frame.render_widget(Clear, area);
+        frame.render_widget(block, area);
+        let area = area.inner(Margin::new(BORDER_SIZE, BORDER_SIZE));
+        let [list_area, question_area] = Layout::vertical([
+            Constraint::Length(LIST_HEIGHT + BORDER_SIZE * 2),
+            Constraint::Length(QUESTION_HEIGHT + BORDER_SIZE * 2),
+        ])
+        .areas(area);
+
+        let rows = self.pending.iter().map(|entry| {
+            Row::new([
+                format::format_date(
+                    entry.datetime.assume_utc().to_offset(self.timezone).date(),
+                    self.locale,
+                ),
+                self.amount_format.format(entry.value),
+                entry.msg.clone(),
+            ])
+        });
+        let list = Table::new(
+            rows,
+            [
+                // spelled out dates need room for names such as "mié 30 sept 2024"
+                Constraint::Length(if self.locale.is_some() { 16 } else { 10 }),
+                Constraint::Fill(1),
+                Constraint::Fill(2),
+            ],
+        )
+        .block(Block::bordered().title(format!("{} pending", self.pending.len())))
+        .highlight_style(theme.highlight());
+
+        let question_field = Block::bordered()
+            .title("Add all missed entries?")
+            .style(theme.active());
+
+        let question_text = Tabs::new(["No", "Yes"])
+            .select(self.should_apply as usize)
+            .block(question_field);
+
+        frame.render_stateful_widget(list, list_area, &mut self.table_state);
+        frame.render_widget(question_text, question_area);
+        self.question_area = question_area;
+    }
+}
@@ -0,0 +1,206 @@
+use std::collections::BTreeSet;
+
+use crossterm::event::{self, Event, KeyCode};
+use ratatui::{
+    layout::{Constraint, Flex, Layout, Margin},
+    prelude::*,
+    widgets::{Block, Clear, List, ListState},
+    Frame,
+};
+
+use crate::{
+    app::{App, AppError},
+    config::{Config, Theme},
+    storage::User,
+    CursoredString,
+};
+
+use super::{pin::render_pin_field, Popup, PopupHandler};
+
+/// Popup for picking other pilots whose transactions are shown alongside the current pilot's
+pub struct CombinePilots {
+    /// Every other pilot, alongside their shown name
+    pilots: Vec<(User, String)>,
+    /// Ids of the pilots picked so far
+    picked: BTreeSet<i32>,
+    list_state: ListState,
+    /// The PIN being typed to pick the highlighted pilot, if they have one
+    pin: Option<CursoredString>,
+    /// Whether the last PIN typed was wrong
+    rejected: bool,
+}
+
+impl CombinePilots {
+    /// Create popup listing every user but the current one, none of them picked
+    pub fn new(users: Vec<User>, current: i32, config: &Config) -> Self {
+        let pilots = users
+            .into_iter()
+            .filter(|user| user.get_id() != current)
+            .map(|user| {
+                let name = config.display_name(user.get_name()).into_owned();
+                (user, name)
+            })
+            .collect();
+        Self {
+            pilots,
+            picked: BTreeSet::new(),
+            list_state: ListState::default().with_selected(Some(0)),
+            pin: None,
+            rejected: false,
+        }
+    }
+
+    /// The highlighted pilot, if there are any
+    fn highlighted(&self) -> Option<&User> {
+        self.list_state
+            .selected()
+            .and_then(|index| self.pilots.get(index))
+            .map(|(user, _)| user)
+    }
+
+    /// Handles typing the highlighted pilot's PIN, picking them once it's right
+    fn handle_pin_key(&mut self, code: KeyCode) {
+        let Some(pin) = &mut self.pin else {
+            return;
+        };
+        match code {
+            KeyCode::Enter => {
+                let Some(user) = self.highlighted() else {
+                    self.pin = None;
+                    return;
+                };
+                let id = user.get_id();
+                if user.verify_pin(self.pin.as_deref().unwrap_or_default()) {
+                    self.picked.insert(id);
+                    self.pin = None;
+                    self.rejected = false;
+                } else {
+                    self.pin = Some(CursoredString::new());
+                    self.rejected = true;
+                }
+            }
+            KeyCode::Backspace => pin.remove_behind(),
+            KeyCode::Esc => {
+                self.pin = None;
+                self.rejected = false;
+            }
+            KeyCode::Char(c) if !c.is_whitespace() => {
+                pin.insert(c);
+            }
+            _ => (),
+        }
+    }
+}
+
+impl PopupHandler for CombinePilots {
+    async fn handle_event(
+        mut self,
+        app: &mut App,
+        event: &Event,
+    ) -> Result<Option<Popup>, AppError> {
+        if let Event::Key(key) = event {
+            if key.kind == event::KeyEventKind::Press {
+                if self.pin.is_some() {
+                    self.handle_pin_key(key.code);
+                    return Ok(Some(Popup::CombinePilots(self)));
+                }
+                match app.data.config.nav_key(key.code) {
+                    KeyCode::Up => self.list_state.select_previous(),
+                    KeyCode::Down => self.list_state.select_next(),
+                    KeyCode::Char(' ') => {
+                        if let Some(user) = self.highlighted() {
+                            let (id, has_pin) = (user.get_id(), user.has_pin());
+                            // a protected pilot's transactions are only shown to those who know their PIN
+                            if !self.picked.remove(&id) {
+                                if has_pin {
+                                    self.pin = Some(CursoredString::new());
+                                } else {
+                                    self.picked.insert(id);
+                                }
+                            }
+                        }
+                    }
+                    KeyCode::Enter if !self.picked.is_empty() => {
+                        let pilots = self
+                            .pilots
+                            .into_iter()
+                            .filter(|(user, _)| self.picked.contains(&user.get_id()))
+                            .map(|(user, name)| (user.get_id(), name))
+                            .collect();
+                        app.data.combine_pilots(pilots).await?;
+                        return Ok(None);
+                    }
+                    KeyCode::Esc => return Ok(None),
+                    _ => (),
+                }
+            }
+        }
+        Ok(Some(Popup::CombinePilots(self)))
+    }
+
+    fn render_to_frame(&mut self, area: Rect, frame: &mut Frame, theme: &Theme) {
+        const LIST_HEIGHT: u16 = 8;
+        const PIN_HEIGHT: u16 = 1;
+        const BORDER_SIZE: u16 = 1;
+
+        let pin_height = if self.pin.is_some() {
+            PIN_HEIGHT + 2 * BORDER_SIZE
+        } else {
+            0
+        };
+        let [area] = Layout::vertical([Constraint::Length(
+            LIST_HEIGHT + 4 * BORDER_SIZE + pin_height,
+        )])
+        .flex(Flex::Center)
+        .areas(area);
+        let [area] = Layout::horizontal([Constraint::Percentage(40)])
+            .flex(Flex::Center)
+            .areas(area);
+        let hint = if self.pin.is_some() {
+            "Enter: check PIN, Esc: cancel"
+        } else {
+            "Space: pick, Enter: show, Esc: cancel"
+        };
+        let block = Block::bordered()
+            .title("Show Pilots Together")
+            .title_bottom(Line::from(hint).dim());
+        frame.render_widget(Clear, area);
+        frame.render_widget(block, area);
+        let [list_area, pin_area] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(pin_height)])
+                .areas(area.inner(Margin::new(BORDER_SIZE, BORDER_SIZE)));
+        if let Some(pin) = &self.pin {
+            let title = if self.rejected {
+                "Wrong PIN, try again"
+            } else {
+                "PIN"
+            };
+            render_pin_field(title, pin, true, pin_area, frame, theme);
+        }
+
+        let mut list_block = Block::bordered().title(format!("{} picked", self.picked.len()));
+        if self.pin.is_none() {
+            list_block = list_block.style(theme.active());
+        }
+        if self.pilots.is_empty() {
+            frame.render_widget(
+                Line::from("No other pilots to show").dim().centered(),
+                list_block.inner(list_area),
+            );
+            frame.render_widget(list_block, list_area);
+            return;
+        }
+        let items = self.pilots.iter().map(|(user, name)| {
+            let lock = if user.has_pin() { " (PIN)" } else { "" };
+            if self.picked.contains(&user.get_id()) {
+                Line::from(format!("[x] {name}{lock}")).bold()
+            } else {
+                Line::from(format!("[ ] {name}{lock}"))
+            }
+        });
+        let list = List::new(items)
+            .block(list_block)
+            .highlight_style(theme.highlight());
+        frame.render_stateful_widget(list, list_area, &mut self.list_state);
+    }
+}
@@ -0,0 +1,125 @@
+use crossterm::event::{self, Event, KeyCode};
+use ratatui::{
+    layout::{Constraint, Flex, Layout, Margin},
+    prelude::*,
+    widgets::{Block, Clear, Paragraph, Tabs, Wrap},
+    Frame,
+};
+
+use crate::{
+    app::{App, AppError},
+    config::Theme,
+    storage::ReasonCode,
+};
+
+use super::{yes_no_clicked, Popup, PopupHandler};
+
+/// Popup asking before every transaction matching the table's filters is given a reason, defaulting to No
+pub struct ConfirmBatchReason {
+    reason: ReasonCode,
+    /// How many transactions matched when the popup was opened
+    count: i64,
+    should_apply: bool,
+    question_area: Rect,
+}
+
+impl ConfirmBatchReason {
+    /// Create popup for giving the reason to the given number of matching transactions
+    pub fn new(reason: ReasonCode, count: i64) -> Self {
+        Self {
+            reason,
+            count,
+            should_apply: false,
+            question_area: Rect::default(),
+        }
+    }
+
+    /// Gives every one of the current user's transactions matching the table's filters the reason, refreshing the table
+    pub async fn apply(reason: &ReasonCode, app: &mut App) -> Result<(), AppError> {
+        let filters = app.data.own_table_filters();
+        let changed = app
+            .data
+            .storage
+            .set_reason_matching(&filters, Some(reason.id))
+            .await?;
+        app.data.status_text = format!("Gave {changed} transactions the reason '{}'", reason.name);
+        app.data.update_table().await?;
+        Ok(())
+    }
+}
+
+impl PopupHandler for ConfirmBatchReason {
+    async fn handle_event(
+        mut self,
+        app: &mut App,
+        event: &Event,
+    ) -> Result<Option<Popup>, AppError> {
+        let mut confirmed = false;
+        if let Some(choice) = yes_no_clicked(event, self.question_area) {
+            self.should_apply = choice;
+            confirmed = true;
+        } else if let Event::Key(key) = event {
+            if key.kind == event::KeyEventKind::Press {
+                match app.data.config.nav_key(key.code) {
+                    KeyCode::Left | KeyCode::BackTab | KeyCode::Right | KeyCode::Tab => {
+                        self.should_apply = !self.should_apply;
+                    }
+                    KeyCode::Enter => confirmed = true,
+                    KeyCode::Esc => return Ok(None),
+                    _ => (),
+                }
+            }
+        }
+        if confirmed {
+            if self.should_apply {
+                Self::apply(&self.reason, app).await?;
+            }
+            return Ok(None);
+        }
+        Ok(Some(Popup::ConfirmBatchReason(self)))
+    }
+
+    fn render_to_frame(&mut self, area: Rect, frame: &mut Frame, theme: &Theme) {
+        const PREVIEW_HEIGHT: u16 = 2;
+        const QUESTION_HEIGHT: u16 = 1;
+        const BORDER_SIZE: u16 = 1;
+
+        let [area] = Layout::vertical([Constraint::Length(
+            PREVIEW_HEIGHT + QUESTION_HEIGHT + 4 * BORDER_SIZE,
+        )])
+        .flex(Flex::Center)
+        .areas(area);
+        let [area] = Layout::horizontal([Constraint::Percentage(40)])
+            .flex(Flex::Center)
+            .areas(area);
+        let block = Block::bordered().title("Give Reason to Filtered");
+        frame.render_widget(Clear, area);
+        frame.render_widget(block, area);
+        let area = area.inner(Margin::new(BORDER_SIZE, BORDER_SIZE));
+        let [preview_area, question_area] = Layout::vertical([
+            Constraint::Length(PREVIEW_HEIGHT),
+            Constraint::Length(QUESTION_HEIGHT + BORDER_SIZE * 2),
+        ])
+        .areas(area);
+
+        let noun = if self.count == 1 {
+            "transaction matches"
+        } else {
+            "transactions match"
+        };
+        let preview = Paragraph::new(format!(
+            "{} {noun} the filters, any reason they already have is replaced with '{}'",
+            self.count, self.reason.name
+        ))
+        .wrap(Wrap { trim: true });
+
+        let question_field = Block::bordered().title("Apply?").style(theme.active());
+        let question_text = Tabs::new(["No", "Yes"])
+            .select(self.should_apply as usize)
+            .block(question_field);
+
+        frame.render_widget(preview, preview_area);
+        frame.render_widget(question_text, question_area);
+        self.question_area = question_area;
+    }
+}
@@ -0,0 +1,106 @@
+use crossterm::event::{self, Event, KeyCode};
+use ratatui::{
+    layout::{Constraint, Flex, Layout, Margin},
+    prelude::*,
+    widgets::{Block, Clear, Paragraph, Tabs, Wrap},
+    Frame,
+};
+
+use crate::{
+    app::{App, AppError},
+    config::Theme,
+};
+
+use super::{yes_no_clicked, Popup, PopupHandler};
+
+/// Popup asking before deleting a transaction, defaulting to No
+pub struct ConfirmDelete {
+    trans_id: i32,
+    /// The transaction's amount and message, to show which one is about to go
+    description: String,
+    should_delete: bool,
+    question_area: Rect,
+}
+
+impl ConfirmDelete {
+    /// Create popup for deleting the transaction with the given id, described by its amount and message
+    pub fn new(trans_id: i32, description: String) -> Self {
+        Self {
+            trans_id,
+            description,
+            should_delete: false,
+            question_area: Rect::default(),
+        }
+    }
+}
+
+impl PopupHandler for ConfirmDelete {
+    async fn handle_event(
+        mut self,
+        app: &mut App,
+        event: &Event,
+    ) -> Result<Option<Popup>, AppError> {
+        let mut confirmed = false;
+        if let Some(choice) = yes_no_clicked(event, self.question_area) {
+            self.should_delete = choice;
+            confirmed = true;
+        } else if let Event::Key(key) = event {
+            if key.kind == event::KeyEventKind::Press {
+                match app.data.config.nav_key(key.code) {
+                    KeyCode::Left | KeyCode::BackTab | KeyCode::Right | KeyCode::Tab => {
+                        self.should_delete = !self.should_delete;
+                    }
+                    KeyCode::Enter => confirmed = true,
+                    KeyCode::Esc => return Ok(None),
+                    _ => (),
+                }
+            }
+        }
+        if confirmed {
+            if self.should_delete {
+                app.data
+                    .delete_transaction(self.trans_id, &self.description)
+                    .await?;
+            }
+            return Ok(None);
+        }
+        Ok(Some(Popup::ConfirmDelete(self)))
+    }
+
+    fn render_to_frame(&mut self, area: Rect, frame: &mut Frame, theme: &Theme) {
+        const DESCRIPTION_HEIGHT: u16 = 2;
+        const QUESTION_HEIGHT: u16 = 1;
+        const BORDER_SIZE: u16 = 1;
+
+        let [area] = Layout::vertical([Constraint::Length(
+            DESCRIPTION_HEIGHT + QUESTION_HEIGHT + 4 * BORDER_SIZE,
+        )])
+        .flex(Flex::Center)
+        .areas(area);
+        let [area] = Layout::horizontal([Constraint::Percentage(40)])
+            .flex(Flex::Center)
+            .areas(area);
+        let block = Block::bordered()
+            .title("Delete Transaction")
+            .title_bottom(Line::from("Deleted transactions can be restored with 'D'").dim());
+        frame.render_widget(Clear, area);
+        frame.render_widget(block, area);
+        let area = area.inner(Margin::new(BORDER_SIZE, BORDER_SIZE));
+        let [description_area, question_area] = Layout::vertical([
+            Constraint::Length(DESCRIPTION_HEIGHT),
+            Constraint::Length(QUESTION_HEIGHT + BORDER_SIZE * 2),
+        ])
+        .areas(area);
+
+        let description = Paragraph::new(self.description.as_str()).wrap(Wrap { trim: true });
+
+        let question_field = Block::bordered().title("Delete it?").style(theme.active());
+        let question_text = Tabs::new(["No", "Yes"])
+            .select(self.should_delete as usize)
+            .block(question_field);
+
+        frame.render_widget(description, description_area);
+        frame.render_widget(question_text, question_area);
+        self.question_area = question_area;
+    }
+}
@@ -0,0 +1,111 @@
+use crossterm::event::{self, Event, KeyCode};
+use ratatui::{
+    layout::{Constraint, Flex, Layout, Margin},
+    prelude::*,
+    widgets::{Block, Clear, Paragraph, Tabs, Wrap},
+    Frame,
+};
+
+use crate::{
+    app::{App, AppError, AppMode},
+    config::Theme,
+};
+
+use super::{yes_no_clicked, Popup, PopupHandler};
+
+/// Popup asking before switching user would throw away a selection, defaulting to No
+pub struct ConfirmLogout {
+    selected: usize,
+    should_logout: bool,
+    question_area: Rect,
+}
+
+impl ConfirmLogout {
+    /// Create popup for switching user while the given number of transactions are selected
+    pub fn new(selected: usize) -> Self {
+        Self {
+            selected,
+            should_logout: false,
+            question_area: Rect::default(),
+        }
+    }
+}
+
+impl PopupHandler for ConfirmLogout {
+    async fn handle_event(
+        mut self,
+        app: &mut App,
+        event: &Event,
+    ) -> Result<Option<Popup>, AppError> {
+        let mut confirmed = false;
+        if let Some(choice) = yes_no_clicked(event, self.question_area) {
+            self.should_logout = choice;
+            confirmed = true;
+        } else if let Event::Key(key) = event {
+            if key.kind == event::KeyEventKind::Press {
+                match app.data.config.nav_key(key.code) {
+                    KeyCode::Left | KeyCode::BackTab | KeyCode::Right | KeyCode::Tab => {
+                        self.should_logout = !self.should_logout;
+                    }
+                    KeyCode::Enter => confirmed = true,
+                    KeyCode::Esc => return Ok(None),
+                    _ => (),
+                }
+            }
+        }
+        if confirmed {
+            if self.should_logout {
+                app.data.logout();
+                app.mode = AppMode::UserLogin(Default::default());
+            }
+            return Ok(None);
+        }
+        Ok(Some(Popup::ConfirmLogout(self)))
+    }
+
+    fn render_to_frame(&mut self, area: Rect, frame: &mut Frame, theme: &Theme) {
+        const WARNING_HEIGHT: u16 = 2;
+        const QUESTION_HEIGHT: u16 = 1;
+        const BORDER_SIZE: u16 = 1;
+
+        let [area] = Layout::vertical([Constraint::Length(
+            WARNING_HEIGHT + QUESTION_HEIGHT + 4 * BORDER_SIZE,
+        )])
+        .flex(Flex::Center)
+        .areas(area);
+        let [area] = Layout::horizontal([Constraint::Percentage(40)])
+            .flex(Flex::Center)
+            .areas(area);
+        let block = Block::bordered().title("Switch User");
+        frame.render_widget(Clear, area);
+        frame.render_widget(block, area);
+        let area = area.inner(Margin::new(BORDER_SIZE, BORDER_SIZE));
+        let [warning_area, question_area] = Layout::vertical([
+            Constraint::Length(WARNING_HEIGHT),
+            Constraint::Length(QUESTION_HEIGHT + BORDER_SIZE * 2),
+        ])
+        .areas(area);
+
+        let noun = if self.selected == 1 {
+            "transaction"
+        } else {
+            "transactions"
+        };
+        let warning = Paragraph::new(format!(
+            "{} selected {noun} will be deselected",
+            self.selected
+        ))
+        .wrap(Wrap { trim: true });
+
+        let question_field = Block::bordered()
+            .title("Switch anyway?")
+            .style(theme.active());
+        let question_text = Tabs::new(["No", "Yes"])
+            .select(self.should_logout as usize)
+            .block(question_field);
+
+        frame.render_widget(warning, warning_area);
+        frame.render_widget(question_text, question_area);
+        self.question_area = question_area;
+    }
+}
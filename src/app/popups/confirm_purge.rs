@@ -0,0 +1,117 @@
+use crossterm::event::{self, Event, KeyCode};
+use ratatui::{
+    layout::{Constraint, Flex, Layout, Margin},
+    prelude::*,
+    widgets::{Block, Clear, Paragraph, Tabs, Wrap},
+    Frame,
+};
+
+use crate::{
+    app::{App, AppError},
+    config::Theme,
+    storage::TransactionFilter,
+};
+
+use super::{yes_no_clicked, Popup, PopupHandler};
+
+/// Popup asking before deleted transactions are purged for good, defaulting to No
+pub struct ConfirmPurge {
+    ids: Vec<i32>,
+    should_purge: bool,
+    question_area: Rect,
+}
+
+impl ConfirmPurge {
+    /// Create popup for purging the given soft deleted transactions
+    pub fn new(ids: Vec<i32>) -> Self {
+        Self {
+            ids,
+            should_purge: false,
+            question_area: Rect::default(),
+        }
+    }
+}
+
+impl PopupHandler for ConfirmPurge {
+    async fn handle_event(
+        mut self,
+        app: &mut App,
+        event: &Event,
+    ) -> Result<Option<Popup>, AppError> {
+        let mut confirmed = false;
+        if let Some(choice) = yes_no_clicked(event, self.question_area) {
+            self.should_purge = choice;
+            confirmed = true;
+        } else if let Event::Key(key) = event {
+            if key.kind == event::KeyEventKind::Press {
+                match app.data.config.nav_key(key.code) {
+                    KeyCode::Left | KeyCode::BackTab | KeyCode::Right | KeyCode::Tab => {
+                        self.should_purge = !self.should_purge;
+                    }
+                    KeyCode::Enter => confirmed = true,
+                    KeyCode::Esc => return Ok(None),
+                    _ => (),
+                }
+            }
+        }
+        if confirmed {
+            if self.should_purge {
+                let purged = app
+                    .data
+                    .storage
+                    .purge_transactions(TransactionFilter::Id(self.ids))
+                    .await?;
+                app.data.status_text = format!("Permanently deleted {purged} transactions");
+                app.data.selected_ids.clear();
+                app.data.update_table().await?;
+            }
+            return Ok(None);
+        }
+        Ok(Some(Popup::ConfirmPurge(self)))
+    }
+
+    fn render_to_frame(&mut self, area: Rect, frame: &mut Frame, theme: &Theme) {
+        const WARNING_HEIGHT: u16 = 2;
+        const QUESTION_HEIGHT: u16 = 1;
+        const BORDER_SIZE: u16 = 1;
+
+        let [area] = Layout::vertical([Constraint::Length(
+            WARNING_HEIGHT + QUESTION_HEIGHT + 4 * BORDER_SIZE,
+        )])
+        .flex(Flex::Center)
+        .areas(area);
+        let [area] = Layout::horizontal([Constraint::Percentage(40)])
+            .flex(Flex::Center)
+            .areas(area);
+        let block = Block::bordered().title("Purge Transactions");
+        frame.render_widget(Clear, area);
+        frame.render_widget(block, area);
+        let area = area.inner(Margin::new(BORDER_SIZE, BORDER_SIZE));
+        let [warning_area, question_area] = Layout::vertical([
+            Constraint::Length(WARNING_HEIGHT),
+            Constraint::Length(QUESTION_HEIGHT + BORDER_SIZE * 2),
+        ])
+        .areas(area);
+
+        let count = self.ids.len();
+        let noun = if count == 1 {
+            "transaction"
+        } else {
+            "transactions"
+        };
+        let warning = Paragraph::new(format!(
+            "Permanently delete {count} {noun}? Unlike deleting, this can't be undone"
+        ))
+        .wrap(Wrap { trim: true })
+        .red();
+
+        let question_field = Block::bordered().title("Purge?").style(theme.active());
+        let question_text = Tabs::new(["No", "Yes"])
+            .select(self.should_purge as usize)
+            .block(question_field);
+
+        frame.render_widget(warning, warning_area);
+        frame.render_widget(question_text, question_area);
+        self.question_area = question_area;
+    }
+}
@@ -2,28 +2,37 @@ use crossterm::event::{self, Event, KeyCode};
 use ratatui::{
     layout::{Constraint, Flex, Layout, Margin},
     prelude::*,
-    style::{Color, Style},
     widgets::{Block, Clear, Tabs},
     Frame,
 };
 
-use crate::app::{App, AppError, AppMode};
+use crate::{
+    app::{App, AppError, AppMode},
+    config::{Config, Theme},
+    format,
+};
 
-use super::{Popup, PopupHandler};
+use super::{yes_no_clicked, Popup, PopupHandler};
 
 /// Popup for confirming new user creation
 pub struct CreateUser {
     new_user: String,
+    /// The new user's name as it'll be shown
+    display_name: String,
     should_create: bool,
+    /// Area of the Yes/No choice as last rendered, for mouse clicks
+    question_area: Rect,
 }
 
 impl CreateUser {
     /// Create popup with 'Yes' selected by default.
     /// Internal buffer can't and won't actually be mutated but it's cheaper to pass the string we have than make a new immutable copy.
-    pub fn new(new_user: String) -> Self {
+    pub fn new(new_user: String, config: &Config) -> Self {
         Self {
+            display_name: config.display_name(&new_user).into_owned(),
             new_user,
             should_create: true,
+            question_area: Rect::default(),
         }
     }
 }
@@ -34,7 +43,11 @@ impl PopupHandler for CreateUser {
         app: &mut App,
         event: &Event,
     ) -> Result<Option<Popup>, AppError> {
-        if let Event::Key(key) = event {
+        let mut confirmed = false;
+        if let Some(choice) = yes_no_clicked(event, self.question_area) {
+            self.should_create = choice;
+            confirmed = true;
+        } else if let Event::Key(key) = event {
             if key.kind == event::KeyEventKind::Press {
                 match key.code {
                     KeyCode::Left | KeyCode::BackTab => {
@@ -43,26 +56,25 @@ impl PopupHandler for CreateUser {
                     KeyCode::Right | KeyCode::Tab => {
                         self.should_create = !self.should_create;
                     }
-                    KeyCode::Enter => {
-                        if self.should_create {
-                            app.data.storage.create_user(&self.new_user).await?;
-                            let user = app.data.storage.get_user(&self.new_user).await?;
-                            app.data.status_text = format!("Logged in as {}", user.get_name());
-                            app.data.current_user = Some(user);
-                            app.mode = AppMode::LogTable;
-                            app.data.update_table().await?;
-                        };
-                        return Ok(None);
-                    }
+                    KeyCode::Enter => confirmed = true,
                     KeyCode::Esc => return Ok(None),
                     _ => (),
                 }
             }
         }
+        if confirmed {
+            if self.should_create {
+                app.data.storage.create_user(&self.new_user).await?;
+                let user = app.data.storage.get_user(&self.new_user).await?;
+                app.mode = AppMode::LogTable;
+                return app.data.login(user).await;
+            };
+            return Ok(None);
+        }
         Ok(Some(Popup::CreateUser(self)))
     }
 
-    fn render_to_frame(&mut self, area: Rect, frame: &mut Frame) {
+    fn render_to_frame(&mut self, area: Rect, frame: &mut Frame, theme: &Theme) {
         const QUESTION_HEIGHT: u16 = 1;
         const BORDER_SIZE: u16 = 1;
 
@@ -79,14 +91,21 @@ impl PopupHandler for CreateUser {
         let [username_area] =
             Layout::vertical([Constraint::Length(QUESTION_HEIGHT + BORDER_SIZE * 2)]).areas(area);
 
+        // the name is cut short rather than letting the title run past the border, it's still created in full
+        let title_width = usize::from(username_area.width.saturating_sub(2 * BORDER_SIZE));
+        let name = format::truncate(
+            &self.display_name,
+            title_width.saturating_sub("Create user ''?".len()),
+        );
         let username_field = Block::bordered()
-            .title(format!("Create user '{}'?", self.new_user))
-            .style(Style::default().bg(Color::LightYellow).fg(Color::Black));
+            .title(format!("Create user '{name}'?"))
+            .style(theme.active());
 
         let username_text = Tabs::new(["No", "Yes"])
             .select(self.should_create as usize)
             .block(username_field);
 
         frame.render_widget(username_text, username_area);
+        self.question_area = username_area;
     }
 }
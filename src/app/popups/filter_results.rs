@@ -1,23 +1,23 @@
 use core::iter::Iterator;
-use std::borrow::Cow;
 
-use crossterm::event::{self, Event, KeyCode};
+use crossterm::event::{self, Event, KeyCode, KeyModifiers};
 use itertools::Itertools;
 use num_derive::FromPrimitive;
 use num_traits::FromPrimitive;
 use ratatui::{
     layout::{Constraint, Flex, Layout, Margin},
     prelude::*,
-    style::{Color, Style},
-    widgets::{Block, Cell, Clear, ListState, Paragraph, Row, Table, Tabs},
+    widgets::{Block, Cell, Clear, Paragraph, Row, Table, TableState, Tabs},
     Frame,
 };
 use strum::{EnumCount, VariantNames};
-use text::{ToSpan, ToText};
 
 use crate::{
     app::{App, AppError},
-    storage::{TransactionFilter, TransactionType},
+    config::{AmountSteps, Config, Theme},
+    format::AmountFormatter,
+    storage::{TransactionFilter, TransactionStatus, TransactionType, TransactionTypeMap},
+    CursoredString,
 };
 
 use super::{Popup, PopupHandler};
@@ -25,16 +25,31 @@ use super::{Popup, PopupHandler};
 /// Popup for viewing and editing filters
 pub struct FilterResults {
     filters: Vec<TransactionFilter>,
-    list_state: ListState,
+    table_state: TableState,
+    /// Narrows the list to filters whose description contains this, showing all of them when empty
+    query: CursoredString,
+    /// Whether keys are going into the query
+    searching: bool,
 }
 
 /// Popup that goes over the filter results for adding new filters
 pub struct AddFilter {
     pop_under: FilterResults,
     filter: TransactionFilter,
+    /// The filter being edited, put back if editing is cancelled
+    original: Option<TransactionFilter>,
+    /// Where in the list the filter goes back, at the end if unset
+    position: Option<usize>,
     selected_field: AddFilterField,
     selected_type: AddFilterType,
     index: usize,
+    /// How many of the user's transactions are of each type, fetched when the popup opens
+    type_counts: TransactionTypeMap<i64>,
+    /// Text typed for a message filter, copied into the filter as it changes
+    message: CursoredString,
+    /// How far the bounds of a value range step, as amounts do when adding a transaction
+    amount_steps: AmountSteps,
+    amount_format: AmountFormatter,
 }
 
 #[derive(Default, PartialEq, Eq, FromPrimitive, EnumCount, Clone, Copy)]
@@ -50,7 +65,10 @@ enum AddFilterField {
 #[repr(u8)]
 enum AddFilterType {
     TransactionType = 0,
+    Status,
     DateRange,
+    Message,
+    ValueRange,
 }
 
 impl FilterResults {
@@ -59,23 +77,198 @@ impl FilterResults {
     pub fn new(filters: Vec<TransactionFilter>) -> Self {
         Self {
             filters,
-            list_state: Default::default(),
+            table_state: Default::default(),
+            query: CursoredString::new(),
+            searching: false,
+        }
+    }
+
+    /// Indices of the filters matching the search query, ignoring case
+    fn visible_indices(&self) -> Vec<usize> {
+        let query = self.query.trim().to_lowercase();
+        self.filters
+            .iter()
+            .positions(|filter| {
+                query.is_empty() || filter.as_cells().join(" ").to_lowercase().contains(&query)
+            })
+            .collect()
+    }
+
+    /// Index into all the filters of the highlighted one, kept within the visible list.
+    /// None when nothing is highlighted or no filters are shown
+    fn highlighted_index(&self) -> Option<usize> {
+        let visible = self.visible_indices();
+        self.table_state
+            .selected()
+            .filter(|_| !visible.is_empty())
+            .map(|index| visible[index.min(visible.len() - 1)])
+    }
+
+    /// Handles typing the search query
+    fn handle_search(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Up => self.table_state.select_previous(),
+            KeyCode::Down => self.table_state.select_next(),
+            KeyCode::Left => self.query.right(),
+            KeyCode::Right => self.query.left(),
+            KeyCode::Enter => self.searching = false,
+            KeyCode::Esc => {
+                self.searching = false;
+                self.query = CursoredString::new();
+            }
+            KeyCode::Backspace => self.query.remove_behind(),
+            KeyCode::Delete => self.query.remove_ahead(),
+            KeyCode::Insert => self.query.inserting = !self.query.inserting,
+            KeyCode::Char(c) => {
+                self.query.insert(c);
+            }
+            _ => return,
+        }
+        // the highlighted row may no longer be there, so start again from the top
+        if !matches!(code, KeyCode::Up | KeyCode::Down) {
+            self.table_state.select(Some(0));
         }
     }
 }
 
 impl AddFilter {
-    pub fn new(pop_under: FilterResults) -> Self {
-        Self::new_with_entry(pop_under, TransactionFilter::Type(Default::default()))
+    pub fn new(
+        pop_under: FilterResults,
+        type_counts: TransactionTypeMap<i64>,
+        config: &Config,
+    ) -> Self {
+        Self {
+            original: None,
+            ..Self::new_with_entry(
+                pop_under,
+                TransactionFilter::Type(Default::default()),
+                type_counts,
+                config,
+            )
+        }
     }
 
-    pub fn new_with_entry(pop_under: FilterResults, filter: TransactionFilter) -> Self {
+    /// Edit an existing filter, which is put back unchanged if editing is cancelled
+    pub fn new_with_entry(
+        pop_under: FilterResults,
+        filter: TransactionFilter,
+        type_counts: TransactionTypeMap<i64>,
+        config: &Config,
+    ) -> Self {
+        let mut message = CursoredString::new();
+        if let Some(text) = message_text(&filter) {
+            message.insert_str(text);
+        }
         Self {
             pop_under,
+            message,
+            selected_type: AddFilterType::of(&filter),
+            original: Some(filter.clone()),
+            position: None,
             filter,
             selected_field: AddFilterField::Type,
-            selected_type: AddFilterType::TransactionType,
             index: 0,
+            type_counts,
+            amount_steps: config.amount_steps.clone(),
+            amount_format: config.amount_format.clone(),
+        }
+    }
+
+    /// Returns to the filter list, adding the given filter if it filters anything
+    fn finish(mut self, filter: Option<TransactionFilter>) -> Popup {
+        if let Some(filter) = filter.and_then(TransactionFilter::get_useful) {
+            let filters = &mut self.pop_under.filters;
+            match self.position {
+                Some(index) if index <= filters.len() => filters.insert(index, filter),
+                _ => filters.push(filter),
+            }
+        }
+        Popup::FilterResults(self.pop_under)
+    }
+
+    /// The filter being edited, looking past a [`TransactionFilter::Not`] to the one it negates
+    fn inner_filter_mut(&mut self) -> &mut TransactionFilter {
+        match &mut self.filter {
+            TransactionFilter::Not(filter) => filter.as_mut(),
+            filter => filter,
+        }
+    }
+
+    /// Handles a key in the value field of a message filter, returning whether it was used.
+    /// Typing goes into the message and Tab switches between matching a part of it or all of it
+    fn handle_message_key(&mut self, code: KeyCode) -> bool {
+        match code {
+            KeyCode::Left => self.message.right(),
+            KeyCode::Right => self.message.left(),
+            KeyCode::Backspace => self.message.remove_behind(),
+            KeyCode::Delete => self.message.remove_ahead(),
+            KeyCode::Insert => self.message.inserting = !self.message.inserting,
+            KeyCode::Char(c) => {
+                self.message.insert(c);
+            }
+            KeyCode::Tab => {
+                let filter = self.inner_filter_mut();
+                *filter = match std::mem::replace(filter, TransactionFilter::Deleted) {
+                    TransactionFilter::MessageContains(text) => {
+                        TransactionFilter::MessageEquals(text)
+                    }
+                    TransactionFilter::MessageEquals(text) => {
+                        TransactionFilter::MessageContains(text)
+                    }
+                    other => other,
+                };
+            }
+            _ => return false,
+        }
+        let text = self.message.to_string();
+        if let TransactionFilter::MessageContains(message)
+        | TransactionFilter::MessageEquals(message) = self.inner_filter_mut()
+        {
+            *message = text;
+        }
+        true
+    }
+
+    /// Handles a key in the value field of a value range, returning whether it was used.
+    /// Left and Right (h and l with vim keys) step the highlighted bound as amounts are stepped, starting from 0 if it's unset,
+    /// Tab switches between the minimum and maximum, and Backspace or Delete unsets it
+    fn handle_value_range_key(&mut self, code: KeyCode, modifiers: KeyModifiers) -> bool {
+        let step = match code {
+            KeyCode::Left => -self.amount_steps.step(modifiers),
+            KeyCode::Right => self.amount_steps.step(modifiers),
+            KeyCode::Tab => {
+                self.index = (self.index + 1).rem_euclid(self.selected_type.value_count());
+                return true;
+            }
+            KeyCode::Backspace | KeyCode::Delete => 0,
+            _ => return false,
+        };
+        let index = self.index;
+        if let TransactionFilter::ValueRange { min, max } = self.inner_filter_mut() {
+            let bound = if index == 0 { min } else { max };
+            *bound = (step != 0).then(|| bound.unwrap_or_default().saturating_add(step));
+        }
+        true
+    }
+
+    /// Toggles the highlighted value of the filter
+    fn toggle_value(&mut self) {
+        let filter = match &mut self.filter {
+            TransactionFilter::Not(filter) => filter.as_mut(),
+            filter => filter,
+        };
+        match filter {
+            TransactionFilter::Type(transaction_types) => {
+                if let Some(transaction_type) = TransactionType::from_repr(self.index as i32) {
+                    transaction_types[transaction_type] = !transaction_types[transaction_type];
+                }
+            }
+            TransactionFilter::Status(statuses) => {
+                if let Some(status) = TransactionStatus::from_repr(self.index as i32) {
+                    statuses[status] = !statuses[status];
+                }
+            }
+            _ => (),
         }
     }
 }
@@ -111,11 +304,27 @@ impl AddFilterType {
                 .expect("Will always be a valid i8 unless AddFilterType became an empty enum")
     }
 
+    /// The kind of the given filter, as far as the popup can edit it
+    fn of(filter: &TransactionFilter) -> Self {
+        match filter {
+            TransactionFilter::DateRange(_) => AddFilterType::DateRange,
+            TransactionFilter::Status(_) => AddFilterType::Status,
+            TransactionFilter::MessageContains(_) | TransactionFilter::MessageEquals(_) => {
+                AddFilterType::Message
+            }
+            TransactionFilter::ValueRange { .. } => AddFilterType::ValueRange,
+            TransactionFilter::Not(filter) => Self::of(filter),
+            _ => AddFilterType::TransactionType,
+        }
+    }
+
     /// How many possibilities available for the value selector
     fn value_count(&self) -> usize {
         match self {
             AddFilterType::TransactionType => TransactionType::COUNT,
-            AddFilterType::DateRange => 2,
+            AddFilterType::Status => TransactionStatus::COUNT,
+            AddFilterType::DateRange | AddFilterType::ValueRange => 2,
+            AddFilterType::Message => 1,
         }
     }
 }
@@ -128,34 +337,75 @@ impl PopupHandler for FilterResults {
     ) -> Result<Option<Popup>, AppError> {
         if let Event::Key(key) = event {
             if key.kind == event::KeyEventKind::Press {
-                match key.code {
+                if self.searching {
+                    self.handle_search(key.code);
+                    return Ok(Some(Popup::FilterResults(self)));
+                }
+                match app.data.config.nav_key(key.code) {
                     KeyCode::Up => {
-                        self.list_state.select_previous();
+                        self.table_state.select_previous();
                     }
                     KeyCode::Down => {
-                        self.list_state.select_next();
+                        self.table_state.select_next();
+                    }
+                    KeyCode::Char('/') => {
+                        self.searching = true;
+                    }
+                    // the first Esc only clears the search
+                    KeyCode::Esc if !self.query.is_empty() => {
+                        self.query = CursoredString::new();
+                        self.table_state.select(Some(0));
                     }
                     KeyCode::Esc => {
-                        app.data.transaction_filters = self.filters;
+                        app.data.set_filters(self.filters).await?;
                         return Ok(None);
                     }
                     KeyCode::Char('d') => {
-                        if let Some(index) = self.list_state.selected() {
-                            let index = index.clamp(0, self.filters.len() - 1);
+                        if let Some(index) = self.highlighted_index() {
                             self.filters.remove(index);
                         }
                     }
-                    KeyCode::Char('a') => return Ok(Some(Popup::AddFilter(AddFilter::new(self)))),
-                    KeyCode::Char('e') => {
-                        if let Some(index) = self.list_state.selected() {
-                            let index = index.clamp(0, self.filters.len() - 1);
-                            let entry = self.filters.swap_remove(index);
+                    KeyCode::Char('a') => {
+                        let type_counts = current_type_counts(app).await?;
+                        return Ok(Some(Popup::AddFilter(AddFilter::new(
+                            self,
+                            type_counts,
+                            &app.data.config,
+                        ))));
+                    }
+                    KeyCode::Char('c') => {
+                        if let Some(index) = self.highlighted_index() {
+                            // the copy goes at the end of the list and is edited from there
+                            let entry = self.filters[index].clone();
+                            let type_counts = current_type_counts(app).await?;
+                            // the copy matches the search just as the original did, so it's shown last
+                            self.table_state.select(Some(self.visible_indices().len()));
 
                             return Ok(Some(Popup::AddFilter(AddFilter::new_with_entry(
-                                self, entry,
+                                self,
+                                entry,
+                                type_counts,
+                                &app.data.config,
                             ))));
                         }
                     }
+                    KeyCode::Char('e') => {
+                        if let Some(index) = self.highlighted_index() {
+                            // the edited filter goes back where it was, keeping the list's order
+                            let entry = self.filters.remove(index);
+                            let type_counts = current_type_counts(app).await?;
+
+                            return Ok(Some(Popup::AddFilter(AddFilter {
+                                position: Some(index),
+                                ..AddFilter::new_with_entry(
+                                    self,
+                                    entry,
+                                    type_counts,
+                                    &app.data.config,
+                                )
+                            })));
+                        }
+                    }
                     _ => (),
                 }
             }
@@ -163,33 +413,62 @@ impl PopupHandler for FilterResults {
         Ok(Some(Popup::FilterResults(self)))
     }
 
-    fn render_to_frame(&mut self, area: Rect, frame: &mut Frame) {
+    fn render_to_frame(&mut self, area: Rect, frame: &mut Frame, theme: &Theme) {
         const LIST_HEIGHT: u16 = 7;
+        const SEARCH_HEIGHT: u16 = 1;
         const BORDER_SIZE: u16 = 1;
 
-        let [area] = Layout::vertical([Constraint::Length(LIST_HEIGHT + 4 * BORDER_SIZE)])
-            .flex(Flex::Center)
-            .areas(area);
+        let search_height = if self.searching || !self.query.is_empty() {
+            SEARCH_HEIGHT + BORDER_SIZE * 2
+        } else {
+            0
+        };
+        let [area] = Layout::vertical([Constraint::Length(
+            LIST_HEIGHT + search_height + 4 * BORDER_SIZE,
+        )])
+        .flex(Flex::Center)
+        .areas(area);
         let [area] = Layout::horizontal([Constraint::Percentage(40)])
             .flex(Flex::Center)
             .areas(area);
-        let block = Block::bordered().title("Filter Transactions");
+        let block = Block::bordered()
+            .title("Filter Transactions")
+            .title_bottom(Line::from("/: search").dim());
         frame.render_widget(Clear, area);
         frame.render_widget(block, area);
         let area = area.inner(Margin::new(BORDER_SIZE, BORDER_SIZE));
-        let [table_area] =
-            Layout::vertical([Constraint::Length(LIST_HEIGHT + BORDER_SIZE * 2)]).areas(area);
+        let [table_area, search_area] = Layout::vertical([
+            Constraint::Length(LIST_HEIGHT + BORDER_SIZE * 2),
+            Constraint::Length(search_height),
+        ])
+        .areas(area);
 
-        let table_block =
-            Block::bordered().style(Style::default().bg(Color::LightYellow).fg(Color::Black));
+        let table_block = Block::bordered().style(theme.active());
 
+        let visible = self.visible_indices();
         let filter_table = Table::new(
-            filters_as_rows(&self.filters),
+            filters_as_rows(visible.iter().map(|&index| &self.filters[index])),
             [Constraint::Percentage(70), Constraint::Fill(1)],
         )
-        .block(table_block);
-
-        frame.render_widget(filter_table, table_area);
+        .block(table_block)
+        .highlight_style(theme.highlight());
+
+        frame.render_stateful_widget(filter_table, table_area, &mut self.table_state);
+
+        if search_height > 0 {
+            let mut search_field = Block::bordered().title("Search");
+            if self.searching {
+                search_field = search_field.style(theme.active());
+                frame.set_cursor_position(Position::new(
+                    search_area.x + self.query.cursor_index() as u16 + BORDER_SIZE,
+                    search_area.y + BORDER_SIZE,
+                ));
+            }
+            frame.render_widget(
+                Paragraph::new(self.query.as_str()).block(search_field),
+                search_area,
+            );
+        }
     }
 }
 
@@ -201,7 +480,21 @@ impl PopupHandler for AddFilter {
     ) -> Result<Option<Popup>, AppError> {
         if let Event::Key(key) = event {
             if key.kind == event::KeyEventKind::Press {
-                match key.code {
+                // the message is typed, so letters mustn't be taken as vim keys
+                if self.selected_field == AddFilterField::Value
+                    && matches!(self.selected_type, AddFilterType::Message)
+                    && self.handle_message_key(key.code)
+                {
+                    return Ok(Some(Popup::AddFilter(self)));
+                }
+                // stepping uses the modifiers, so the bounds are handled before the usual moves
+                if self.selected_field == AddFilterField::Value
+                    && matches!(self.selected_type, AddFilterType::ValueRange)
+                    && self.handle_value_range_key(app.data.config.nav_key(key.code), key.modifiers)
+                {
+                    return Ok(Some(Popup::AddFilter(self)));
+                }
+                match app.data.config.nav_key(key.code) {
                     KeyCode::Up => {
                         self.selected_field.prev();
                     }
@@ -211,7 +504,9 @@ impl PopupHandler for AddFilter {
                     KeyCode::Left => match self.selected_field {
                         AddFilterField::Type => {
                             self.selected_type.prev();
-                            self.filter = self.selected_type.into()
+                            self.filter = self.selected_type.into();
+                            self.message = CursoredString::new();
+                            self.index = 0;
                         }
                         AddFilterField::Value => {
                             self.index = (self.index as isize - 1)
@@ -223,7 +518,9 @@ impl PopupHandler for AddFilter {
                     KeyCode::Right => match self.selected_field {
                         AddFilterField::Type => {
                             self.selected_type.next();
-                            self.filter = self.selected_type.into()
+                            self.filter = self.selected_type.into();
+                            self.message = CursoredString::new();
+                            self.index = 0;
                         }
                         AddFilterField::Value => {
                             self.index =
@@ -231,8 +528,17 @@ impl PopupHandler for AddFilter {
                         }
                         AddFilterField::Submit => (),
                     },
+                    KeyCode::Enter => match self.selected_field {
+                        AddFilterField::Type => self.selected_field.next(),
+                        AddFilterField::Value => self.toggle_value(),
+                        AddFilterField::Submit => {
+                            let filter = self.filter.clone();
+                            return Ok(Some(self.finish(Some(filter))));
+                        }
+                    },
                     KeyCode::Esc => {
-                        return Ok(Some(Popup::FilterResults(self.pop_under)));
+                        let original = self.original.take();
+                        return Ok(Some(self.finish(original)));
                     }
                     _ => (),
                 }
@@ -241,7 +547,7 @@ impl PopupHandler for AddFilter {
         Ok(Some(Popup::AddFilter(self)))
     }
 
-    fn render_to_frame(&mut self, area: Rect, frame: &mut Frame)
+    fn render_to_frame(&mut self, area: Rect, frame: &mut Frame, theme: &Theme)
     where
         Self: Sized,
     {
@@ -251,9 +557,13 @@ impl PopupHandler for AddFilter {
             selected_type,
             filter,
             index,
+            type_counts,
+            message,
+            amount_format,
+            ..
         } = self;
 
-        pop_under.render_to_frame(area, frame);
+        pop_under.render_to_frame(area, frame, theme);
 
         const BOX_HEIGHT: u16 = 1;
         const BORDER_SIZE: u16 = 1;
@@ -279,9 +589,20 @@ impl PopupHandler for AddFilter {
 
         let mut type_field = Block::bordered().title("Type");
         let mut values_field = Block::bordered().title("Values");
+        match selected_type {
+            AddFilterType::Message => {
+                values_field =
+                    values_field.title(Line::from("Tab: contains/equals").dim().right_aligned());
+            }
+            AddFilterType::ValueRange => {
+                values_field = values_field
+                    .title(Line::from("Tab: min/max, Del: clear").dim().right_aligned());
+            }
+            _ => (),
+        }
         let mut submit_field = Block::bordered();
 
-        let active_style = Style::default().bg(Color::LightYellow).fg(Color::Black);
+        let active_style = theme.active();
 
         {
             use AddFilterField::*;
@@ -295,13 +616,24 @@ impl PopupHandler for AddFilter {
         let type_text = Tabs::new(<AddFilterType as VariantNames>::VARIANTS.iter().copied())
             .select(*selected_type as usize)
             .block(type_field);
-        let values_text = display_filter_values(filter, *index).block(values_field);
+        let values_text = display_filter_values(filter, *index, type_counts, amount_format, theme)
+            .block(values_field);
         let submit_text = Paragraph::new(SUBMIT_TEXT)
             .block(submit_field)
             .alignment(Alignment::Center);
 
         frame.render_widget(type_text, type_area);
         frame.render_widget(values_text, values_area);
+        if *selected_field == AddFilterField::Value {
+            if let Some(mode) = message_mode(filter) {
+                // the message is drawn after its mode and ": "
+                let prefix = mode.len() + 2;
+                frame.set_cursor_position(Position::new(
+                    values_area.x + BORDER_SIZE + (prefix + message.cursor_index()) as u16,
+                    values_area.y + BORDER_SIZE,
+                ));
+            }
+        }
         frame.render_widget(
             submit_text,
             Layout::horizontal([Constraint::Length(
@@ -317,58 +649,57 @@ impl From<AddFilterType> for TransactionFilter {
     fn from(value: AddFilterType) -> Self {
         match value {
             AddFilterType::TransactionType => TransactionFilter::Type(Default::default()),
+            AddFilterType::Status => TransactionFilter::Status(Default::default()),
             AddFilterType::DateRange => TransactionFilter::DateRange((..).into()),
+            AddFilterType::Message => TransactionFilter::MessageContains(String::new()),
+            AddFilterType::ValueRange => TransactionFilter::ValueRange {
+                min: None,
+                max: None,
+            },
         }
     }
 }
 
-fn filters_as_rows(filters: &[TransactionFilter]) -> impl Iterator<Item = Row> {
-    filters
-        .iter()
-        .map(|filter| Row::new(filter_as_cells(filter).into_iter().map(Cell::from)))
+/// The text a message filter matches against, if it's one
+fn message_text(filter: &TransactionFilter) -> Option<&str> {
+    match filter {
+        TransactionFilter::MessageContains(text) | TransactionFilter::MessageEquals(text) => {
+            Some(text)
+        }
+        TransactionFilter::Not(filter) => message_text(filter),
+        _ => None,
+    }
 }
 
-fn filter_as_cells(filter: &TransactionFilter) -> [String; 2] {
+/// How a message filter matches, shown before its text
+fn message_mode(filter: &TransactionFilter) -> Option<&'static str> {
     match filter {
-        TransactionFilter::UserId(ids) => [
-            String::from("user id must be"),
-            Itertools::intersperse(
-                ids.iter().map(|v| Cow::from(v.to_string())),
-                Cow::from(" or "),
-            )
-            .collect(),
-        ],
-        TransactionFilter::Type(transaction_types) => [
-            String::from("transaction type must be"),
-            Itertools::intersperse(
-                transaction_types
-                    .kv_pairs()
-                    .filter(|&(_, selected)| *selected)
-                    .map(|(t_type, _)| Cow::from(t_type.to_string())),
-                Cow::from(" or "),
-            )
-            .collect(),
-        ],
-        TransactionFilter::DateRange(date_range) => {
-            [String::from("date must be within"), date_range.to_string()]
-        }
-        TransactionFilter::Id(ids) => [
-            String::from("transaction id must be"),
-            Itertools::intersperse(
-                ids.iter().map(|v| Cow::from(v.to_string())),
-                Cow::from(" or "),
-            )
-            .collect(),
-        ],
-        TransactionFilter::Not(filter) => {
-            let mut cells = filter_as_cells(filter);
-            cells[0] = cells[0].replace("must ", "must not ");
-            cells
-        }
+        TransactionFilter::MessageContains(_) => Some("contains"),
+        TransactionFilter::MessageEquals(_) => Some("equals"),
+        TransactionFilter::Not(filter) => message_mode(filter),
+        _ => None,
     }
 }
 
-fn display_filter_values(filter: &TransactionFilter, index: usize) -> Paragraph {
+fn filters_as_rows<'a>(
+    filters: impl Iterator<Item = &'a TransactionFilter>,
+) -> impl Iterator<Item = Row<'a>> {
+    filters.map(|filter| Row::new(filter.as_cells().into_iter().map(Cell::from)))
+}
+
+/// Counts the current user's transactions of each type
+async fn current_type_counts(app: &App) -> Result<TransactionTypeMap<i64>, AppError> {
+    let user_id = app.data.current_user.as_ref().map(|v| v.get_id()).unwrap();
+    Ok(app.data.storage.count_by_type(user_id).await?)
+}
+
+fn display_filter_values<'a>(
+    filter: &'a TransactionFilter,
+    index: usize,
+    type_counts: &TransactionTypeMap<i64>,
+    amount_format: &AmountFormatter,
+    theme: &Theme,
+) -> Paragraph<'a> {
     match filter {
         TransactionFilter::Type(transaction_types) => {
             Paragraph::new(Line::from_iter(Itertools::intersperse(
@@ -376,11 +707,29 @@ fn display_filter_values(filter: &TransactionFilter, index: usize) -> Paragraph
                     .kv_pairs()
                     .enumerate()
                     .map(|(i, (t_type, selected))| {
-                        let text = Span::from(t_type.to_string());
+                        let text = Span::from(format!("{t_type} ({})", type_counts[t_type]));
+                        if i == index {
+                            text.style(theme.active())
+                        } else if *selected {
+                            text.style(theme.highlight())
+                        } else {
+                            text
+                        }
+                    }),
+                Span::from(", "),
+            )))
+        }
+        TransactionFilter::Status(statuses) => {
+            Paragraph::new(Line::from_iter(Itertools::intersperse(
+                statuses
+                    .kv_pairs()
+                    .enumerate()
+                    .map(|(i, (status, selected))| {
+                        let text = Span::from(status.to_string());
                         if i == index {
-                            text.style(Style::default().fg(Color::Black).bg(Color::LightYellow))
+                            text.style(theme.active())
                         } else if *selected {
-                            text.style(Style::default().fg(Color::Black).bg(Color::White))
+                            text.style(theme.highlight())
                         } else {
                             text
                         }
@@ -389,7 +738,34 @@ fn display_filter_values(filter: &TransactionFilter, index: usize) -> Paragraph
             )))
         }
         TransactionFilter::DateRange(date_range) => Paragraph::new(date_range.to_string()),
-        TransactionFilter::Not(filter) => display_filter_values(filter, index),
+        TransactionFilter::MessageContains(text) | TransactionFilter::MessageEquals(text) => {
+            Paragraph::new(Line::from_iter([
+                Span::from(message_mode(filter).unwrap_or_default()).bold(),
+                Span::from(": "),
+                Span::from(text.as_str()),
+            ]))
+        }
+        TransactionFilter::ValueRange { min, max } => {
+            Paragraph::new(Line::from_iter(Itertools::intersperse(
+                [("min", min), ("max", max)]
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, (name, bound))| {
+                        let value =
+                            bound.map_or(String::from("any"), |value| amount_format.format(value));
+                        let text = Span::from(format!("{name}: {value}"));
+                        if i == index {
+                            text.style(theme.active())
+                        } else {
+                            text
+                        }
+                    }),
+                Span::from(", "),
+            )))
+        }
+        TransactionFilter::Not(filter) => {
+            display_filter_values(filter, index, type_counts, amount_format, theme)
+        }
         _ => Paragraph::new(""),
     }
 }
@@ -0,0 +1,261 @@
+use crossterm::event::{self, Event, KeyCode};
+use ratatui::{
+    layout::{Constraint, Flex, Layout, Margin},
+    prelude::*,
+    widgets::{Block, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::{
+    app::{App, AppError, AppMode},
+    config::{Config, Theme},
+    format::{format_utc_offset, parse_utc_offset},
+    storage::User,
+    CursoredString,
+};
+
+use super::{Popup, PopupHandler};
+
+/// Popup walking a new install through its currency, timezone, and first pilot
+pub struct FirstRun {
+    step: SetupStep,
+    currency: CursoredString,
+    timezone: CursoredString,
+    pilot: CursoredString,
+    /// Used for any field left empty
+    default_currency: String,
+    default_timezone: String,
+    error: Option<String>,
+    then: Option<Box<Popup>>,
+}
+
+/// The steps of [`FirstRun`], in the order they're shown
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SetupStep {
+    Currency,
+    Timezone,
+    Pilot,
+}
+
+impl SetupStep {
+    const COUNT: usize = 3;
+
+    fn next(self) -> Option<Self> {
+        match self {
+            SetupStep::Currency => Some(SetupStep::Timezone),
+            SetupStep::Timezone => Some(SetupStep::Pilot),
+            SetupStep::Pilot => None,
+        }
+    }
+
+    fn previous(self) -> Option<Self> {
+        match self {
+            SetupStep::Currency => None,
+            SetupStep::Timezone => Some(SetupStep::Currency),
+            SetupStep::Pilot => Some(SetupStep::Timezone),
+        }
+    }
+
+    /// Explanation shown above the field
+    fn description(self) -> &'static str {
+        match self {
+            SetupStep::Currency => "Welcome! What is the currency you're tracking called?",
+            SetupStep::Timezone => {
+                "Which timezone should dates be shown in? Enter an offset from UTC, such as +02:00"
+            }
+            SetupStep::Pilot => {
+                "Finally, name the first pilot. More can be made from the login screen"
+            }
+        }
+    }
+
+    fn field_title(self) -> &'static str {
+        match self {
+            SetupStep::Currency => "Currency",
+            SetupStep::Timezone => "UTC offset",
+            SetupStep::Pilot => "Pilot name",
+        }
+    }
+}
+
+impl FirstRun {
+    /// Create popup on the first step, suggesting the given config's values
+    pub fn new(config: &Config) -> Self {
+        Self {
+            step: SetupStep::Currency,
+            currency: CursoredString::new(),
+            timezone: CursoredString::new(),
+            pilot: CursoredString::new(),
+            default_currency: config.currency.long.clone(),
+            default_timezone: format_utc_offset(config.timezone),
+            error: None,
+            then: None,
+        }
+    }
+
+    /// Shows the given popup once setup is finished or skipped
+    pub fn then(mut self, popup: Option<Popup>) -> Self {
+        self.then = popup.map(Box::new);
+        self
+    }
+
+    fn field(&self) -> &CursoredString {
+        match self.step {
+            SetupStep::Currency => &self.currency,
+            SetupStep::Timezone => &self.timezone,
+            SetupStep::Pilot => &self.pilot,
+        }
+    }
+
+    fn field_mut(&mut self) -> &mut CursoredString {
+        match self.step {
+            SetupStep::Currency => &mut self.currency,
+            SetupStep::Timezone => &mut self.timezone,
+            SetupStep::Pilot => &mut self.pilot,
+        }
+    }
+
+    /// Checks the current step and moves onto the next, finishing after the last
+    async fn submit(mut self, app: &mut App) -> Result<Option<Popup>, AppError> {
+        match self.step {
+            SetupStep::Timezone
+                if !self.timezone.trim().is_empty()
+                    && parse_utc_offset(&self.timezone).is_none() =>
+            {
+                self.error = Some(format!(
+                    "'{}' isn't an offset like +02:00",
+                    self.timezone.trim()
+                ));
+            }
+            SetupStep::Pilot => {
+                let name = self.pilot.to_lowercase();
+                match User::validate_name(&name) {
+                    Ok(()) => return self.finish(name, app).await,
+                    Err(e) => self.error = Some(e.to_string()),
+                }
+            }
+            step => {
+                self.error = None;
+                self.step = step.next().unwrap_or(step);
+            }
+        }
+        Ok(Some(Popup::FirstRun(self)))
+    }
+
+    /// Saves the chosen settings and logs in as the new pilot
+    async fn finish(self, name: String, app: &mut App) -> Result<Option<Popup>, AppError> {
+        let currency = self.currency.trim();
+        if !currency.is_empty() {
+            app.data.config.currency = currency.into();
+        }
+        if let Some(timezone) = parse_utc_offset(&self.timezone) {
+            app.data.config.timezone = timezone;
+        }
+        app.data.config.save().await?;
+
+        app.data.storage.create_user(&name).await?;
+        let user = app.data.storage.get_user(&name).await?;
+        app.mode = AppMode::LogTable;
+        let popup = app.data.login(user).await?;
+        Ok(popup.or(self.then.map(|popup| *popup)))
+    }
+}
+
+impl PopupHandler for FirstRun {
+    async fn handle_event(
+        mut self,
+        app: &mut App,
+        event: &Event,
+    ) -> Result<Option<Popup>, AppError> {
+        if let Event::Key(key) = event {
+            if key.kind == event::KeyEventKind::Press {
+                match key.code {
+                    KeyCode::Left => self.field_mut().right(),
+                    KeyCode::Right => self.field_mut().left(),
+                    KeyCode::Backspace => self.field_mut().remove_behind(),
+                    KeyCode::Delete => self.field_mut().remove_ahead(),
+                    KeyCode::Insert => {
+                        let field = self.field_mut();
+                        field.inserting = !field.inserting;
+                    }
+                    KeyCode::Enter => return self.submit(app).await,
+                    KeyCode::Esc => {
+                        self.error = None;
+                        match self.step.previous() {
+                            Some(step) => self.step = step,
+                            // the defaults were already written, so this won't be shown again
+                            None => return Ok(self.then.map(|popup| *popup)),
+                        }
+                    }
+                    KeyCode::Char(c) => {
+                        self.error = None;
+                        self.field_mut().insert(c);
+                    }
+                    _ => (),
+                }
+            }
+        }
+        Ok(Some(Popup::FirstRun(self)))
+    }
+
+    fn render_to_frame(&mut self, area: Rect, frame: &mut Frame, theme: &Theme) {
+        const DESCRIPTION_HEIGHT: u16 = 2;
+        const FIELD_HEIGHT: u16 = 1;
+        const BORDER_SIZE: u16 = 1;
+
+        let [area] = Layout::vertical([Constraint::Length(
+            DESCRIPTION_HEIGHT + FIELD_HEIGHT + 4 * BORDER_SIZE,
+        )])
+        .flex(Flex::Center)
+        .areas(area);
+        let [area] = Layout::horizontal([Constraint::Percentage(50)])
+            .flex(Flex::Center)
+            .areas(area);
+        let step_number = self.step as usize + 1;
+        let hint = if self.step.previous().is_some() {
+            "Enter: next, Esc: back"
+        } else {
+            "Enter: next, Esc: skip setup"
+        };
+        let block = Block::bordered()
+            .title("First Run Setup")
+            .title(Line::from(format!("{step_number}/{}", SetupStep::COUNT)).right_aligned())
+            .title_bottom(Line::from(hint).dim().right_aligned());
+        frame.render_widget(Clear, area);
+        frame.render_widget(block, area);
+        let area = area.inner(Margin::new(BORDER_SIZE, BORDER_SIZE));
+        let [description_area, field_area] = Layout::vertical([
+            Constraint::Length(DESCRIPTION_HEIGHT),
+            Constraint::Length(FIELD_HEIGHT + BORDER_SIZE * 2),
+        ])
+        .areas(area);
+
+        frame.render_widget(
+            Paragraph::new(self.step.description()).wrap(Wrap { trim: true }),
+            description_area,
+        );
+
+        let mut field_block = Block::bordered()
+            .title(self.step.field_title())
+            .style(theme.active());
+        if let Some(error) = &self.error {
+            field_block = field_block.title(Line::from(error.as_str()).red());
+        }
+        let field = self.field();
+        // an empty field keeps the default, so show what that is
+        let text = match self.step {
+            SetupStep::Currency if field.is_empty() => {
+                Line::from(self.default_currency.as_str()).dim()
+            }
+            SetupStep::Timezone if field.is_empty() => {
+                Line::from(self.default_timezone.as_str()).dim()
+            }
+            _ => Line::from(field.as_str()),
+        };
+        frame.render_widget(Paragraph::new(text).block(field_block), field_area);
+        frame.set_cursor_position(Position::new(
+            field_area.x + field.cursor_index() as u16 + BORDER_SIZE,
+            field_area.y + BORDER_SIZE,
+        ));
+    }
+}
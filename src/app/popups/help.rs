@@ -0,0 +1,138 @@
+use crossterm::event::{self, Event, KeyCode};
+use ratatui::{
+    layout::{Constraint, Flex, Layout},
+    prelude::*,
+    widgets::{Block, Clear, Row, Table, TableState},
+    Frame,
+};
+
+use crate::{
+    app::{App, AppError},
+    config::{Config, Keybindings, Theme},
+};
+
+use super::{Popup, PopupHandler};
+
+/// Popup listing the table's keys and what each does, using the keys as configured
+pub struct Help {
+    /// Each key as it's shown beside what it does
+    rows: Vec<(String, &'static str)>,
+    table_state: TableState,
+}
+
+impl Help {
+    /// Create popup listing the keys the config has the table using
+    pub fn new(config: &Config) -> Self {
+        let keys = &config.keybindings;
+        let key = |c: char| c.to_string();
+        let esc = if config.esc_quits {
+            "Quit"
+        } else {
+            "Clear the selection or leave the deleted view"
+        };
+        let rows = vec![
+            (String::from("↑/↓ PgUp/PgDn"), "Move through the table"),
+            (key(config.quit_key), "Quit"),
+            (String::from("Esc"), esc),
+            (String::from("Enter"), "Show the transaction and its notes"),
+            (key(keys.add), "Add a transaction"),
+            (key(keys.quick_add), "Quick add from one line"),
+            (key(Keybindings::REPEAT), "Repeat your last transaction now"),
+            (key(keys.edit), "Edit the transaction"),
+            (key(keys.delete), "Delete the transaction"),
+            (String::from("Space"), "Select or deselect the transaction"),
+            (key(keys.set_type), "Set the type of the selection"),
+            (
+                key(keys.toggle_status),
+                "Toggle the selection cleared/pending",
+            ),
+            (key(Keybindings::SPLIT), "Split the transaction"),
+            (key(keys.filter), "Filters"),
+            (
+                format!("{}/{}", keys.undo_filters, Keybindings::REDO_FILTERS),
+                "Undo or redo filter changes",
+            ),
+            (key(keys.summary), "Summary"),
+            (key(keys.copy_total), "Copy the filtered total"),
+            (key(keys.export), "Export the table"),
+            (key(Keybindings::EXPORT_SELECTION), "Export the selection"),
+            (
+                key(Keybindings::SHOW_DELETED),
+                "Show deleted transactions, or go back",
+            ),
+            (key(Keybindings::RESTORE), "Restore deleted transactions"),
+            (
+                key(Keybindings::PURGE),
+                "Permanently delete deleted transactions",
+            ),
+            (key(Keybindings::BIG_BALANCE), "Big balance display"),
+            (key(Keybindings::SESSION), "Start or end a session"),
+            (
+                key(Keybindings::COMBINE),
+                "Show other pilots' transactions too",
+            ),
+            (key(Keybindings::TRANSFER), "Transfer to another pilot"),
+            (key(Keybindings::MERGE), "Merge another pilot into yours"),
+            (key(keys.reasons), "Reason codes"),
+            (key(keys.pin), "Set or remove your PIN"),
+            (key(keys.utc), "Toggle UTC times"),
+            (key(Keybindings::VAULT), "Switch vault"),
+            (key(keys.version), "Version"),
+            (key(keys.logout), "Log out"),
+            (key(Keybindings::HELP), "This help"),
+        ];
+        Self {
+            rows,
+            table_state: TableState::default().with_selected(Some(0)),
+        }
+    }
+}
+
+impl PopupHandler for Help {
+    async fn handle_event(
+        mut self,
+        app: &mut App,
+        event: &Event,
+    ) -> Result<Option<Popup>, AppError> {
+        if let Event::Key(key) = event {
+            if key.kind == event::KeyEventKind::Press {
+                match app.data.config.nav_key(key.code) {
+                    KeyCode::Up => self.table_state.select_previous(),
+                    KeyCode::Down => self.table_state.select_next(),
+                    KeyCode::Esc | KeyCode::Enter | KeyCode::Char('?') => return Ok(None),
+                    _ => (),
+                }
+            }
+        }
+        Ok(Some(Popup::Help(self)))
+    }
+
+    fn render_to_frame(&mut self, area: Rect, frame: &mut Frame, theme: &Theme) {
+        let [area] = Layout::vertical([Constraint::Percentage(70)])
+            .flex(Flex::Center)
+            .areas(area);
+        let [area] = Layout::horizontal([Constraint::Percentage(50)])
+            .flex(Flex::Center)
+            .areas(area);
+        let key_width = self
+            .rows
+            .iter()
+            .map(|(key, _)| key.chars().count())
+            .max()
+            .unwrap_or_default() as u16;
+        let rows = self
+            .rows
+            .iter()
+            .map(|(key, action)| Row::new([Line::from(key.as_str()).bold(), Line::from(*action)]));
+        let table = Table::new(rows, [Constraint::Length(key_width), Constraint::Fill(1)])
+            .column_spacing(2)
+            .block(
+                Block::bordered()
+                    .title("Keys")
+                    .title_bottom(Line::from("Esc to close").dim()),
+            )
+            .highlight_style(theme.highlight());
+        frame.render_widget(Clear, area);
+        frame.render_stateful_widget(table, area, &mut self.table_state);
+    }
+}
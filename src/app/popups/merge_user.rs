@@ -0,0 +1,220 @@
+use crossterm::event::{self, Event, KeyCode};
+use ratatui::{
+    layout::{Constraint, Flex, Layout, Margin},
+    prelude::*,
+    widgets::{Block, Clear, Paragraph, Tabs},
+    Frame,
+};
+
+use crate::{
+    app::{App, AppError},
+    config::Theme,
+    format,
+    storage::{StorageRunError, TransactionFilter},
+    CursoredString,
+};
+
+use super::{yes_no_clicked, Popup, PopupHandler};
+
+/// Popup for folding another pilot into the current one, asking for their name then previewing what will move
+pub struct MergeUser {
+    from: CursoredString,
+    error: Option<String>,
+    /// Number of transactions that will move once a valid name has been entered
+    preview: Option<i64>,
+    should_merge: bool,
+    /// Area of the Yes/No choice as last rendered, for mouse clicks
+    question_area: Rect,
+}
+
+impl MergeUser {
+    /// Create popup with an empty name and 'No' selected for the confirmation
+    pub fn new() -> Self {
+        Self {
+            from: CursoredString::new(),
+            error: None,
+            preview: None,
+            should_merge: false,
+            question_area: Rect::default(),
+        }
+    }
+
+    /// Checks the typed name can be merged and counts its transactions, merging straight away in fast mode
+    async fn check(mut self, app: &mut App) -> Result<Option<Popup>, AppError> {
+        let current = app.data.current_user.as_ref().unwrap();
+        let from = match app.data.storage.get_user(&self.from).await {
+            Ok(user) => user,
+            Err(StorageRunError::RecordMissing) => {
+                self.error = Some(format!("No pilot named '{}'", self.from.as_str()));
+                return Ok(Some(Popup::MergeUser(self)));
+            }
+            Err(error) => return Err(error.into()),
+        };
+        if from.get_id() == current.get_id() {
+            self.error = Some(StorageRunError::MergeIntoSelf.to_string());
+            return Ok(Some(Popup::MergeUser(self)));
+        }
+        // merging takes everything the other pilot has, so don't let it get around their PIN
+        if from.has_pin() {
+            self.error = Some(format!(
+                "'{}' has a PIN, remove it first",
+                app.data.config.display_name(from.get_name())
+            ));
+            return Ok(Some(Popup::MergeUser(self)));
+        }
+        if app
+            .data
+            .storage
+            .share_transfers(from.get_id(), current.get_id())
+            .await?
+        {
+            self.error = Some(StorageRunError::MergeSharedTransfers.to_string());
+            return Ok(Some(Popup::MergeUser(self)));
+        }
+
+        if app.data.config.fast_mode {
+            return self.merge(app).await;
+        }
+        let count = app
+            .data
+            .storage
+            .count_transactions(&[TransactionFilter::UserId(vec![from.get_id()])])
+            .await?;
+        self.preview = Some(count);
+        Ok(Some(Popup::MergeUser(self)))
+    }
+
+    /// Moves everything from the typed pilot into the current one
+    async fn merge(self, app: &mut App) -> Result<Option<Popup>, AppError> {
+        let into = app
+            .data
+            .current_user
+            .as_ref()
+            .unwrap()
+            .get_name()
+            .to_owned();
+        let moved = app.data.storage.merge_users(&self.from, &into).await?;
+        app.data.status_text = format!(
+            "Merged '{}' into '{}', moving {moved} transactions",
+            app.data.config.display_name(&self.from),
+            app.data.config.display_name(&into)
+        );
+        app.data.update_table().await?;
+        Ok(None)
+    }
+}
+
+impl Default for MergeUser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PopupHandler for MergeUser {
+    async fn handle_event(
+        mut self,
+        app: &mut App,
+        event: &Event,
+    ) -> Result<Option<Popup>, AppError> {
+        if self.preview.is_some() {
+            let mut confirmed = false;
+            if let Some(choice) = yes_no_clicked(event, self.question_area) {
+                self.should_merge = choice;
+                confirmed = true;
+            } else if let Event::Key(key) = event {
+                if key.kind == event::KeyEventKind::Press {
+                    match key.code {
+                        KeyCode::Left | KeyCode::BackTab | KeyCode::Right | KeyCode::Tab => {
+                            self.should_merge = !self.should_merge;
+                        }
+                        KeyCode::Enter => confirmed = true,
+                        // back to the name
+                        KeyCode::Esc => {
+                            self.preview = None;
+                            self.should_merge = false;
+                        }
+                        _ => (),
+                    }
+                }
+            }
+            if confirmed {
+                if self.should_merge {
+                    return self.merge(app).await;
+                }
+                return Ok(None);
+            }
+        } else if let Event::Key(key) = event {
+            if key.kind == event::KeyEventKind::Press {
+                match key.code {
+                    KeyCode::Left => self.from.right(),
+                    KeyCode::Right => self.from.left(),
+                    KeyCode::Backspace => self.from.remove_behind(),
+                    KeyCode::Delete => self.from.remove_ahead(),
+                    KeyCode::Insert => self.from.inserting = !self.from.inserting,
+                    KeyCode::Enter if !self.from.is_empty() => return self.check(app).await,
+                    KeyCode::Esc => return Ok(None),
+                    KeyCode::Char(c) if !c.is_whitespace() => {
+                        self.error = None;
+                        self.from.insert(c);
+                    }
+                    _ => (),
+                }
+            }
+        }
+        Ok(Some(Popup::MergeUser(self)))
+    }
+
+    fn render_to_frame(&mut self, area: Rect, frame: &mut Frame, theme: &Theme) {
+        const FIELD_HEIGHT: u16 = 1;
+        const BORDER_SIZE: u16 = 1;
+
+        let [area] = Layout::vertical([Constraint::Length(FIELD_HEIGHT + 4 * BORDER_SIZE)])
+            .flex(Flex::Center)
+            .areas(area);
+        let [area] = Layout::horizontal([Constraint::Percentage(40)])
+            .flex(Flex::Center)
+            .areas(area);
+        let block = Block::bordered().title("Merge Pilot");
+        frame.render_widget(Clear, area);
+        frame.render_widget(block, area);
+        let area = area.inner(Margin::new(BORDER_SIZE, BORDER_SIZE));
+        let [field_area] =
+            Layout::vertical([Constraint::Length(FIELD_HEIGHT + BORDER_SIZE * 2)]).areas(area);
+        let active_style = theme.active();
+
+        match self.preview {
+            Some(count) => {
+                let without_name = format!("Move {count} transactions from '' and delete them?");
+                let title_width = usize::from(field_area.width.saturating_sub(2 * BORDER_SIZE));
+                let name =
+                    format::truncate(&self.from, title_width.saturating_sub(without_name.len()));
+                let question_field = Block::bordered()
+                    .title(format!(
+                        "Move {count} transactions from '{name}' and delete them?"
+                    ))
+                    .style(active_style);
+                let question_text = Tabs::new(["No", "Yes"])
+                    .select(self.should_merge as usize)
+                    .block(question_field);
+                frame.render_widget(question_text, field_area);
+                self.question_area = field_area;
+            }
+            None => {
+                let mut name_field = Block::bordered()
+                    .title("Pilot to merge into this one")
+                    .style(active_style);
+                if let Some(error) = &self.error {
+                    name_field = name_field.title(Line::from(error.as_str()).red());
+                }
+                let (visible, cursor) = self.from.visible(usize::from(
+                    field_area.width.saturating_sub(2 * BORDER_SIZE),
+                ));
+                frame.render_widget(Paragraph::new(visible).block(name_field), field_area);
+                frame.set_cursor_position(Position::new(
+                    field_area.x + cursor as u16 + BORDER_SIZE,
+                    field_area.y + BORDER_SIZE,
+                ));
+            }
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use crossterm::event::{self, Event, KeyCode};
+use ratatui::{
+    layout::{Constraint, Flex, Layout, Margin},
+    prelude::*,
+    widgets::{Block, Clear, Paragraph, Tabs},
+    Frame,
+};
+
+use crate::{
+    app::{App, AppError},
+    config::Theme,
+    storage::LegacyData,
+};
+
+use super::{yes_no_clicked, Popup, PopupHandler};
+
+/// Popup offering to import the data of apps mantra replaces, one app at a time
+pub struct MigrateLegacy {
+    /// Apps still to be asked about, the first being the one currently shown
+    pending: Vec<LegacyData>,
+    should_import: bool,
+    /// Area of the Yes/No choice as last rendered, for mouse clicks
+    question_area: Rect,
+}
+
+impl MigrateLegacy {
+    /// Create popup with 'Yes' selected by default, or None if there's nothing to ask about
+    pub fn new(pending: Vec<LegacyData>) -> Option<Self> {
+        if pending.is_empty() {
+            return None;
+        }
+        Some(Self {
+            pending,
+            should_import: true,
+            question_area: Rect::default(),
+        })
+    }
+}
+
+impl PopupHandler for MigrateLegacy {
+    async fn handle_event(
+        mut self,
+        app: &mut App,
+        event: &Event,
+    ) -> Result<Option<Popup>, AppError> {
+        let mut confirmed = false;
+        if let Some(choice) = yes_no_clicked(event, self.question_area) {
+            self.should_import = choice;
+            confirmed = true;
+        } else if let Event::Key(key) = event {
+            if key.kind == event::KeyEventKind::Press {
+                match key.code {
+                    KeyCode::Left | KeyCode::BackTab | KeyCode::Right | KeyCode::Tab => {
+                        self.should_import = !self.should_import;
+                    }
+                    KeyCode::Enter => confirmed = true,
+                    // asks again next time
+                    KeyCode::Esc => return Ok(None),
+                    _ => (),
+                }
+            }
+        }
+        if confirmed {
+            let legacy = self.pending.remove(0);
+            if self.should_import {
+                let imported = app.data.storage.import_legacy(&legacy).await?;
+                app.data.status_text =
+                    format!("Imported {imported} transactions from {}", legacy.app);
+            } else {
+                app.data.storage.mark_migrated(&legacy.app).await?;
+            }
+            return Ok(MigrateLegacy::new(self.pending).map(Popup::MigrateLegacy));
+        }
+        Ok(Some(Popup::MigrateLegacy(self)))
+    }
+
+    fn render_to_frame(&mut self, area: Rect, frame: &mut Frame, theme: &Theme) {
+        const PATH_HEIGHT: u16 = 1;
+        const QUESTION_HEIGHT: u16 = 1;
+        const BORDER_SIZE: u16 = 1;
+
+        let Some(legacy) = self.pending.first() else {
+            return;
+        };
+        let [area] = Layout::vertical([Constraint::Length(
+            PATH_HEIGHT + QUESTION_HEIGHT + 4 * BORDER_SIZE,
+        )])
+        .flex(Flex::Center)
+        .areas(area);
+        let [area] = Layout::horizontal([Constraint::Percentage(40)])
+            .flex(Flex::Center)
+            .areas(area);
+        let block = Block::bordered().title("Import Legacy Data");
+        frame.render_widget(Clear, area);
+        frame.render_widget(block, area);
+        let area = area.inner(Margin::new(BORDER_SIZE, BORDER_SIZE));
+        let [path_area, question_area] = Layout::vertical([
+            Constraint::Length(PATH_HEIGHT),
+            Constraint::Length(QUESTION_HEIGHT + BORDER_SIZE * 2),
+        ])
+        .areas(area);
+
+        let question_field = Block::bordered()
+            .title(format!(
+                "Import pilots and transactions from {}?",
+                legacy.app
+            ))
+            .style(theme.active());
+
+        let question_text = Tabs::new(["No", "Yes"])
+            .select(self.should_import as usize)
+            .block(question_field);
+
+        frame.render_widget(
+            Paragraph::new(legacy.path.to_string_lossy()).dim(),
+            path_area,
+        );
+        frame.render_widget(question_text, question_area);
+        self.question_area = question_area;
+    }
+}
@@ -0,0 +1,73 @@
+use crossterm::event::{self, Event, KeyCode};
+use ratatui::{
+    layout::{Constraint, Flex, Layout},
+    prelude::*,
+    widgets::{Block, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::{
+    app::{App, AppError},
+    config::Theme,
+};
+
+use super::{Popup, PopupHandler};
+
+/// Popup showing a message until dismissed, then any popup queued up behind it
+pub struct Notice {
+    title: String,
+    text: String,
+    then: Option<Box<Popup>>,
+}
+
+impl Notice {
+    /// Create popup with the given title and message
+    pub fn new(title: impl Into<String>, text: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            text: text.into(),
+            then: None,
+        }
+    }
+
+    /// Shows the given popup once this one is dismissed
+    pub fn then(mut self, popup: Option<Popup>) -> Self {
+        self.then = popup.map(Box::new);
+        self
+    }
+}
+
+impl PopupHandler for Notice {
+    async fn handle_event(self, _app: &mut App, event: &Event) -> Result<Option<Popup>, AppError> {
+        if let Event::Key(key) = event {
+            if key.kind == event::KeyEventKind::Press {
+                if let KeyCode::Enter | KeyCode::Esc = key.code {
+                    return Ok(self.then.map(|popup| *popup));
+                }
+            }
+        }
+        Ok(Some(Popup::Notice(self)))
+    }
+
+    fn render_to_frame(&mut self, area: Rect, frame: &mut Frame, _theme: &Theme) {
+        const TEXT_HEIGHT: u16 = 6;
+        const BORDER_SIZE: u16 = 1;
+
+        let [area] = Layout::vertical([Constraint::Length(TEXT_HEIGHT + 2 * BORDER_SIZE)])
+            .flex(Flex::Center)
+            .areas(area);
+        let [area] = Layout::horizontal([Constraint::Percentage(50)])
+            .flex(Flex::Center)
+            .areas(area);
+        let block = Block::bordered()
+            .title(self.title.as_str())
+            .title_bottom(Line::from("Enter to continue").dim().right_aligned());
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(self.text.as_str())
+                .wrap(Wrap { trim: false })
+                .block(block),
+            area,
+        );
+    }
+}
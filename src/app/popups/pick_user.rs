@@ -0,0 +1,111 @@
+use crossterm::event::{self, Event, KeyCode};
+use ratatui::{
+    layout::{Constraint, Flex, Layout, Margin},
+    prelude::*,
+    widgets::{Block, Clear, List, ListState},
+    Frame,
+};
+
+use crate::{
+    app::{App, AppError, AppMode},
+    config::{Config, Theme},
+    storage::User,
+};
+
+use super::{EnterPin, Popup, PopupHandler};
+
+/// Popup listing every pilot to log in as without typing their name
+pub struct PickUser {
+    users: Vec<User>,
+    /// Each user's name as it's shown
+    names: Vec<String>,
+    list_state: ListState,
+}
+
+impl PickUser {
+    /// Create popup listing the given users, with the first highlighted
+    pub fn new(users: Vec<User>, config: &Config) -> Self {
+        let names = users
+            .iter()
+            .map(|user| config.display_name(user.get_name()).into_owned())
+            .collect();
+        Self {
+            users,
+            names,
+            list_state: ListState::default().with_selected(Some(0)),
+        }
+    }
+}
+
+impl PopupHandler for PickUser {
+    async fn handle_event(
+        mut self,
+        app: &mut App,
+        event: &Event,
+    ) -> Result<Option<Popup>, AppError> {
+        if let Event::Key(key) = event {
+            if key.kind == event::KeyEventKind::Press {
+                match app.data.config.nav_key(key.code) {
+                    KeyCode::Up => self.list_state.select_previous(),
+                    KeyCode::Down => self.list_state.select_next(),
+                    KeyCode::Enter => {
+                        let Some(index) = self
+                            .list_state
+                            .selected()
+                            .filter(|&index| index < self.users.len())
+                        else {
+                            return Ok(Some(Popup::PickUser(self)));
+                        };
+                        let user = self.users.swap_remove(index);
+                        // a protected pilot still needs their PIN
+                        if user.has_pin() {
+                            return Ok(Some(Popup::EnterPin(EnterPin::new(
+                                user,
+                                &app.data.config,
+                            ))));
+                        }
+                        app.mode = AppMode::LogTable;
+                        return app.data.login(user).await;
+                    }
+                    KeyCode::Esc | KeyCode::Tab => return Ok(None),
+                    _ => (),
+                }
+            }
+        }
+        Ok(Some(Popup::PickUser(self)))
+    }
+
+    fn render_to_frame(&mut self, area: Rect, frame: &mut Frame, theme: &Theme) {
+        const LIST_HEIGHT: u16 = 8;
+        const BORDER_SIZE: u16 = 1;
+
+        let [area] = Layout::vertical([Constraint::Length(LIST_HEIGHT + 4 * BORDER_SIZE)])
+            .flex(Flex::Center)
+            .areas(area);
+        let [area] = Layout::horizontal([Constraint::Percentage(40)])
+            .flex(Flex::Center)
+            .areas(area);
+        let block = Block::bordered()
+            .title("Pilots")
+            .title_bottom(Line::from("Enter: log in, Esc: type a name").dim());
+        frame.render_widget(Clear, area);
+        frame.render_widget(block, area);
+        let list_area = area.inner(Margin::new(BORDER_SIZE, BORDER_SIZE));
+
+        let list_block = Block::bordered().style(theme.active());
+        if self.names.is_empty() {
+            frame.render_widget(
+                Line::from("No pilots yet, Ctrl+N creates one")
+                    .dim()
+                    .centered(),
+                list_block.inner(list_area),
+            );
+            frame.render_widget(list_block, list_area);
+            return;
+        }
+        let list = List::new(self.names.iter().map(String::as_str))
+            .block(list_block)
+            .highlight_style(theme.highlight());
+        frame.render_stateful_widget(list, list_area, &mut self.list_state);
+    }
+}
@@ -0,0 +1,235 @@
+use crossterm::event::{self, Event, KeyCode};
+use ratatui::{
+    layout::{Constraint, Flex, Layout, Margin},
+    prelude::*,
+    widgets::{Block, Clear, Paragraph},
+    Frame,
+};
+
+use crate::{
+    app::{App, AppError, AppMode},
+    config::{Config, Theme},
+    format,
+    storage::User,
+    CursoredString,
+};
+
+use super::{Popup, PopupHandler};
+
+/// The shortest PIN that can be set
+const MIN_PIN_LEN: usize = 4;
+
+/// Popup asking for the PIN of a protected user before logging in
+pub struct EnterPin {
+    user: User,
+    /// The user's name as it's shown
+    display_name: String,
+    pin: CursoredString,
+    rejected: bool,
+}
+
+/// Popup for setting, changing, or removing the current user's PIN
+pub struct SetPin {
+    pin: CursoredString,
+    confirm: CursoredString,
+    confirming: bool,
+    error: Option<String>,
+}
+
+impl EnterPin {
+    /// Create popup for logging in as the given user
+    pub fn new(user: User, config: &Config) -> Self {
+        Self {
+            display_name: config.display_name(user.get_name()).into_owned(),
+            user,
+            pin: CursoredString::new(),
+            rejected: false,
+        }
+    }
+}
+
+impl SetPin {
+    /// Create popup with both fields empty
+    pub fn new() -> Self {
+        Self {
+            pin: CursoredString::new(),
+            confirm: CursoredString::new(),
+            confirming: false,
+            error: None,
+        }
+    }
+
+    /// The field currently being typed in
+    fn field(&mut self) -> &mut CursoredString {
+        if self.confirming {
+            &mut self.confirm
+        } else {
+            &mut self.pin
+        }
+    }
+}
+
+impl Default for SetPin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PopupHandler for EnterPin {
+    async fn handle_event(
+        mut self,
+        app: &mut App,
+        event: &Event,
+    ) -> Result<Option<Popup>, AppError> {
+        if let Event::Key(key) = event {
+            if key.kind == event::KeyEventKind::Press {
+                match key.code {
+                    KeyCode::Enter => {
+                        if self.user.verify_pin(&self.pin) {
+                            app.mode = AppMode::LogTable;
+                            return app.data.login(self.user).await;
+                        }
+                        self.rejected = true;
+                        self.pin = CursoredString::new();
+                    }
+                    KeyCode::Backspace => self.pin.remove_behind(),
+                    KeyCode::Esc => return Ok(None),
+                    KeyCode::Char(c) if !c.is_whitespace() => {
+                        self.pin.insert(c);
+                    }
+                    _ => (),
+                }
+            }
+        }
+        Ok(Some(Popup::EnterPin(self)))
+    }
+
+    fn render_to_frame(&mut self, area: Rect, frame: &mut Frame, theme: &Theme) {
+        let [pin_area] = pin_box_areas("Login", area, frame);
+        let title = if self.rejected {
+            String::from("Wrong PIN, try again")
+        } else {
+            let title_width = usize::from(pin_area.width.saturating_sub(2));
+            let name = format::truncate(
+                &self.display_name,
+                title_width.saturating_sub("PIN for ''".len()),
+            );
+            format!("PIN for '{name}'")
+        };
+        render_pin_field(&title, &self.pin, true, pin_area, frame, theme);
+    }
+}
+
+impl PopupHandler for SetPin {
+    async fn handle_event(
+        mut self,
+        app: &mut App,
+        event: &Event,
+    ) -> Result<Option<Popup>, AppError> {
+        if let Event::Key(key) = event {
+            if key.kind == event::KeyEventKind::Press {
+                match key.code {
+                    KeyCode::Up | KeyCode::Down | KeyCode::Tab | KeyCode::BackTab => {
+                        self.confirming = !self.confirming
+                    }
+                    KeyCode::Enter if !self.confirming => self.confirming = true,
+                    KeyCode::Enter => {
+                        if self.pin.as_str() != self.confirm.as_str() {
+                            self.error = Some(String::from("PINs don't match"));
+                        } else if !self.pin.is_empty() && self.pin.chars().count() < MIN_PIN_LEN {
+                            self.error =
+                                Some(format!("PIN must be at least {MIN_PIN_LEN} characters"));
+                        } else {
+                            let user_id =
+                                app.data.current_user.as_ref().map(|v| v.get_id()).unwrap();
+                            let pin = (!self.pin.is_empty()).then_some(self.pin.as_str());
+                            app.data.storage.set_pin(user_id, pin).await?;
+                            app.data.status_text = match pin {
+                                Some(_) => String::from("PIN set"),
+                                None => String::from("PIN removed"),
+                            };
+                            return Ok(None);
+                        }
+                    }
+                    KeyCode::Backspace => self.field().remove_behind(),
+                    KeyCode::Esc => return Ok(None),
+                    KeyCode::Char(c) if !c.is_whitespace() => {
+                        self.field().insert(c);
+                    }
+                    _ => (),
+                }
+            }
+        }
+        Ok(Some(Popup::SetPin(self)))
+    }
+
+    fn render_to_frame(&mut self, area: Rect, frame: &mut Frame, theme: &Theme) {
+        let [pin_area, confirm_area, hint_area] = pin_box_areas("Set PIN", area, frame);
+        render_pin_field(
+            "New PIN (empty to remove)",
+            &self.pin,
+            !self.confirming,
+            pin_area,
+            frame,
+            theme,
+        );
+        render_pin_field(
+            "Confirm PIN",
+            &self.confirm,
+            self.confirming,
+            confirm_area,
+            frame,
+            theme,
+        );
+        if let Some(error) = &self.error {
+            frame.render_widget(
+                Paragraph::new(error.as_str())
+                    .alignment(Alignment::Center)
+                    .red(),
+                hint_area,
+            );
+        }
+    }
+}
+
+/// Lays out a centered box with room for `N` bordered fields
+fn pin_box_areas<const N: usize>(title: &str, area: Rect, frame: &mut Frame) -> [Rect; N] {
+    const FIELD_HEIGHT: u16 = 1;
+    const BORDER_SIZE: u16 = 1;
+    let fields = N as u16;
+
+    let [area] = Layout::vertical([Constraint::Length(
+        fields * (FIELD_HEIGHT + 2 * BORDER_SIZE) + 2 * BORDER_SIZE,
+    )])
+    .flex(Flex::Center)
+    .areas(area);
+    let [area] = Layout::horizontal([Constraint::Percentage(40)])
+        .flex(Flex::Center)
+        .areas(area);
+    let block = Block::bordered().title(title);
+    frame.render_widget(Clear, area);
+    frame.render_widget(block, area);
+    let area = area.inner(Margin::new(BORDER_SIZE, BORDER_SIZE));
+    Layout::vertical([Constraint::Length(FIELD_HEIGHT + BORDER_SIZE * 2); N]).areas(area)
+}
+
+/// Renders a PIN field with its characters masked
+pub(super) fn render_pin_field(
+    title: &str,
+    pin: &CursoredString,
+    active: bool,
+    area: Rect,
+    frame: &mut Frame,
+    theme: &Theme,
+) {
+    let mut field = Block::bordered().title(title);
+    if active {
+        field = field.style(theme.active());
+        frame.set_cursor_position(Position::new(
+            area.x + pin.chars().count() as u16 + 1,
+            area.y + 1,
+        ));
+    }
+    let masked = "*".repeat(pin.chars().count());
+    frame.render_widget(Paragraph::new(masked).block(field), area);
+}
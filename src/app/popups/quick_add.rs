@@ -0,0 +1,132 @@
+use crossterm::event::{self, Event, KeyCode};
+use ratatui::{
+    layout::{Constraint, Layout},
+    prelude::*,
+    widgets::{Block, Clear, Paragraph},
+    Frame,
+};
+
+use crate::{
+    app::{App, AppError},
+    config::Theme,
+    parse_quick_entry, storage, CursoredString,
+};
+
+use super::{Popup, PopupHandler};
+
+/// One line entry bar over the status bar, for adding a transaction as `<amount> [type] [message]`
+pub struct QuickAdd {
+    input: CursoredString,
+    error: Option<String>,
+}
+
+impl QuickAdd {
+    /// Create an empty entry bar
+    pub fn new() -> Self {
+        Self {
+            input: CursoredString::new(),
+            error: None,
+        }
+    }
+
+    /// Adds the typed transaction, or keeps the bar open with an error explaining why it couldn't
+    async fn submit(mut self, app: &mut App) -> Result<Option<Popup>, AppError> {
+        let entry = match parse_quick_entry(&self.input) {
+            Ok(entry) => entry,
+            Err(error) => {
+                self.error = Some(error.to_string());
+                return Ok(Some(Popup::QuickAdd(self)));
+            }
+        };
+        if entry.msg.is_empty()
+            && app
+                .data
+                .config
+                .require_message
+                .contains(&entry.transaction_type)
+        {
+            self.error = Some(format!("Message required for {}", entry.transaction_type));
+            return Ok(Some(Popup::QuickAdd(self)));
+        }
+
+        let user_id = app.data.current_user.as_ref().map(|v| v.get_id()).unwrap();
+        let trans_id = app
+            .data
+            .storage
+            .add_transaction_at(
+                user_id,
+                entry.amount,
+                entry.transaction_type,
+                &entry.msg,
+                storage::now_utc(),
+            )
+            .await?;
+        app.data.tag_session(trans_id).await?;
+        app.data.status_text = format!(
+            "Added {} {}",
+            app.data.config.amount_format.format(entry.amount),
+            entry.transaction_type
+        );
+        app.data.update_table().await?;
+        Ok(None)
+    }
+}
+
+impl Default for QuickAdd {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PopupHandler for QuickAdd {
+    async fn handle_event(
+        mut self,
+        app: &mut App,
+        event: &Event,
+    ) -> Result<Option<Popup>, AppError> {
+        if let Event::Key(key) = event {
+            if key.kind == event::KeyEventKind::Press {
+                match key.code {
+                    KeyCode::Left => self.input.right(),
+                    KeyCode::Right => self.input.left(),
+                    KeyCode::Backspace => self.input.remove_behind(),
+                    KeyCode::Delete => self.input.remove_ahead(),
+                    KeyCode::Insert => self.input.inserting = !self.input.inserting,
+                    KeyCode::Enter => return self.submit(app).await,
+                    KeyCode::Esc => return Ok(None),
+                    KeyCode::Char(c) => {
+                        self.error = None;
+                        self.input.insert(c);
+                    }
+                    _ => (),
+                }
+            }
+        }
+        Ok(Some(Popup::QuickAdd(self)))
+    }
+
+    fn render_to_frame(&mut self, area: Rect, frame: &mut Frame, theme: &Theme) {
+        const INPUT_HEIGHT: u16 = 1;
+        const BORDER_SIZE: u16 = 1;
+
+        // sits over the status bar at the bottom of the table
+        let [_, area] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(INPUT_HEIGHT + 2 * BORDER_SIZE),
+        ])
+        .areas(area);
+        let mut block = Block::bordered()
+            .title("Quick Add: <amount> [type] [message]")
+            .style(theme.active());
+        if let Some(error) = &self.error {
+            block = block.title(Line::from(error.as_str()).red());
+        }
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(Paragraph::new(self.input.as_str()).block(block), area);
+        frame.set_cursor_position(Position::new(
+            area.x + self.input.cursor_index() as u16 + BORDER_SIZE,
+            area.y + BORDER_SIZE,
+        ));
+    }
+}
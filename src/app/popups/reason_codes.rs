@@ -0,0 +1,249 @@
+use crossterm::event::{self, Event, KeyCode};
+use ratatui::{
+    layout::{Constraint, Flex, Layout, Margin},
+    prelude::*,
+    widgets::{Block, Clear, Paragraph, Row, Table, TableState},
+    Frame,
+};
+
+use crate::{
+    app::{App, AppData, AppError},
+    config::Theme,
+    format::AmountFormatter,
+    storage::{ReasonCode, ReasonTotal, StorageRunError, TransactionFilter},
+    CursoredString,
+};
+
+use super::{ConfirmBatchReason, Popup, PopupHandler};
+
+/// Popup listing the reason codes with the current user's totals for each, for managing and filtering by them
+pub struct ReasonCodes {
+    reasons: Vec<ReasonCode>,
+    totals: Vec<ReasonTotal>,
+    table_state: TableState,
+    /// Name being typed for a new reason code, if one is being added
+    new_name: Option<CursoredString>,
+    error: Option<String>,
+    amount_format: AmountFormatter,
+}
+
+impl ReasonCodes {
+    /// Create popup showing the reason codes and the current user's totals
+    pub async fn load(data: &AppData) -> Result<Self, AppError> {
+        let mut popup = Self {
+            reasons: Vec::new(),
+            totals: Vec::new(),
+            table_state: TableState::default().with_selected(0),
+            new_name: None,
+            error: None,
+            amount_format: data.config.amount_format.clone(),
+        };
+        popup.reload(data).await?;
+        Ok(popup)
+    }
+
+    /// Fetches the reason codes and totals again after a change
+    async fn reload(&mut self, data: &AppData) -> Result<(), AppError> {
+        let user_id = data.current_user.as_ref().map(|v| v.get_id()).unwrap();
+        self.reasons = data.storage.reason_codes().await?;
+        self.totals = data.storage.totals_by_reason(user_id).await?;
+        Ok(())
+    }
+
+    /// The highlighted reason code, if any
+    fn highlighted(&self) -> Option<&ReasonCode> {
+        self.table_state.selected().and_then(|index| {
+            self.reasons
+                .get(index.min(self.reasons.len().saturating_sub(1)))
+        })
+    }
+
+    /// The current user's count and total for a reason, None meaning transactions without one
+    fn total_for(&self, id: Option<i32>) -> (i64, i64) {
+        self.totals
+            .iter()
+            .find(|total| total.reason.as_ref().map(|reason| reason.id) == id)
+            .map_or((0, 0), |total| (total.count, total.total))
+    }
+
+    /// Handles typing the name of a new reason code
+    async fn handle_new_name(
+        mut self,
+        mut name: CursoredString,
+        app: &mut App,
+        code: KeyCode,
+    ) -> Result<Option<Popup>, AppError> {
+        match code {
+            KeyCode::Left => name.right(),
+            KeyCode::Right => name.left(),
+            KeyCode::Backspace => name.remove_behind(),
+            KeyCode::Delete => name.remove_ahead(),
+            KeyCode::Insert => name.inserting = !name.inserting,
+            KeyCode::Esc => return Ok(Some(Popup::ReasonCodes(self))),
+            KeyCode::Enter if !name.trim().is_empty() => {
+                let trimmed = name.trim();
+                if self.reasons.iter().any(|reason| reason.name == trimmed) {
+                    self.error = Some(format!("'{trimmed}' already exists"));
+                } else {
+                    app.data.storage.add_reason_code(trimmed).await?;
+                    self.reload(&app.data).await?;
+                    self.table_state.select(Some(self.reasons.len() - 1));
+                    return Ok(Some(Popup::ReasonCodes(self)));
+                }
+            }
+            KeyCode::Char(c) => {
+                self.error = None;
+                name.insert(c);
+            }
+            _ => (),
+        }
+        self.new_name = Some(name);
+        Ok(Some(Popup::ReasonCodes(self)))
+    }
+}
+
+impl PopupHandler for ReasonCodes {
+    async fn handle_event(
+        mut self,
+        app: &mut App,
+        event: &Event,
+    ) -> Result<Option<Popup>, AppError> {
+        if let Event::Key(key) = event {
+            if key.kind == event::KeyEventKind::Press {
+                if let Some(name) = self.new_name.take() {
+                    return self.handle_new_name(name, app, key.code).await;
+                }
+                match app.data.config.nav_key(key.code) {
+                    KeyCode::Up => self.table_state.select_previous(),
+                    KeyCode::Down => self.table_state.select_next(),
+                    KeyCode::Char('a') => {
+                        self.error = None;
+                        self.new_name = Some(CursoredString::new());
+                    }
+                    KeyCode::Char('d') => {
+                        if let Some(reason) = self.highlighted() {
+                            match app.data.storage.remove_reason_code(reason.id).await {
+                                Ok(()) => {
+                                    self.error = None;
+                                    self.reload(&app.data).await?;
+                                }
+                                Err(StorageRunError::BuiltinReason) => {
+                                    self.error = Some(StorageRunError::BuiltinReason.to_string());
+                                }
+                                Err(error) => return Err(error.into()),
+                            }
+                        }
+                    }
+                    // show only transactions with the highlighted reason
+                    KeyCode::Enter => {
+                        if let Some(reason) = self.highlighted() {
+                            let mut filters = app.data.transaction_filters.clone();
+                            filters.push(TransactionFilter::Reason(vec![reason.id]));
+                            app.data.status_text = format!("Filtered to reason '{}'", reason.name);
+                            app.data.set_filters(filters).await?;
+                            return Ok(None);
+                        }
+                    }
+                    // give the highlighted reason to everything the table's filters match, not just what's selected
+                    KeyCode::Char('A') => {
+                        if let Some(reason) = self.highlighted().cloned() {
+                            if app.data.config.fast_mode {
+                                ConfirmBatchReason::apply(&reason, app).await?;
+                                return Ok(None);
+                            }
+                            let count = app
+                                .data
+                                .storage
+                                .count_transactions(&app.data.own_table_filters())
+                                .await?;
+                            return Ok(Some(Popup::ConfirmBatchReason(ConfirmBatchReason::new(
+                                reason, count,
+                            ))));
+                        }
+                    }
+                    KeyCode::Esc => return Ok(None),
+                    _ => (),
+                }
+            }
+        }
+        Ok(Some(Popup::ReasonCodes(self)))
+    }
+
+    fn render_to_frame(&mut self, area: Rect, frame: &mut Frame, theme: &Theme) {
+        const LIST_HEIGHT: u16 = 8;
+        const NAME_HEIGHT: u16 = 1;
+        const BORDER_SIZE: u16 = 1;
+
+        let name_height = if self.new_name.is_some() {
+            NAME_HEIGHT + BORDER_SIZE * 2
+        } else {
+            0
+        };
+        let [area] = Layout::vertical([Constraint::Length(
+            LIST_HEIGHT + name_height + 4 * BORDER_SIZE,
+        )])
+        .flex(Flex::Center)
+        .areas(area);
+        let [area] = Layout::horizontal([Constraint::Percentage(40)])
+            .flex(Flex::Center)
+            .areas(area);
+        let block = Block::bordered().title("Reason Codes").title_bottom(
+            Line::from("a: add, d: delete, Enter: filter, A: give to filtered").dim(),
+        );
+        frame.render_widget(Clear, area);
+        frame.render_widget(block, area);
+        let area = area.inner(Margin::new(BORDER_SIZE, BORDER_SIZE));
+        let [list_area, name_area] = Layout::vertical([
+            Constraint::Length(LIST_HEIGHT + BORDER_SIZE * 2),
+            Constraint::Length(name_height),
+        ])
+        .areas(area);
+
+        let rows = self.reasons.iter().map(|reason| {
+            let (count, total) = self.total_for(Some(reason.id));
+            let name = if reason.is_builtin() {
+                Line::from(reason.name.as_str())
+            } else {
+                Line::from(reason.name.as_str()).italic()
+            };
+            Row::new([
+                name,
+                Line::from(count.to_string()),
+                Line::from(self.amount_format.format_wide(total)),
+            ])
+        });
+        let (unreasoned_count, unreasoned_total) = self.total_for(None);
+        let mut list_block = Block::bordered()
+            .title_bottom(Line::from(format!(
+                "No reason: {unreasoned_count}, {}",
+                self.amount_format.format_wide(unreasoned_total)
+            )))
+            .style(theme.active());
+        if let Some(error) = &self.error {
+            list_block = list_block.title(Line::from(error.as_str()).red());
+        }
+        let list = Table::new(
+            rows,
+            [
+                Constraint::Fill(2),
+                Constraint::Fill(1),
+                Constraint::Fill(2),
+            ],
+        )
+        .header(Row::new(["Reason", "Count", "Total"]).underlined())
+        .block(list_block)
+        .highlight_style(theme.highlight());
+        frame.render_stateful_widget(list, list_area, &mut self.table_state);
+
+        if let Some(name) = &self.new_name {
+            let name_field = Block::bordered()
+                .title("New reason code")
+                .style(theme.active());
+            frame.render_widget(Paragraph::new(name.as_str()).block(name_field), name_area);
+            frame.set_cursor_position(Position::new(
+                name_area.x + name.cursor_index() as u16 + BORDER_SIZE,
+                name_area.y + BORDER_SIZE,
+            ));
+        }
+    }
+}
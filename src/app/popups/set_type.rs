@@ -0,0 +1,91 @@
+use crossterm::event::{self, Event, KeyCode};
+use ratatui::{
+    layout::{Constraint, Flex, Layout, Margin},
+    prelude::*,
+    widgets::{Block, Clear, Tabs},
+    Frame,
+};
+use strum::VariantNames;
+
+use crate::{
+    app::{App, AppError},
+    config::Theme,
+    storage::TransactionType,
+};
+
+use super::{Popup, PopupHandler};
+
+/// Popup for changing the type of several transactions at once
+pub struct SetType {
+    ids: Vec<i32>,
+    trans_type: TransactionType,
+}
+
+impl SetType {
+    /// Create popup for the transactions with the given ids
+    pub fn new(ids: Vec<i32>) -> Self {
+        Self {
+            ids,
+            trans_type: TransactionType::default(),
+        }
+    }
+}
+
+impl PopupHandler for SetType {
+    async fn handle_event(
+        mut self,
+        app: &mut App,
+        event: &Event,
+    ) -> Result<Option<Popup>, AppError> {
+        if let Event::Key(key) = event {
+            if key.kind == event::KeyEventKind::Press {
+                match app.data.config.nav_key(key.code) {
+                    KeyCode::Left | KeyCode::BackTab => self.trans_type = self.trans_type.prev(),
+                    KeyCode::Right | KeyCode::Tab => self.trans_type = self.trans_type.next(),
+                    KeyCode::Enter => {
+                        let changed = app
+                            .data
+                            .storage
+                            .set_transaction_types(&self.ids, self.trans_type)
+                            .await?;
+                        app.data.status_text =
+                            format!("Set {changed} transactions to {}", self.trans_type);
+                        app.data.update_table().await?;
+                        return Ok(None);
+                    }
+                    KeyCode::Esc => return Ok(None),
+                    _ => (),
+                }
+            }
+        }
+        Ok(Some(Popup::SetType(self)))
+    }
+
+    fn render_to_frame(&mut self, area: Rect, frame: &mut Frame, theme: &Theme) {
+        const TYPE_HEIGHT: u16 = 1;
+        const BORDER_SIZE: u16 = 1;
+
+        let [area] = Layout::vertical([Constraint::Length(TYPE_HEIGHT + 4 * BORDER_SIZE)])
+            .flex(Flex::Center)
+            .areas(area);
+        let [area] = Layout::horizontal([Constraint::Percentage(40)])
+            .flex(Flex::Center)
+            .areas(area);
+        let block = Block::bordered().title("Set Type");
+        frame.render_widget(Clear, area);
+        frame.render_widget(block, area);
+        let area = area.inner(Margin::new(BORDER_SIZE, BORDER_SIZE));
+        let [type_area] =
+            Layout::vertical([Constraint::Length(TYPE_HEIGHT + BORDER_SIZE * 2)]).areas(area);
+
+        let type_field = Block::bordered()
+            .title(format!("Type for {} transactions", self.ids.len()))
+            .style(theme.active());
+
+        let type_text = Tabs::new(<TransactionType as VariantNames>::VARIANTS.iter().copied())
+            .select(self.trans_type as usize)
+            .block(type_field);
+
+        frame.render_widget(type_text, type_area);
+    }
+}
@@ -0,0 +1,336 @@
+use crossterm::event::{self, Event, KeyCode, KeyModifiers};
+use ratatui::{
+    layout::{Constraint, Flex, Layout, Margin},
+    prelude::*,
+    style::Style,
+    widgets::{Block, Clear, Paragraph},
+    Frame,
+};
+
+use crate::{
+    app::{App, AppError},
+    config::Theme,
+    format::AmountFormatter,
+    parse_amount,
+    storage::{SplitPart, StorageRunError, Transaction, TransactionType},
+    CursoredString,
+};
+
+use super::{Popup, PopupHandler};
+
+/// Popup for splitting a transaction into several whose amounts add up to the original
+pub struct SplitTransaction {
+    trans_id: i32,
+    value: i32,
+    lines: Vec<SplitLine>,
+    selected_line: usize,
+    selected_column: SplitColumn,
+    amount_format: AmountFormatter,
+    error: Option<String>,
+}
+
+/// One of the transactions being split off
+struct SplitLine {
+    amount: CursoredString,
+    transaction_type: TransactionType,
+    msg: CursoredString,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SplitColumn {
+    Amount,
+    Type,
+    Message,
+}
+
+impl SplitTransaction {
+    /// More lines than this won't fit on most screens
+    const MAX_LINES: usize = 10;
+
+    /// Create popup with the whole amount on the first line and an empty second line to split it into
+    pub fn new(transaction: &Transaction, amount_format: &AmountFormatter) -> Self {
+        let amount = filled(&transaction.value.to_string());
+        let msg = filled(&transaction.msg);
+        Self {
+            trans_id: transaction.trans_id,
+            value: transaction.value,
+            lines: vec![
+                SplitLine {
+                    amount,
+                    transaction_type: transaction.transaction_type,
+                    msg,
+                },
+                SplitLine {
+                    amount: CursoredString::new(),
+                    transaction_type: transaction.transaction_type,
+                    msg: CursoredString::new(),
+                },
+            ],
+            selected_line: 1,
+            selected_column: SplitColumn::Amount,
+            amount_format: amount_format.clone(),
+            error: None,
+        }
+    }
+
+    /// How much of the original is left once the lines' amounts are taken out, counting unreadable amounts as nothing
+    fn remaining(&self) -> i64 {
+        let split: i64 = self
+            .lines
+            .iter()
+            .map(|line| i64::from(parse_amount(&line.amount).unwrap_or(0)))
+            .sum();
+        i64::from(self.value) - split
+    }
+
+    /// Splits the transaction if every line's amount can be read and they add up to the original
+    async fn submit(mut self, app: &mut App) -> Result<Option<Popup>, AppError> {
+        let mut parts = Vec::with_capacity(self.lines.len());
+        for (index, line) in self.lines.iter().enumerate() {
+            match parse_amount(&line.amount) {
+                Ok(value) => parts.push(SplitPart {
+                    value,
+                    transaction_type: line.transaction_type,
+                    msg: line.msg.to_string(),
+                }),
+                Err(error) => {
+                    self.error = Some(format!("Line {}: {error}", index + 1));
+                    self.selected_line = index;
+                    self.selected_column = SplitColumn::Amount;
+                    return Ok(Some(Popup::SplitTransaction(self)));
+                }
+            }
+        }
+        let remaining = self.remaining();
+        if remaining != 0 {
+            self.error = Some(format!(
+                "{} still to split",
+                self.amount_format.format_wide(remaining)
+            ));
+            return Ok(Some(Popup::SplitTransaction(self)));
+        }
+        match app
+            .data
+            .storage
+            .split_transaction(self.trans_id, &parts)
+            .await
+        {
+            Ok(ids) => {
+                app.data.status_text = format!("Split into {} transactions", ids.len());
+                app.data.selected_ids.remove(&self.trans_id);
+                app.data.update_table().await?;
+                Ok(None)
+            }
+            Err(error @ StorageRunError::SplitMismatch { .. }) => {
+                self.error = Some(error.to_string());
+                Ok(Some(Popup::SplitTransaction(self)))
+            }
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// The text field under the cursor, if the selected column is one
+    fn selected_text(&mut self) -> Option<&mut CursoredString> {
+        let line = &mut self.lines[self.selected_line];
+        match self.selected_column {
+            SplitColumn::Amount => Some(&mut line.amount),
+            SplitColumn::Type => None,
+            SplitColumn::Message => Some(&mut line.msg),
+        }
+    }
+}
+
+/// A text field holding the given text, with the cursor after it ready to keep typing
+fn filled(text: &str) -> CursoredString {
+    let mut field = CursoredString::new();
+    field.insert_str(text);
+    field
+}
+
+impl SplitColumn {
+    fn next(self) -> Self {
+        match self {
+            SplitColumn::Amount => SplitColumn::Type,
+            SplitColumn::Type => SplitColumn::Message,
+            SplitColumn::Message => SplitColumn::Amount,
+        }
+    }
+
+    fn prev(self) -> Self {
+        match self {
+            SplitColumn::Amount => SplitColumn::Message,
+            SplitColumn::Type => SplitColumn::Amount,
+            SplitColumn::Message => SplitColumn::Type,
+        }
+    }
+}
+
+impl PopupHandler for SplitTransaction {
+    async fn handle_event(
+        mut self,
+        app: &mut App,
+        event: &Event,
+    ) -> Result<Option<Popup>, AppError> {
+        if let Event::Key(key) = event {
+            if key.kind == event::KeyEventKind::Press {
+                if !matches!(key.code, KeyCode::Enter) {
+                    self.error = None;
+                }
+                let control = key.modifiers.contains(KeyModifiers::CONTROL);
+                match key.code {
+                    KeyCode::Esc => return Ok(None),
+                    KeyCode::Enter => return self.submit(app).await,
+                    KeyCode::Up => self.selected_line = self.selected_line.saturating_sub(1),
+                    KeyCode::Down => {
+                        self.selected_line = (self.selected_line + 1).min(self.lines.len() - 1)
+                    }
+                    KeyCode::Tab => self.selected_column = self.selected_column.next(),
+                    KeyCode::BackTab => self.selected_column = self.selected_column.prev(),
+                    KeyCode::Char('n') if control => {
+                        if self.lines.len() < Self::MAX_LINES {
+                            let transaction_type = self.lines[self.selected_line].transaction_type;
+                            self.lines.push(SplitLine {
+                                amount: CursoredString::new(),
+                                transaction_type,
+                                msg: CursoredString::new(),
+                            });
+                            self.selected_line = self.lines.len() - 1;
+                            self.selected_column = SplitColumn::Amount;
+                        }
+                    }
+                    // a split needs at least two parts
+                    KeyCode::Char('d') if control => {
+                        if self.lines.len() > 2 {
+                            self.lines.remove(self.selected_line);
+                            self.selected_line = self.selected_line.min(self.lines.len() - 1);
+                        }
+                    }
+                    code if self.selected_column == SplitColumn::Type => {
+                        let line = &mut self.lines[self.selected_line];
+                        match code {
+                            KeyCode::Left => line.transaction_type = line.transaction_type.prev(),
+                            KeyCode::Right => line.transaction_type = line.transaction_type.next(),
+                            _ => (),
+                        }
+                    }
+                    code => {
+                        if let Some(text) = self.selected_text() {
+                            match code {
+                                KeyCode::Left => text.right(),
+                                KeyCode::Right => text.left(),
+                                KeyCode::Backspace => text.remove_behind(),
+                                KeyCode::Delete => text.remove_ahead(),
+                                KeyCode::Insert => text.inserting = !text.inserting,
+                                KeyCode::Char(c) => {
+                                    text.insert(c);
+                                }
+                                _ => (),
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        Ok(Some(Popup::SplitTransaction(self)))
+    }
+
+    fn render_to_frame(&mut self, area: Rect, frame: &mut Frame, theme: &Theme) {
+        const HEADER_HEIGHT: u16 = 1;
+        const FOOTER_HEIGHT: u16 = 1;
+        const BORDER_SIZE: u16 = 1;
+        const COLUMNS: [Constraint; 3] = [
+            Constraint::Length(14),
+            Constraint::Length(17),
+            Constraint::Fill(1),
+        ];
+
+        let lines_height = self.lines.len() as u16;
+        let [area] = Layout::vertical([Constraint::Length(
+            HEADER_HEIGHT + lines_height + FOOTER_HEIGHT + 2 * BORDER_SIZE,
+        )])
+        .flex(Flex::Center)
+        .areas(area);
+        let [area] = Layout::horizontal([Constraint::Percentage(50)])
+            .flex(Flex::Center)
+            .areas(area);
+        let block = Block::bordered()
+            .title("Split Transaction")
+            .title(Line::from(self.amount_format.format(self.value)).right_aligned())
+            .title_bottom(
+                Line::from("Tab: next column, Ctrl+N: add line, Ctrl+D: remove line, Enter: split")
+                    .dim(),
+            );
+        frame.render_widget(Clear, area);
+        frame.render_widget(block, area);
+        let area = area.inner(Margin::new(BORDER_SIZE, BORDER_SIZE));
+        let [header_area, lines_area, footer_area] = Layout::vertical([
+            Constraint::Length(HEADER_HEIGHT),
+            Constraint::Length(lines_height),
+            Constraint::Length(FOOTER_HEIGHT),
+        ])
+        .areas(area);
+
+        let header_areas: [Rect; 3] = Layout::horizontal(COLUMNS).spacing(1).areas(header_area);
+        for (title, header_area) in ["Amount", "Type", "Message"].into_iter().zip(header_areas) {
+            frame.render_widget(Paragraph::new(title).underlined(), header_area);
+        }
+
+        let active_style = theme.active();
+        let line_areas =
+            Layout::vertical(vec![Constraint::Length(1); self.lines.len()]).split(lines_area);
+        for (index, (line, line_area)) in self.lines.iter().zip(line_areas.iter()).enumerate() {
+            let [amount_area, type_area, msg_area]: [Rect; 3] =
+                Layout::horizontal(COLUMNS).spacing(1).areas(*line_area);
+            let selected = index == self.selected_line;
+            let style_for = |column: SplitColumn| {
+                if selected && column == self.selected_column {
+                    active_style
+                } else {
+                    Style::default()
+                }
+            };
+            let type_text = if selected && self.selected_column == SplitColumn::Type {
+                format!("< {} >", line.transaction_type)
+            } else {
+                line.transaction_type.to_string()
+            };
+            frame.render_widget(
+                Paragraph::new(line.amount.as_str()).style(style_for(SplitColumn::Amount)),
+                amount_area,
+            );
+            frame.render_widget(
+                Paragraph::new(type_text).style(style_for(SplitColumn::Type)),
+                type_area,
+            );
+            frame.render_widget(
+                Paragraph::new(line.msg.as_str()).style(style_for(SplitColumn::Message)),
+                msg_area,
+            );
+            if selected {
+                let cursor = match self.selected_column {
+                    SplitColumn::Amount => Some((amount_area, line.amount.cursor_index())),
+                    SplitColumn::Type => None,
+                    SplitColumn::Message => Some((msg_area, line.msg.cursor_index())),
+                };
+                if let Some((area, index)) = cursor {
+                    frame.set_cursor_position(Position::new(area.x + index as u16, area.y));
+                }
+            }
+        }
+
+        // feedback on what's left to split, or why it can't be split yet
+        let footer = match &self.error {
+            Some(error) => Line::from(error.as_str()).red(),
+            None => {
+                let remaining = self.remaining();
+                let text = format!("Remaining: {}", self.amount_format.format_wide(remaining));
+                if remaining == 0 {
+                    Line::from(text).green()
+                } else {
+                    Line::from(text).yellow()
+                }
+            }
+        };
+        frame.render_widget(Paragraph::new(footer), footer_area);
+    }
+}
@@ -0,0 +1,207 @@
+use crossterm::event::{self, Event, KeyCode, KeyModifiers};
+use ratatui::{
+    layout::{Constraint, Flex, Layout, Margin},
+    prelude::*,
+    widgets::{Block, Clear, Paragraph},
+    Frame,
+};
+use time::{PrimitiveDateTime, Time, UtcOffset};
+
+use crate::{
+    app::{App, AppError},
+    config::{Config, Theme},
+    export,
+    format::{self, AmountFormatter},
+    storage::TransactionTypeMap,
+    CursoredString,
+};
+
+use super::{Popup, PopupHandler};
+
+/// Popup summarizing the current user's transactions
+pub struct Summary {
+    currency: String,
+    timezone: UtcOffset,
+    amount_format: AmountFormatter,
+    rounding: Option<u32>,
+    balance: i32,
+    /// Net change so far today in the configured timezone
+    today: i32,
+    /// Total of each transaction type, counted the same way as the balance
+    type_totals: TransactionTypeMap<i64>,
+    as_of: CursoredString,
+    as_of_result: String,
+}
+
+impl Summary {
+    /// Create a summary of the given balance, today's net change, and per-type totals, using the config's currency and timezone
+    pub fn new(
+        balance: i32,
+        today: i32,
+        type_totals: TransactionTypeMap<i64>,
+        config: &Config,
+    ) -> Self {
+        Self {
+            currency: config.currency.long.clone(),
+            timezone: config.timezone,
+            amount_format: config.amount_format.clone(),
+            rounding: config.summary_rounding,
+            balance,
+            today,
+            type_totals,
+            as_of: CursoredString::new(),
+            as_of_result: String::new(),
+        }
+    }
+
+    /// Formats an aggregate for display, rounded if the config asks for it
+    fn format_total(&self, value: i64) -> String {
+        match self.rounding {
+            Some(step) => format!(
+                "~{} {}",
+                self.amount_format
+                    .format_wide(format::round_to_nearest(value, step)),
+                self.currency
+            ),
+            None => format!(
+                "{} {}",
+                self.amount_format.format_wide(value),
+                self.currency
+            ),
+        }
+    }
+
+    /// Parses the entered date as a local datetime, converted to UTC for querying.
+    /// A date without a time is taken to mean the end of that day.
+    fn parse_as_of(&self) -> Option<PrimitiveDateTime> {
+        let input = self.as_of.trim();
+        let local = PrimitiveDateTime::parse(
+            input,
+            time::macros::format_description!("[year]-[month]-[day] [hour]:[minute]"),
+        )
+        .or_else(|_| {
+            time::Date::parse(
+                input,
+                time::macros::format_description!("[year]-[month]-[day]"),
+            )
+            .map(|date| date.with_time(Time::MAX))
+        })
+        .ok()?;
+        let utc = local.assume_offset(self.timezone).to_offset(UtcOffset::UTC);
+        Some(PrimitiveDateTime::new(utc.date(), utc.time()))
+    }
+}
+
+impl PopupHandler for Summary {
+    async fn handle_event(
+        mut self,
+        app: &mut App,
+        event: &Event,
+    ) -> Result<Option<Popup>, AppError> {
+        if let Event::Key(key) = event {
+            if key.kind == event::KeyEventKind::Press {
+                match key.code {
+                    KeyCode::Left => self.as_of.right(),
+                    KeyCode::Right => self.as_of.left(),
+                    KeyCode::Backspace => self.as_of.remove_behind(),
+                    KeyCode::Delete => self.as_of.remove_ahead(),
+                    KeyCode::Insert => self.as_of.inserting = !self.as_of.inserting,
+                    KeyCode::Enter => {
+                        self.as_of_result = match self.parse_as_of() {
+                            Some(at) => {
+                                let user_id =
+                                    app.data.current_user.as_ref().map(|v| v.get_id()).unwrap();
+                                let balance = app
+                                    .data
+                                    .storage
+                                    .balance_as_of(user_id, at, app.data.config.pending_in_balance)
+                                    .await?;
+                                self.format_total(balance.into())
+                            }
+                            None => String::from("Expected YYYY-MM-DD or YYYY-MM-DD HH:MM"),
+                        }
+                    }
+                    KeyCode::Char('x') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        let user = app.data.current_user.as_ref().unwrap();
+                        let path = export::export_type_chart(
+                            user,
+                            &self.type_totals,
+                            &self.amount_format,
+                            &self.currency,
+                            self.timezone,
+                        )?;
+                        self.as_of_result = format!("Exported chart to {}", path.display());
+                    }
+                    KeyCode::Esc => return Ok(None),
+                    KeyCode::Char(c) => {
+                        self.as_of.insert(c);
+                    }
+                    _ => (),
+                }
+            }
+        }
+        Ok(Some(Popup::Summary(self)))
+    }
+
+    fn render_to_frame(&mut self, area: Rect, frame: &mut Frame, theme: &Theme) {
+        const LINE_HEIGHT: u16 = 1;
+        const BORDER_SIZE: u16 = 1;
+
+        let types_height = self.type_totals.values().count() as u16 * LINE_HEIGHT;
+        let [area] = Layout::vertical([Constraint::Length(
+            3 * LINE_HEIGHT + types_height + 8 * BORDER_SIZE,
+        )])
+        .flex(Flex::Center)
+        .areas(area);
+        let [area] = Layout::horizontal([Constraint::Percentage(40)])
+            .flex(Flex::Center)
+            .areas(area);
+        let block = Block::bordered()
+            .title("Summary")
+            .title_bottom(Line::from("Ctrl+X: export chart").dim().right_aligned());
+        frame.render_widget(Clear, area);
+        frame.render_widget(block, area);
+        let area = area.inner(Margin::new(BORDER_SIZE, BORDER_SIZE));
+        let [balance_area, types_area, as_of_area, result_area] = Layout::vertical([
+            Constraint::Length(LINE_HEIGHT + BORDER_SIZE * 2),
+            Constraint::Length(types_height + BORDER_SIZE * 2),
+            Constraint::Length(LINE_HEIGHT + BORDER_SIZE * 2),
+            Constraint::Length(LINE_HEIGHT),
+        ])
+        .areas(area);
+        let [balance_area, today_area] =
+            Layout::horizontal([Constraint::Fill(1), Constraint::Fill(1)]).areas(balance_area);
+
+        let balance_text = Paragraph::new(self.format_total(self.balance.into()))
+            .block(Block::bordered().title("Balance"));
+        let today_text = Paragraph::new(self.format_total(self.today.into()))
+            .block(Block::bordered().title("Today"));
+        let type_lines: Vec<Line> = self
+            .type_totals
+            .kv_pairs()
+            .map(|(transaction_type, total)| {
+                Line::from(vec![
+                    Span::raw(format!("{transaction_type}: ")),
+                    Span::raw(self.format_total(*total)).bold(),
+                ])
+            })
+            .collect();
+        let types_text = Paragraph::new(type_lines).block(Block::bordered().title("By type"));
+        let as_of_text = Paragraph::new(self.as_of.as_str()).block(
+            Block::bordered()
+                .title("Balance as of (YYYY-MM-DD [HH:MM])")
+                .style(theme.active()),
+        );
+        let result_text = Paragraph::new(self.as_of_result.as_str()).alignment(Alignment::Center);
+
+        frame.set_cursor_position(Position::new(
+            as_of_area.x + self.as_of.cursor_index() as u16 + 1,
+            as_of_area.y + 1,
+        ));
+        frame.render_widget(balance_text, balance_area);
+        frame.render_widget(today_text, today_area);
+        frame.render_widget(types_text, types_area);
+        frame.render_widget(as_of_text, as_of_area);
+        frame.render_widget(result_text, result_area);
+    }
+}
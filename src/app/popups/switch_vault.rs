@@ -0,0 +1,193 @@
+use crossterm::event::{self, Event, KeyCode};
+use ratatui::{
+    layout::{Constraint, Flex, Layout, Margin},
+    prelude::*,
+    widgets::{Block, Clear, List, ListState, Paragraph},
+    Frame,
+};
+
+use crate::{
+    app::{App, AppError, AppMode},
+    config::Theme,
+    storage::{Storage, StorageLoadError},
+    CursoredString,
+};
+
+use super::{Popup, PopupHandler};
+
+/// Popup listing the vaults to switch between, each a separate database with its own pilots
+pub struct SwitchVault {
+    /// None being the default vault, always listed first
+    vaults: Vec<Option<String>>,
+    current: Option<String>,
+    list_state: ListState,
+    /// Name being typed for a new vault, if one is being made
+    new_name: Option<CursoredString>,
+    error: Option<String>,
+}
+
+impl SwitchVault {
+    /// Create popup listing the vaults, with the open one highlighted
+    pub fn load(storage: &Storage) -> Result<Self, AppError> {
+        let current = storage.vault().map(str::to_owned);
+        let mut vaults = vec![None];
+        vaults.extend(Storage::vaults()?.into_iter().map(Some));
+        let selected = vaults.iter().position(|vault| *vault == current);
+        Ok(Self {
+            vaults,
+            current,
+            list_state: ListState::default().with_selected(selected),
+            new_name: None,
+            error: None,
+        })
+    }
+
+    /// Opens the vault, creating it if it doesn't exist, and goes back to the login screen
+    async fn open(
+        mut self,
+        vault: Option<String>,
+        app: &mut App,
+    ) -> Result<Option<Popup>, AppError> {
+        if vault == self.current {
+            return Ok(None);
+        }
+        match Storage::load_or_create(vault.as_deref()).await {
+            Ok(storage) => {
+                app.data.switch_vault(storage).await?;
+                app.mode = AppMode::UserLogin(Default::default());
+                Ok(None)
+            }
+            Err(error @ StorageLoadError::VaultName(_)) => {
+                self.error = Some(error.to_string());
+                Ok(Some(Popup::SwitchVault(self)))
+            }
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// Handles typing the name of a new vault
+    async fn handle_new_name(
+        mut self,
+        mut name: CursoredString,
+        app: &mut App,
+        code: KeyCode,
+    ) -> Result<Option<Popup>, AppError> {
+        match code {
+            KeyCode::Left => name.right(),
+            KeyCode::Right => name.left(),
+            KeyCode::Backspace => name.remove_behind(),
+            KeyCode::Delete => name.remove_ahead(),
+            KeyCode::Insert => name.inserting = !name.inserting,
+            KeyCode::Esc => return Ok(Some(Popup::SwitchVault(self))),
+            KeyCode::Enter if !name.is_empty() => {
+                let vault = name.to_lowercase();
+                // keep the name around in case it's rejected
+                self.new_name = Some(name);
+                return self.open(Some(vault), app).await;
+            }
+            KeyCode::Char(c) => {
+                self.error = None;
+                name.insert(c);
+            }
+            _ => (),
+        }
+        self.new_name = Some(name);
+        Ok(Some(Popup::SwitchVault(self)))
+    }
+}
+
+impl PopupHandler for SwitchVault {
+    async fn handle_event(
+        mut self,
+        app: &mut App,
+        event: &Event,
+    ) -> Result<Option<Popup>, AppError> {
+        if let Event::Key(key) = event {
+            if key.kind == event::KeyEventKind::Press {
+                if let Some(name) = self.new_name.take() {
+                    return self.handle_new_name(name, app, key.code).await;
+                }
+                match app.data.config.nav_key(key.code) {
+                    KeyCode::Up => self.list_state.select_previous(),
+                    KeyCode::Down => self.list_state.select_next(),
+                    KeyCode::Char('a') => {
+                        self.error = None;
+                        self.new_name = Some(CursoredString::new());
+                    }
+                    KeyCode::Enter => {
+                        if let Some(vault) = self
+                            .list_state
+                            .selected()
+                            .and_then(|index| self.vaults.get(index))
+                        {
+                            let vault = vault.clone();
+                            return self.open(vault, app).await;
+                        }
+                    }
+                    KeyCode::Esc => return Ok(None),
+                    _ => (),
+                }
+            }
+        }
+        Ok(Some(Popup::SwitchVault(self)))
+    }
+
+    fn render_to_frame(&mut self, area: Rect, frame: &mut Frame, theme: &Theme) {
+        const LIST_HEIGHT: u16 = 6;
+        const NAME_HEIGHT: u16 = 1;
+        const BORDER_SIZE: u16 = 1;
+
+        let name_height = if self.new_name.is_some() {
+            NAME_HEIGHT + BORDER_SIZE * 2
+        } else {
+            0
+        };
+        let [area] = Layout::vertical([Constraint::Length(
+            LIST_HEIGHT + name_height + 4 * BORDER_SIZE,
+        )])
+        .flex(Flex::Center)
+        .areas(area);
+        let [area] = Layout::horizontal([Constraint::Percentage(40)])
+            .flex(Flex::Center)
+            .areas(area);
+        let block = Block::bordered()
+            .title("Vaults")
+            .title_bottom(Line::from("a: new vault, Enter: open").dim());
+        frame.render_widget(Clear, area);
+        frame.render_widget(block, area);
+        let area = area.inner(Margin::new(BORDER_SIZE, BORDER_SIZE));
+        let [list_area, name_area] = Layout::vertical([
+            Constraint::Length(LIST_HEIGHT + BORDER_SIZE * 2),
+            Constraint::Length(name_height),
+        ])
+        .areas(area);
+
+        let items = self.vaults.iter().map(|vault| {
+            let name = vault.as_deref().unwrap_or("default");
+            if *vault == self.current {
+                Line::from(format!("{name} (open)")).bold()
+            } else {
+                Line::from(name)
+            }
+        });
+        let mut list_block = Block::bordered().style(theme.active());
+        if let Some(error) = &self.error {
+            list_block = list_block.title(Line::from(error.as_str()).red());
+        }
+        let list = List::new(items)
+            .block(list_block)
+            .highlight_style(theme.highlight());
+        frame.render_stateful_widget(list, list_area, &mut self.list_state);
+
+        if let Some(name) = &self.new_name {
+            let name_field = Block::bordered()
+                .title("New vault name")
+                .style(theme.active());
+            frame.render_widget(Paragraph::new(name.as_str()).block(name_field), name_area);
+            frame.set_cursor_position(Position::new(
+                name_area.x + name.cursor_index() as u16 + BORDER_SIZE,
+                name_area.y + BORDER_SIZE,
+            ));
+        }
+    }
+}
@@ -0,0 +1,213 @@
+use crossterm::event::{self, Event, KeyCode};
+use ratatui::{
+    layout::{Constraint, Flex, Layout, Margin},
+    prelude::*,
+    widgets::{Block, Clear, Paragraph, Wrap},
+    Frame,
+};
+use time::{PrimitiveDateTime, UtcOffset};
+
+use crate::{
+    app::{App, AppError},
+    config::{Config, Theme},
+    format::{self, Locale},
+    storage::{Transaction, TransactionNote},
+    CursoredString,
+};
+
+use super::{Popup, PopupHandler};
+
+/// Popup showing everything about one transaction, with its message and any notes appended since as a thread
+pub struct TransactionDetails {
+    trans_id: i32,
+    /// Lines describing the transaction above the thread
+    summary: Vec<String>,
+    /// When each entry of the thread was written and what it says, starting with the transaction's message
+    thread: Vec<(PrimitiveDateTime, String)>,
+    /// The note being written, if one has been started
+    note: Option<CursoredString>,
+    /// Notes can't be added, such as to another pilot's transaction
+    read_only: bool,
+    scroll: u16,
+    timezone: UtcOffset,
+    locale: Option<Locale>,
+}
+
+impl TransactionDetails {
+    /// Create popup for the transaction, with the name of its reason if it has one and the notes it already has
+    pub fn new(
+        transaction: &Transaction,
+        reason: Option<String>,
+        notes: Vec<TransactionNote>,
+        config: &Config,
+    ) -> Self {
+        let mut summary = vec![
+            format!(
+                "{} {}, {}",
+                config.amount_format.format(transaction.value),
+                transaction.transaction_type,
+                transaction.status.to_string().to_lowercase()
+            ),
+            format::format_datetime(
+                transaction.datetime.assume_utc().to_offset(config.timezone),
+                config.locale,
+            ),
+        ];
+        if let Some(reason) = reason {
+            summary.push(format!("Reason: {reason}"));
+        }
+        let thread = std::iter::once((transaction.datetime, transaction.msg.clone()))
+            .chain(notes.into_iter().map(|note| (note.datetime, note.text)))
+            .collect();
+        Self {
+            trans_id: transaction.trans_id,
+            summary,
+            thread,
+            note: None,
+            read_only: false,
+            scroll: 0,
+            timezone: config.timezone,
+            locale: config.locale,
+        }
+    }
+
+    /// Only shows the transaction, without letting notes be added to it
+    pub fn read_only(mut self) -> Self {
+        self.read_only = true;
+        self
+    }
+
+    /// Appends the note being written, leaving it open if there's nothing written yet
+    async fn submit(mut self, app: &mut App) -> Result<Option<Popup>, AppError> {
+        let text = self
+            .note
+            .as_ref()
+            .map(|note| note.trim().to_owned())
+            .unwrap_or_default();
+        if text.is_empty() {
+            return Ok(Some(Popup::TransactionDetails(self)));
+        }
+        let note = app.data.storage.add_note(self.trans_id, &text).await?;
+        self.thread.push((note.datetime, note.text));
+        self.note = None;
+        app.data.status_text = String::from("Added note");
+        Ok(Some(Popup::TransactionDetails(self)))
+    }
+}
+
+impl PopupHandler for TransactionDetails {
+    async fn handle_event(
+        mut self,
+        app: &mut App,
+        event: &Event,
+    ) -> Result<Option<Popup>, AppError> {
+        if let Event::Key(key) = event {
+            if key.kind == event::KeyEventKind::Press {
+                if let Some(note) = &mut self.note {
+                    match key.code {
+                        KeyCode::Left => note.right(),
+                        KeyCode::Right => note.left(),
+                        KeyCode::Backspace => note.remove_behind(),
+                        KeyCode::Delete => note.remove_ahead(),
+                        KeyCode::Insert => note.inserting = !note.inserting,
+                        KeyCode::Home => note.home(),
+                        KeyCode::End => note.end(),
+                        KeyCode::Enter => return self.submit(app).await,
+                        KeyCode::Esc => self.note = None,
+                        KeyCode::Char(c) => {
+                            note.insert(c);
+                        }
+                        _ => (),
+                    }
+                } else {
+                    match app.data.config.nav_key(key.code) {
+                        KeyCode::Char('a') if !self.read_only => {
+                            self.note = Some(CursoredString::new())
+                        }
+                        KeyCode::Down => self.scroll = self.scroll.saturating_add(1),
+                        KeyCode::Up => self.scroll = self.scroll.saturating_sub(1),
+                        KeyCode::Esc | KeyCode::Enter => return Ok(None),
+                        _ => (),
+                    }
+                }
+            }
+        }
+        Ok(Some(Popup::TransactionDetails(self)))
+    }
+
+    fn render_to_frame(&mut self, area: Rect, frame: &mut Frame, theme: &Theme) {
+        const NOTE_HEIGHT: u16 = 1;
+        const BORDER_SIZE: u16 = 1;
+
+        let [area] = Layout::vertical([Constraint::Percentage(60)])
+            .flex(Flex::Center)
+            .areas(area);
+        let [area] = Layout::horizontal([Constraint::Percentage(50)])
+            .flex(Flex::Center)
+            .areas(area);
+        let hint = if self.note.is_some() {
+            "Enter to add, Esc to cancel"
+        } else if self.read_only {
+            "Esc to close"
+        } else {
+            "a: add note, Esc to close"
+        };
+        let block = Block::bordered()
+            .title("Transaction")
+            .title_bottom(Line::from(hint).dim());
+        frame.render_widget(Clear, area);
+        frame.render_widget(block, area);
+        let area = area.inner(Margin::new(BORDER_SIZE, BORDER_SIZE));
+        let note_height = if self.note.is_some() {
+            NOTE_HEIGHT + 2 * BORDER_SIZE
+        } else {
+            0
+        };
+        let [summary_area, thread_area, note_area] = Layout::vertical([
+            Constraint::Length(self.summary.len() as u16),
+            Constraint::Fill(1),
+            Constraint::Length(note_height),
+        ])
+        .areas(area);
+
+        let summary = Paragraph::new(
+            self.summary
+                .iter()
+                .map(|line| Line::from(line.as_str()))
+                .collect::<Vec<_>>(),
+        );
+
+        let thread_lines: Vec<Line> = self
+            .thread
+            .iter()
+            .map(|(datetime, text)| {
+                let datetime = format::format_datetime(
+                    datetime.assume_utc().to_offset(self.timezone),
+                    self.locale,
+                );
+                Line::from(vec![
+                    Span::from(format!("{datetime}  ")).dim(),
+                    Span::from(text.as_str()),
+                ])
+            })
+            .collect();
+        // keep from scrolling past the last entry, counting each as at least a line
+        self.scroll = self.scroll.min(thread_lines.len().saturating_sub(1) as u16);
+        let thread = Paragraph::new(thread_lines)
+            .wrap(Wrap { trim: false })
+            .scroll((self.scroll, 0))
+            .block(Block::bordered().title("Notes"));
+
+        frame.render_widget(summary, summary_area);
+        frame.render_widget(thread, thread_area);
+
+        if let Some(note) = &self.note {
+            let note_field = Block::bordered().title("New Note").style(theme.active());
+            frame.render_widget(Paragraph::new(note.as_str()).block(note_field), note_area);
+            frame.set_cursor_position(Position::new(
+                note_area.x + note.cursor_index() as u16 + BORDER_SIZE,
+                note_area.y + BORDER_SIZE,
+            ));
+        }
+    }
+}
@@ -0,0 +1,318 @@
+use crossterm::event::{self, Event, KeyCode};
+use ratatui::{
+    layout::{Constraint, Flex, Layout, Margin},
+    prelude::*,
+    widgets::{Block, Clear, Paragraph},
+    Frame,
+};
+
+use crate::{
+    app::{App, AppError},
+    config::Theme,
+    format, parse_amount,
+    storage::{StorageRunError, Transfer, User},
+    CursoredString,
+};
+
+use super::{Popup, PopupHandler};
+
+/// Popup for sending an amount to another pilot, listing the transfers between the two once they're named
+pub struct TransferFunds {
+    pilot: CursoredString,
+    amount: CursoredString,
+    msg: CursoredString,
+    selected_field: TransferField,
+    /// The named pilot and their name as it's shown, once it's been checked they exist
+    other: Option<(User, String)>,
+    /// Transfers between the current pilot and the named one, newest first, formatted for display
+    history: Vec<Line<'static>>,
+    error: Option<String>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum TransferField {
+    Pilot,
+    Amount,
+    Message,
+}
+
+impl TransferFunds {
+    /// Create popup with every field empty
+    pub fn new() -> Self {
+        Self {
+            pilot: CursoredString::new(),
+            amount: CursoredString::new(),
+            msg: CursoredString::new(),
+            selected_field: TransferField::Pilot,
+            other: None,
+            history: Vec::new(),
+            error: None,
+        }
+    }
+
+    fn selected_input(&mut self) -> &mut CursoredString {
+        match self.selected_field {
+            TransferField::Pilot => &mut self.pilot,
+            TransferField::Amount => &mut self.amount,
+            TransferField::Message => &mut self.msg,
+        }
+    }
+
+    fn next_field(&mut self) {
+        self.selected_field = match self.selected_field {
+            TransferField::Pilot => TransferField::Amount,
+            TransferField::Amount => TransferField::Message,
+            TransferField::Message => TransferField::Pilot,
+        }
+    }
+
+    fn prev_field(&mut self) {
+        self.selected_field = match self.selected_field {
+            TransferField::Pilot => TransferField::Message,
+            TransferField::Amount => TransferField::Pilot,
+            TransferField::Message => TransferField::Amount,
+        }
+    }
+
+    /// Checks the typed pilot exists and isn't the current one, loading the transfers between them if so
+    async fn find_pilot(&mut self, app: &App) -> Result<(), AppError> {
+        let current = app.data.current_user.as_ref().unwrap();
+        let name = self.pilot.to_lowercase();
+        let other = match app.data.storage.get_user(&name).await {
+            Ok(user) => user,
+            Err(StorageRunError::RecordMissing) => {
+                self.error = Some(format!("No pilot named '{name}'"));
+                return Ok(());
+            }
+            Err(error) => return Err(error.into()),
+        };
+        if other.get_id() == current.get_id() {
+            self.error = Some(StorageRunError::TransferToSelf.to_string());
+            return Ok(());
+        }
+        let other_name = app.data.config.display_name(other.get_name()).into_owned();
+        self.other = Some((other, other_name));
+        self.load_history(app).await
+    }
+
+    /// Reads the transfers with the named pilot, formatting each as its date, which way it went, its amount, and its message
+    async fn load_history(&mut self, app: &App) -> Result<(), AppError> {
+        let current = app.data.current_user.as_ref().unwrap();
+        let Some((other, other_name)) = &self.other else {
+            return Ok(());
+        };
+        let transfers: Vec<Transfer> = app.data.storage.transfers_between(current, other).await?;
+        let current_name = app
+            .data
+            .config
+            .display_name(current.get_name())
+            .into_owned();
+        let name_of = |user_id: i32| {
+            if user_id == other.get_id() {
+                other_name.clone()
+            } else {
+                current_name.clone()
+            }
+        };
+        self.history = transfers
+            .iter()
+            .filter_map(|transfer| {
+                let side = transfer.sent.as_ref().or(transfer.received.as_ref())?;
+                let datetime = format::format_datetime(
+                    side.datetime
+                        .assume_utc()
+                        .to_offset(app.data.config.timezone),
+                    app.data.config.locale,
+                );
+                // a deleted side leaves only the other to say who was involved
+                let from = transfer
+                    .sent
+                    .as_ref()
+                    .map_or(String::from("?"), |sent| name_of(sent.user_id));
+                let to = transfer
+                    .received
+                    .as_ref()
+                    .map_or(String::from("?"), |received| name_of(received.user_id));
+                Some(Line::from(vec![
+                    Span::from(format!("{datetime}  ")).dim(),
+                    Span::from(format!("{from} -> {to}  ")),
+                    Span::from(app.data.config.amount_format.format(side.value.abs())).bold(),
+                    Span::from(format!("  {}", side.msg)),
+                ]))
+            })
+            .collect();
+        Ok(())
+    }
+
+    /// Sends the typed amount to the named pilot, keeping the popup open to show it in the history
+    async fn submit(mut self, app: &mut App) -> Result<Option<Popup>, AppError> {
+        let Some((other, other_name)) = self.other.clone() else {
+            self.selected_field = TransferField::Pilot;
+            self.find_pilot(app).await?;
+            return Ok(Some(Popup::TransferFunds(self)));
+        };
+        let amount = match parse_amount(&self.amount) {
+            Ok(amount) if amount > 0 => amount,
+            Ok(_) => {
+                self.error = Some(String::from("Amount must be more than nothing"));
+                self.selected_field = TransferField::Amount;
+                return Ok(Some(Popup::TransferFunds(self)));
+            }
+            Err(error) => {
+                self.error = Some(error.to_string());
+                self.selected_field = TransferField::Amount;
+                return Ok(Some(Popup::TransferFunds(self)));
+            }
+        };
+        let current = app.data.current_user.as_ref().unwrap();
+        let msg = if self.msg.trim().is_empty() {
+            format!(
+                "Transfer from {} to {other_name}",
+                app.data.config.display_name(current.get_name())
+            )
+        } else {
+            self.msg.trim().to_owned()
+        };
+        app.data
+            .storage
+            .add_transfer(current.get_id(), other.get_id(), amount, &msg)
+            .await?;
+        app.data.status_text = format!(
+            "Sent {} to '{other_name}'",
+            app.data.config.amount_format.format(amount)
+        );
+        app.data.update_table().await?;
+
+        self.amount = CursoredString::new();
+        self.msg = CursoredString::new();
+        self.selected_field = TransferField::Amount;
+        self.load_history(app).await?;
+        Ok(Some(Popup::TransferFunds(self)))
+    }
+}
+
+impl Default for TransferFunds {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PopupHandler for TransferFunds {
+    async fn handle_event(
+        mut self,
+        app: &mut App,
+        event: &Event,
+    ) -> Result<Option<Popup>, AppError> {
+        if let Event::Key(key) = event {
+            if key.kind == event::KeyEventKind::Press {
+                match key.code {
+                    KeyCode::Tab | KeyCode::Down => self.next_field(),
+                    KeyCode::BackTab | KeyCode::Up => self.prev_field(),
+                    KeyCode::Left => self.selected_input().right(),
+                    KeyCode::Right => self.selected_input().left(),
+                    KeyCode::Enter if self.selected_field == TransferField::Pilot => {
+                        self.find_pilot(app).await?;
+                        if self.other.is_some() {
+                            self.selected_field = TransferField::Amount;
+                        }
+                    }
+                    KeyCode::Enter => return self.submit(app).await,
+                    KeyCode::Esc => return Ok(None),
+                    KeyCode::Backspace | KeyCode::Delete | KeyCode::Char(_) => {
+                        self.error = None;
+                        let input = self.selected_input();
+                        match key.code {
+                            KeyCode::Backspace => input.remove_behind(),
+                            KeyCode::Delete => input.remove_ahead(),
+                            KeyCode::Char(c) => {
+                                input.insert(c);
+                            }
+                            _ => (),
+                        }
+                        // a different name needs checking again before anything can be sent
+                        if self.selected_field == TransferField::Pilot {
+                            self.other = None;
+                            self.history.clear();
+                        }
+                    }
+                    KeyCode::Insert => {
+                        let input = self.selected_input();
+                        input.inserting = !input.inserting;
+                    }
+                    _ => (),
+                }
+            }
+        }
+        Ok(Some(Popup::TransferFunds(self)))
+    }
+
+    fn render_to_frame(&mut self, area: Rect, frame: &mut Frame, theme: &Theme) {
+        const FIELD_HEIGHT: u16 = 1;
+        const BORDER_SIZE: u16 = 1;
+
+        let [area] = Layout::vertical([Constraint::Percentage(60)])
+            .flex(Flex::Center)
+            .areas(area);
+        let [area] = Layout::horizontal([Constraint::Percentage(50)])
+            .flex(Flex::Center)
+            .areas(area);
+        let mut block = Block::bordered()
+            .title("Transfer")
+            .title_bottom(Line::from("Tab: next field, Enter: send, Esc: close").dim());
+        if let Some(error) = &self.error {
+            block = block.title(Line::from(error.as_str()).red().right_aligned());
+        }
+        frame.render_widget(Clear, area);
+        frame.render_widget(block, area);
+        let area = area.inner(Margin::new(BORDER_SIZE, BORDER_SIZE));
+        let [pilot_area, amount_area, msg_area, history_area] = Layout::vertical([
+            Constraint::Length(FIELD_HEIGHT + BORDER_SIZE * 2),
+            Constraint::Length(FIELD_HEIGHT + BORDER_SIZE * 2),
+            Constraint::Length(FIELD_HEIGHT + BORDER_SIZE * 2),
+            Constraint::Fill(1),
+        ])
+        .areas(area);
+
+        let active_style = theme.active();
+        for (field, title, input, field_area) in [
+            (TransferField::Pilot, "To pilot", &self.pilot, pilot_area),
+            (TransferField::Amount, "Amount", &self.amount, amount_area),
+            (TransferField::Message, "Message", &self.msg, msg_area),
+        ] {
+            let mut field_block = Block::bordered().title(title);
+            let width = usize::from(field_area.width.saturating_sub(2 * BORDER_SIZE));
+            let (visible, cursor) = input.visible(width);
+            if field == self.selected_field {
+                field_block = field_block.style(active_style);
+                frame.set_cursor_position(Position::new(
+                    field_area.x + cursor as u16 + BORDER_SIZE,
+                    field_area.y + BORDER_SIZE,
+                ));
+            }
+            frame.render_widget(Paragraph::new(visible).block(field_block), field_area);
+        }
+
+        let history_title = match &self.other {
+            Some((_, name)) => format!(
+                "Transfers with '{}'",
+                format::truncate(
+                    name,
+                    usize::from(history_area.width.saturating_sub(2 * BORDER_SIZE))
+                        .saturating_sub("Transfers with ''".len())
+                )
+            ),
+            None => String::from("Transfers"),
+        };
+        let history = if self.other.is_none() {
+            Paragraph::new(Line::from("Enter a pilot to see transfers with them").dim())
+        } else if self.history.is_empty() {
+            Paragraph::new(Line::from("None yet").dim())
+        } else {
+            Paragraph::new(self.history.clone())
+        };
+        frame.render_widget(
+            history.block(Block::bordered().title(history_title)),
+            history_area,
+        );
+    }
+}
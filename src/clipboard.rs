@@ -0,0 +1,62 @@
+//! Copying text to the system clipboard without a windowing library,
+//! by handing it to whichever clipboard tool is installed or asking the terminal to do it
+
+use std::{
+    io::{self, Write},
+    process::{Command, Stdio},
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+/// Commands that take text to copy on stdin, tried in order
+const TOOLS: [(&str, &[&str]); 5] = [
+    ("wl-copy", &[]),
+    ("xclip", &["-selection", "clipboard"]),
+    ("xsel", &["--clipboard", "--input"]),
+    ("pbcopy", &[]),
+    ("clip.exe", &[]),
+];
+
+/// How text was copied, as the terminal fallback can't tell whether it worked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CopyMethod {
+    /// One of the clipboard tools took it
+    Tool(&'static str),
+    /// Sent to the terminal as an OSC 52 sequence, which only works if the terminal supports it
+    Terminal,
+}
+
+/// Copies the text to the clipboard, trying each clipboard tool before falling back to the terminal.
+/// Only fails if the text couldn't even be written to the terminal
+pub fn copy(text: &str) -> io::Result<CopyMethod> {
+    for (tool, args) in TOOLS {
+        if copy_with(tool, args, text).is_ok() {
+            return Ok(CopyMethod::Tool(tool));
+        }
+    }
+    let mut stdout = io::stdout();
+    write!(stdout, "\x1b]52;c;{}\x07", STANDARD.encode(text))?;
+    stdout.flush()?;
+    Ok(CopyMethod::Terminal)
+}
+
+/// Pipes the text into a clipboard tool, failing if it isn't installed or doesn't exit cleanly
+fn copy_with(tool: &str, args: &[&str], text: &str) -> io::Result<()> {
+    let mut child = Command::new(tool)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    // dropped once written so the tool sees the end of the text
+    child
+        .stdin
+        .take()
+        .expect("stdin was piped")
+        .write_all(text.as_bytes())?;
+    if child.wait()?.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!("{tool} failed")))
+    }
+}
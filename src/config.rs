@@ -1,13 +1,22 @@
 //! This module provides configuration data and serialization
 use std::{
+    borrow::Cow,
     fs::File,
     io::{Seek, SeekFrom},
+    path::PathBuf,
 };
 
+use crossterm::event::{KeyCode, KeyModifiers};
+use ratatui::style::{Color, Style};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use time::UtcOffset;
 
+use crate::{
+    format::{self, AmountFormatter, Locale},
+    storage::TransactionType,
+};
+
 #[cfg(doc)]
 use crate::app::App;
 
@@ -16,20 +25,86 @@ mod config_serde;
 /// Possible errors while loading a [`Config`] from a file
 #[derive(Error, Debug)]
 pub enum ConfigError {
-    #[error(transparent)]
-    BaseDirs(#[from] xdg::BaseDirectoriesError),
     #[error(transparent)]
     Io(#[from] std::io::Error),
     #[error(transparent)]
     Serde(#[from] serde_yaml::Error),
 }
 
+/// A config file that couldn't be parsed, which was moved aside so the defaults could be used instead
+#[derive(Debug)]
+pub struct ConfigRecovery {
+    /// Where the broken file was moved to
+    pub backup: PathBuf,
+    pub error: serde_yaml::Error,
+}
+
 /// Configuration for the [`App`] to use
 #[derive(Serialize, Deserialize)]
+#[serde(default)]
 pub struct Config {
     pub currency: Currency,
     #[serde(with = "config_serde::utc_offset")]
     pub timezone: UtcOffset,
+    /// Enter amounts as a positive magnitude with a credit/debit choice instead of a signed value
+    pub credit_debit: bool,
+    /// Also navigate with h/j/k/l outside of text fields
+    pub vim_keys: bool,
+    /// Show the current user's balance beside the status messages
+    pub show_balance: bool,
+    /// Select table rows and press popup buttons with the mouse. Captures the mouse, so terminal text selection needs a modifier held
+    pub mouse: bool,
+    /// Count pending transactions towards the balance, rather than only cleared ones
+    pub pending_in_balance: bool,
+    /// Apps whose data directories are checked for a database to import on startup. Each is only offered once
+    pub legacy_apps: Vec<String>,
+    /// Suggest recently used messages while typing a message, accepted with Tab
+    pub autocomplete: bool,
+    /// Transaction types that can't be added without a message
+    pub require_message: Vec<TransactionType>,
+    /// Skip every confirmation and carry out destructive actions such as deleting or quitting immediately
+    pub fast_mode: bool,
+    /// Round the figures in the summary to the nearest multiple of this. Display only, stored values and exports stay exact
+    pub summary_rounding: Option<u32>,
+    /// Language for weekday and month names in dates, which are shown numerically if unset
+    pub locale: Option<Locale>,
+    /// How amounts are displayed: symbol, grouping, decimals, and sign style
+    pub amount_format: AmountFormatter,
+    /// How far the arrow keys step an amount, depending on the modifiers held
+    pub amount_steps: AmountSteps,
+    /// Key that quits from the table, taking priority over any other action on the same key
+    pub quit_key: char,
+    /// Keys for the table's lowercase actions. Ones that clash with another key are put back to their default
+    pub keybindings: Keybindings,
+    /// Also quit with Esc from the table. When off, Esc only backs out of the selection or the deleted view
+    pub esc_quits: bool,
+    /// Ask before adding a transaction whose amount, either way, is over this, to catch mistyped amounts. Never asks if unset
+    pub large_transaction_threshold: Option<u32>,
+    /// Keep the add transaction popup open after submitting, cleared for the next entry, until closed with Esc
+    pub keep_add_open: bool,
+    /// Most characters a transaction's message can have when adding or editing one. Unlimited if unset
+    pub max_message_len: Option<usize>,
+    /// How many rows the table loads at a time, with the rows loaded following the highlighted one as it's scrolled. 500 if unset
+    pub max_display_rows: Option<u32>,
+    /// Play the intro animation on startup. When off, the intro is never shown
+    pub show_intro: bool,
+    /// Only play the intro on the first run of each version, skipping it otherwise
+    pub intro_once_per_version: bool,
+    /// How often the big balance display re-reads the balance, in seconds, to pick up changes made elsewhere
+    pub big_balance_refresh_secs: u64,
+    /// Show usernames title-cased, such as "Kaito" for "kaito". Display only, names are still stored and looked up in lowercase
+    pub title_case_names: bool,
+    /// Make transactions added within this many minutes stand out in the table. Off if unset
+    pub recent_within_mins: Option<u32>,
+    /// Mute transactions older than this many days in the table, such as pending ones gone stale. Off if unset
+    pub stale_after_days: Option<u32>,
+    /// Shade every other row of the table so values are easier to follow across to their dates
+    pub row_stripes: bool,
+    /// Background of the shaded rows when `row_stripes` is on
+    #[serde(with = "config_serde::color")]
+    pub stripe_color: Color,
+    /// Colors of the selected field, highlighted rows, and the table's border
+    pub theme: Theme,
 }
 
 /// Configuration for currency type, optional short form
@@ -39,18 +114,353 @@ pub struct Currency {
     pub short: Option<String>,
 }
 
+/// Steps for changing an amount with the arrow keys. Shift picks the fine step,
+/// then Control and Alt each multiply whichever step is in use
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AmountSteps {
+    /// Step with no modifiers held
+    pub base: i32,
+    /// Step with Shift held
+    pub shift: i32,
+    pub control_multiplier: i32,
+    pub alt_multiplier: i32,
+}
+
+/// Colors used across the app, each read by name ("lightyellow"), index ("236"), or hex ("#262626")
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Theme {
+    /// Background of the field or button being typed in or chosen
+    #[serde(with = "config_serde::color")]
+    pub active_bg: Color,
+    /// Text of the field or button being typed in or chosen
+    #[serde(with = "config_serde::color")]
+    pub active_fg: Color,
+    /// Background of the highlighted row in the table and lists, and of chosen options
+    #[serde(with = "config_serde::color")]
+    pub highlight_bg: Color,
+    /// Text of the highlighted row in the table and lists, and of chosen options
+    #[serde(with = "config_serde::color")]
+    pub highlight_fg: Color,
+    /// Border around the transaction table
+    #[serde(with = "config_serde::color")]
+    pub border: Color,
+    /// Amounts in the table and the big balance that add to the balance
+    #[serde(with = "config_serde::color")]
+    pub income: Color,
+    /// Amounts in the table and the big balance that take from the balance
+    #[serde(with = "config_serde::color")]
+    pub expense: Color,
+    /// Text of the table's selected rows
+    #[serde(with = "config_serde::color")]
+    pub selected: Color,
+    /// Text of rows added within `recent_within_mins`
+    #[serde(with = "config_serde::color")]
+    pub recent: Color,
+    /// Text of rows older than `stale_after_days`
+    #[serde(with = "config_serde::color")]
+    pub stale: Color,
+}
+
+/// Keys for the table actions that can be rebound. The uppercase actions keep their keys
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Keybindings {
+    pub add: char,
+    pub edit: char,
+    pub delete: char,
+    /// Toggle the highlighted transaction between pending and cleared
+    pub toggle_status: char,
+    pub set_type: char,
+    pub filter: char,
+    /// Step back through the filter history, `Z` stepping forward again
+    pub undo_filters: char,
+    pub quick_add: char,
+    pub summary: char,
+    pub reasons: char,
+    pub pin: char,
+    /// Switch the table between the configured timezone and UTC
+    pub utc: char,
+    pub export: char,
+    /// Copy the filtered total to the clipboard
+    pub copy_total: char,
+    pub version: char,
+    pub logout: char,
+}
+
+impl Keybindings {
+    /// Select or deselect the highlighted transaction
+    pub const SELECT: char = ' ';
+    /// Add the last transaction again, now
+    pub const REPEAT: char = '.';
+    /// List the table's keys
+    pub const HELP: char = '?';
+    /// Show the balance alone, as large as it fits
+    pub const BIG_BALANCE: char = 'B';
+    /// Show other pilots' transactions alongside the current one's
+    pub const COMBINE: char = 'C';
+    /// Switch between the live and deleted transactions
+    pub const SHOW_DELETED: char = 'D';
+    /// Start or end a session
+    pub const SESSION: char = 'G';
+    /// Merge another pilot into the current one
+    pub const MERGE: char = 'M';
+    /// Permanently delete deleted transactions
+    pub const PURGE: char = 'P';
+    /// Restore deleted transactions
+    pub const RESTORE: char = 'R';
+    /// Split the highlighted transaction
+    pub const SPLIT: char = 'S';
+    /// Transfer to another pilot
+    pub const TRANSFER: char = 'T';
+    /// Switch vault
+    pub const VAULT: char = 'V';
+    /// Export the selected transactions
+    pub const EXPORT_SELECTION: char = 'X';
+    /// Step forward through the filter history
+    pub const REDO_FILTERS: char = 'Z';
+
+    /// Table keys that aren't rebindable, so can't be taken by a binding
+    pub const FIXED: [char; 15] = [
+        Self::SELECT,
+        Self::REPEAT,
+        Self::HELP,
+        Self::BIG_BALANCE,
+        Self::COMBINE,
+        Self::SHOW_DELETED,
+        Self::SESSION,
+        Self::MERGE,
+        Self::PURGE,
+        Self::RESTORE,
+        Self::SPLIT,
+        Self::TRANSFER,
+        Self::VAULT,
+        Self::EXPORT_SELECTION,
+        Self::REDO_FILTERS,
+    ];
+
+    /// Keys that change the highlighted transaction itself
+    pub fn changing_highlighted(&self) -> [char; 4] {
+        [self.edit, self.delete, Self::SELECT, Self::SPLIT]
+    }
+
+    /// Keys that change the selected transactions, or the highlighted one when nothing is selected
+    pub fn changing_selection(&self) -> [char; 4] {
+        [
+            self.set_type,
+            self.toggle_status,
+            Self::RESTORE,
+            Self::PURGE,
+        ]
+    }
+
+    /// Each binding's name, key, and default key, in the order they claim keys
+    fn entries(&mut self) -> [(&'static str, &mut char, char); 16] {
+        let default = Self::default();
+        [
+            ("add", &mut self.add, default.add),
+            ("edit", &mut self.edit, default.edit),
+            ("delete", &mut self.delete, default.delete),
+            (
+                "toggle_status",
+                &mut self.toggle_status,
+                default.toggle_status,
+            ),
+            ("set_type", &mut self.set_type, default.set_type),
+            ("filter", &mut self.filter, default.filter),
+            ("undo_filters", &mut self.undo_filters, default.undo_filters),
+            ("quick_add", &mut self.quick_add, default.quick_add),
+            ("summary", &mut self.summary, default.summary),
+            ("reasons", &mut self.reasons, default.reasons),
+            ("pin", &mut self.pin, default.pin),
+            ("utc", &mut self.utc, default.utc),
+            ("export", &mut self.export, default.export),
+            ("copy_total", &mut self.copy_total, default.copy_total),
+            ("version", &mut self.version, default.version),
+            ("logout", &mut self.logout, default.logout),
+        ]
+    }
+
+    /// Puts back to its default any binding that's whitespace, one of the `reserved` keys, or shared with
+    /// another binding, until none clash. Returns the names of the bindings put back
+    ///
+    /// ```
+    /// use mantra_lancer::config::Keybindings;
+    ///
+    /// let mut keys = Keybindings {
+    ///     add: 'n',
+    ///     edit: 'n',
+    ///     delete: 'q',
+    ///     filter: 'e',
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(keys.reset_conflicts(&['q']), ["add", "edit", "delete", "filter"]);
+    /// assert_eq!(keys, Keybindings::default());
+    /// ```
+    pub fn reset_conflicts(&mut self, reserved: &[char]) -> Vec<&'static str> {
+        let mut reset = Vec::new();
+        loop {
+            let mut entries = self.entries();
+            let keys: Vec<char> = entries.iter().map(|(_, key, _)| **key).collect();
+            let mut changed = false;
+            for (index, (name, key, default)) in entries.iter_mut().enumerate() {
+                let clashes = key.is_whitespace()
+                    || reserved.contains(key)
+                    || Self::FIXED.contains(key)
+                    || keys
+                        .iter()
+                        .enumerate()
+                        .any(|(other, other_key)| other != index && other_key == *key);
+                // a default that clashes is only with the reserved keys, which take priority anyway
+                if clashes && **key != *default {
+                    **key = *default;
+                    reset.push(*name);
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        reset
+    }
+}
+
+impl Theme {
+    /// Style of the field or button being typed in or chosen
+    pub fn active(&self) -> Style {
+        Style::new().bg(self.active_bg).fg(self.active_fg)
+    }
+
+    /// Style of the highlighted row in the table and lists, and of chosen options
+    pub fn highlight(&self) -> Style {
+        Style::new().bg(self.highlight_bg).fg(self.highlight_fg)
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            active_bg: Color::LightYellow,
+            active_fg: Color::Black,
+            highlight_bg: Color::White,
+            highlight_fg: Color::Black,
+            border: Color::White,
+            income: Color::Green,
+            expense: Color::Red,
+            selected: Color::Yellow,
+            recent: Color::LightCyan,
+            stale: Color::DarkGray,
+        }
+    }
+}
+
+impl Default for Keybindings {
+    fn default() -> Self {
+        Self {
+            add: 'a',
+            edit: 'e',
+            delete: 'd',
+            toggle_status: 'c',
+            set_type: 't',
+            filter: 'f',
+            undo_filters: 'z',
+            quick_add: 'i',
+            summary: 's',
+            reasons: 'r',
+            pin: 'p',
+            utc: 'u',
+            export: 'x',
+            copy_total: 'y',
+            version: 'v',
+            logout: 'o',
+        }
+    }
+}
+
 impl Config {
     /// A default configuration for Manna and local/UTC time (current_local_offset doesn't work on linux)
     pub fn new() -> Self {
         Self {
             currency: "Manna".into(),
             timezone: UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC),
+            credit_debit: false,
+            vim_keys: false,
+            show_balance: true,
+            mouse: false,
+            fast_mode: false,
+            require_message: Vec::new(),
+            autocomplete: false,
+            pending_in_balance: true,
+            legacy_apps: vec![String::from("manna"), String::from("tracker")],
+            summary_rounding: None,
+            locale: None,
+            amount_format: AmountFormatter::default(),
+            amount_steps: AmountSteps::default(),
+            max_display_rows: None,
+            large_transaction_threshold: None,
+            keep_add_open: false,
+            max_message_len: Some(256),
+            quit_key: 'q',
+            keybindings: Keybindings::default(),
+            esc_quits: true,
+            show_intro: true,
+            intro_once_per_version: false,
+            big_balance_refresh_secs: 2,
+            title_case_names: false,
+            recent_within_mins: None,
+            stale_after_days: None,
+            row_stripes: false,
+            stripe_color: Color::Indexed(236),
+            theme: Theme::default(),
+        }
+    }
+
+    /// A username as it should be shown, title-cased if `title_case_names` is on
+    pub fn display_name<'a>(&self, name: &'a str) -> Cow<'a, str> {
+        if self.title_case_names {
+            Cow::Owned(format::title_case(name))
+        } else {
+            Cow::Borrowed(name)
+        }
+    }
+
+    /// Puts back any keybinding that clashes with the quit key, vim navigation, or another key,
+    /// returning a warning naming them if there were any
+    pub fn check_keybindings(&mut self) -> Option<String> {
+        let mut reserved = vec![self.quit_key];
+        if self.vim_keys {
+            reserved.extend(['h', 'j', 'k', 'l']);
+        }
+        let reset = self.keybindings.reset_conflicts(&reserved);
+        if reset.is_empty() {
+            return None;
+        }
+        Some(format!(
+            "Warning: keybindings for {} clashed with other keys, using their defaults",
+            reset.join(", ")
+        ))
+    }
+
+    /// Maps h/j/k/l onto the arrow keys if vim keys are enabled, otherwise returns the key unchanged.
+    /// Shouldn't be used where the user is typing text.
+    pub fn nav_key(&self, code: KeyCode) -> KeyCode {
+        if !self.vim_keys {
+            return code;
+        }
+        match code {
+            KeyCode::Char('h') => KeyCode::Left,
+            KeyCode::Char('j') => KeyCode::Down,
+            KeyCode::Char('k') => KeyCode::Up,
+            KeyCode::Char('l') => KeyCode::Right,
+            code => code,
         }
     }
 
     /// Loads or creates a default config in the mantra xdg directory
     pub async fn load_or_create() -> Result<Config, ConfigError> {
-        let config_path = super::base_dirs()?.place_config_file("config.yaml")?;
+        let config_path = crate::dirs::app_dirs().place_config_file("config.yaml")?;
         let config_file = match File::open(&config_path) {
             Ok(file) => file,
             Err(error) => match error.kind() {
@@ -70,6 +480,57 @@ impl Config {
         };
         Ok(serde_yaml::from_reader(config_file)?)
     }
+
+    /// Whether a config file has been written yet, which is only false before the first run
+    pub fn exists() -> Result<bool, ConfigError> {
+        Ok(crate::dirs::app_dirs()
+            .find_config_file("config.yaml")
+            .is_some())
+    }
+
+    /// Writes the config to the mantra xdg directory, replacing what's there
+    pub async fn save(&self) -> Result<(), ConfigError> {
+        let config_path = crate::dirs::app_dirs().place_config_file("config.yaml")?;
+        serde_yaml::to_writer(File::create(config_path)?, self)?;
+        Ok(())
+    }
+
+    /// Like [`Config::load_or_create`], but a file that fails to parse is moved to `config.yaml.bak`
+    /// and replaced with the defaults, so a bad hand edit doesn't stop the app from starting
+    pub async fn load_or_recover() -> Result<(Config, Option<ConfigRecovery>), ConfigError> {
+        match Self::load_or_create().await {
+            Err(ConfigError::Serde(error)) => {
+                let config_path = crate::dirs::app_dirs().place_config_file("config.yaml")?;
+                let backup = config_path.with_extension("yaml.bak");
+                std::fs::rename(&config_path, &backup)?;
+                let config = Self::load_or_create().await?;
+                Ok((config, Some(ConfigRecovery { backup, error })))
+            }
+            result => result.map(|config| (config, None)),
+        }
+    }
+}
+
+impl ConfigRecovery {
+    /// Explains what went wrong and where, for showing to the user
+    pub fn notice(&self) -> String {
+        let error = self.error.to_string();
+        let (location, reason) = match self.error.location() {
+            Some(location) => {
+                let (line, column) = (location.line(), location.column());
+                // serde_yaml puts the location at the end of its message, it reads better up front
+                let reason = error
+                    .strip_suffix(&format!(" at line {line} column {column}"))
+                    .unwrap_or(&error);
+                (format!(" at line {line}, column {column}"), reason)
+            }
+            None => (String::new(), error.as_str()),
+        };
+        format!(
+            "config.yaml couldn't be read{location}: {reason}\n\nIt was moved to {} and the default settings are being used",
+            self.backup.display()
+        )
+    }
 }
 
 impl Default for Config {
@@ -78,6 +539,77 @@ impl Default for Config {
     }
 }
 
+impl AmountSteps {
+    /// Returns the step for the given held modifier keys
+    pub fn step(&self, modifiers: KeyModifiers) -> i32 {
+        let mut value = if modifiers.contains(KeyModifiers::SHIFT) {
+            self.shift
+        } else {
+            self.base
+        };
+        if modifiers.contains(KeyModifiers::CONTROL) {
+            value = value.saturating_mul(self.control_multiplier);
+        }
+        if modifiers.contains(KeyModifiers::ALT) {
+            value = value.saturating_mul(self.alt_multiplier);
+        }
+        value
+    }
+
+    /// Describes the steps each modifier gives, for showing while an amount is being changed
+    ///
+    /// ```
+    /// use mantra_lancer::config::AmountSteps;
+    ///
+    /// assert_eq!(AmountSteps::default().hint(), "±10 · Shift:1 · Ctrl:50 · Alt:200");
+    /// ```
+    pub fn hint(&self) -> String {
+        format!(
+            "±{} · Shift:{} · Ctrl:{} · Alt:{}",
+            self.step(KeyModifiers::NONE),
+            self.step(KeyModifiers::SHIFT),
+            self.step(KeyModifiers::CONTROL),
+            self.step(KeyModifiers::ALT)
+        )
+    }
+}
+
+impl Default for AmountSteps {
+    fn default() -> Self {
+        Self {
+            base: 10,
+            shift: 1,
+            control_multiplier: 5,
+            alt_multiplier: 20,
+        }
+    }
+}
+
+impl Currency {
+    /// Widths narrower than this show the short form
+    pub const NARROW_WIDTH: u16 = 80;
+
+    /// The name to show in a layout this wide, the short form if it's narrow and there is one
+    ///
+    /// ```
+    /// use mantra_lancer::config::Currency;
+    ///
+    /// let currency = Currency {
+    ///     long: String::from("Manna"),
+    ///     short: Some(String::from("m")),
+    /// };
+    /// assert_eq!(currency.display_name(120), "Manna");
+    /// assert_eq!(currency.display_name(60), "m");
+    /// assert_eq!(Currency::from("Manna").display_name(60), "Manna");
+    /// ```
+    pub fn display_name(&self, width: u16) -> &str {
+        match &self.short {
+            Some(short) if width < Self::NARROW_WIDTH => short,
+            _ => &self.long,
+        }
+    }
+}
+
 impl From<String> for Currency {
     fn from(value: String) -> Self {
         Self {
@@ -92,3 +624,46 @@ impl From<&str> for Currency {
         Self::from(value.to_owned())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{lock_config, test_home};
+
+    #[test]
+    fn changing_keys_follow_rebinding() {
+        let keys = Keybindings {
+            edit: 'n',
+            set_type: 'y',
+            ..Default::default()
+        };
+        assert!(keys.changing_highlighted().contains(&'n'));
+        assert!(!keys.changing_highlighted().contains(&'e'));
+        assert!(keys.changing_selection().contains(&'y'));
+        // the fixed keys among them can't be rebound, so can't clash with the bindings
+        for key in keys
+            .changing_highlighted()
+            .into_iter()
+            .chain(keys.changing_selection())
+        {
+            let bound = [keys.edit, keys.delete, keys.set_type, keys.toggle_status].contains(&key);
+            assert!(bound != Keybindings::FIXED.contains(&key));
+        }
+    }
+
+    #[async_std::test]
+    async fn bad_config_is_backed_up_and_replaced_with_defaults() -> Result<(), ConfigError> {
+        let _config = lock_config().await;
+        let home = test_home();
+        // no timezone is 30 hours ahead
+        std::fs::write(home.join("config.yaml"), "timezone:\n  hours: 30\n")?;
+
+        let (config, recovery) = Config::load_or_recover().await?;
+        let recovery = recovery.expect("the bad timezone is reported");
+        assert_eq!(recovery.backup, home.join("config.yaml.bak"));
+        assert!(recovery.backup.exists());
+        assert!(recovery.notice().contains("invalid timezone"));
+        assert_eq!(config.quit_key, Config::default().quit_key);
+        Ok(())
+    }
+}
@@ -1,5 +1,5 @@
 pub(crate) mod utc_offset {
-    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
     use time::UtcOffset;
 
     #[derive(Serialize, Deserialize)]
@@ -13,7 +13,7 @@ pub(crate) mod utc_offset {
     where
         D: Deserializer<'de>,
     {
-        Ok(match <Option<UtcOffsetDef>>::deserialize(deserializer)? {
+        match <Option<UtcOffsetDef>>::deserialize(deserializer)? {
             Some(UtcOffsetDef {
                 hours,
                 minutes,
@@ -23,10 +23,10 @@ pub(crate) mod utc_offset {
                 minutes.unwrap_or(0),
                 seconds.unwrap_or(0),
             )
-            .ok(),
-            None => UtcOffset::current_local_offset().ok(),
+            .map_err(|error| D::Error::custom(format!("invalid timezone: {error}"))),
+            // only a missing offset falls back, as the local one can't always be found
+            None => Ok(UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC)),
         }
-        .unwrap_or(UtcOffset::UTC))
     }
 
     pub fn serialize<S>(utc_offset: &UtcOffset, serializer: S) -> Result<S::Ok, S::Error>
@@ -42,3 +42,30 @@ pub(crate) mod utc_offset {
         .serialize(serializer)
     }
 }
+
+pub(crate) mod color {
+    use std::str::FromStr;
+
+    use ratatui::style::Color;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    /// Reads a color by name ("darkgray"), index ("236"), or hex ("#262626")
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Color, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let name = String::deserialize(deserializer)?;
+        Color::from_str(&name).map_err(|_| {
+            D::Error::custom(format!(
+                "'{name}' isn't a color, expected a name, a 256 color index, or a hex code like #262626"
+            ))
+        })
+    }
+
+    pub fn serialize<S>(color: &Color, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(color)
+    }
+}
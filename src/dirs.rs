@@ -0,0 +1,111 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    io,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use xdg::BaseDirectories;
+
+/// Environment variable naming a single directory to keep everything in, instead of the XDG directories
+pub const HOME_VAR: &str = "MANTRA_HOME";
+/// Used when neither `MANTRA_HOME` is set nor the XDG directories can be found, relative to the working directory
+const FALLBACK_DIR: &str = "mantra-data";
+
+/// Where mantra keeps its config, databases, exports, and state
+pub enum AppDirs {
+    /// The usual XDG directories, under a `mantra` prefix
+    Xdg(BaseDirectories),
+    /// One directory holding every file, given by `MANTRA_HOME` or the fallback when XDG isn't available
+    Home(PathBuf),
+}
+
+impl AppDirs {
+    /// `MANTRA_HOME` if it's set, otherwise the XDG directories, falling back to `./mantra-data`
+    /// in environments without them such as minimal containers
+    fn choose() -> Self {
+        if let Some(home) = std::env::var_os(HOME_VAR).filter(|home| !home.is_empty()) {
+            return AppDirs::Home(PathBuf::from(home));
+        }
+        match BaseDirectories::with_prefix("mantra") {
+            Ok(base_dirs) => AppDirs::Xdg(base_dirs),
+            Err(_) => {
+                AppDirs::Home(std::path::absolute(FALLBACK_DIR).unwrap_or(FALLBACK_DIR.into()))
+            }
+        }
+    }
+
+    /// Path to write a config file to, creating the directories leading to it
+    pub fn place_config_file(&self, path: impl AsRef<Path>) -> io::Result<PathBuf> {
+        match self {
+            AppDirs::Xdg(base_dirs) => base_dirs.place_config_file(path),
+            AppDirs::Home(home) => place(home, path),
+        }
+    }
+
+    /// Path to write a data file to, creating the directories leading to it
+    pub fn place_data_file(&self, path: impl AsRef<Path>) -> io::Result<PathBuf> {
+        match self {
+            AppDirs::Xdg(base_dirs) => base_dirs.place_data_file(path),
+            AppDirs::Home(home) => place(home, path),
+        }
+    }
+
+    /// Path to write a state file to, creating the directories leading to it
+    pub fn place_state_file(&self, path: impl AsRef<Path>) -> io::Result<PathBuf> {
+        match self {
+            AppDirs::Xdg(base_dirs) => base_dirs.place_state_file(path),
+            AppDirs::Home(home) => place(home, path),
+        }
+    }
+
+    /// The config file's path, if it exists
+    pub fn find_config_file(&self, path: impl AsRef<Path>) -> Option<PathBuf> {
+        match self {
+            AppDirs::Xdg(base_dirs) => base_dirs.find_config_file(path),
+            AppDirs::Home(home) => Some(home.join(path)).filter(|path| path.exists()),
+        }
+    }
+
+    /// Files within a data directory, empty if it doesn't exist
+    pub fn list_data_files(&self, path: impl AsRef<Path>) -> Vec<PathBuf> {
+        match self {
+            AppDirs::Xdg(base_dirs) => base_dirs.list_data_files(path),
+            AppDirs::Home(home) => std::fs::read_dir(home.join(path))
+                .map(|entries| {
+                    entries
+                        .filter_map(|entry| Some(entry.ok()?.path()))
+                        .collect()
+                })
+                .unwrap_or_default(),
+        }
+    }
+}
+
+impl Display for AppDirs {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            AppDirs::Xdg(base_dirs) => write!(
+                f,
+                "XDG directories, data in {}",
+                base_dirs.get_data_home().display()
+            ),
+            AppDirs::Home(home) => write!(f, "{}", home.display()),
+        }
+    }
+}
+
+/// Joins the path onto the directory, creating any directories it needs
+fn place(dir: &Path, path: impl AsRef<Path>) -> io::Result<PathBuf> {
+    let path = dir.join(path);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    Ok(path)
+}
+
+/// The directories mantra uses, chosen the first time they're needed and kept for the rest of the run
+pub fn app_dirs() -> &'static AppDirs {
+    static DIRS: OnceLock<AppDirs> = OnceLock::new();
+    DIRS.get_or_init(AppDirs::choose)
+}
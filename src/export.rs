@@ -0,0 +1,256 @@
+//! This module writes transactions out to shareable CSV and JSON files
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::PathBuf,
+    pin::pin,
+};
+
+use async_std::stream::{Stream, StreamExt};
+use serde::Serialize;
+use strum::VariantNames;
+use thiserror::Error;
+use time::{format_description::well_known::Rfc3339, UtcOffset};
+
+use crate::{
+    format::AmountFormatter,
+    storage::{
+        StorageRunError, Transaction, TransactionFilter, TransactionType, TransactionTypeMap, User,
+    },
+};
+
+/// Possible errors while exporting transactions
+#[derive(Error, Debug)]
+pub enum ExportError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    StorageRun(#[from] StorageRunError),
+}
+
+/// The criteria that produced an export, so the recipient knows its scope
+#[derive(Serialize)]
+struct ExportedFilter {
+    condition: String,
+    values: String,
+}
+
+/// A transaction as written to a JSON export
+#[derive(Serialize)]
+struct ExportedTransaction<'a> {
+    id: i32,
+    datetime: String,
+    value: i32,
+    #[serde(rename = "type")]
+    transaction_type: String,
+    message: &'a str,
+}
+
+/// Writes both a CSV and a JSON export of the streamed transactions to the mantra data directory,
+/// one row at a time so the whole ledger is never held in memory.
+/// Returns the paths written to
+pub async fn export_files(
+    user: &User,
+    transactions: impl Stream<Item = Result<Transaction, StorageRunError>>,
+    filters: &[TransactionFilter],
+    timezone: UtcOffset,
+) -> Result<[PathBuf; 2], ExportError> {
+    let stamp = export_stamp(timezone);
+    let dirs = crate::dirs::app_dirs();
+    let csv_path = dirs.place_data_file(format!("exports/{user}-{stamp}.csv"))?;
+    let json_path = dirs.place_data_file(format!("exports/{user}-{stamp}.json"))?;
+
+    let mut csv_file = BufWriter::new(File::create(&csv_path)?);
+    let mut json_file = BufWriter::new(File::create(&json_path)?);
+    write_csv_header(&mut csv_file, filters)?;
+    write_json_header(&mut json_file, user, filters)?;
+
+    let mut transactions = pin!(transactions);
+    let mut first = true;
+    while let Some(trans) = transactions.next().await {
+        let trans = trans?;
+        write_csv_row(&mut csv_file, &trans, timezone)?;
+        write_json_row(&mut json_file, &trans, timezone, first)?;
+        first = false;
+    }
+
+    writeln!(json_file, "\n]}}")?;
+    csv_file.flush()?;
+    json_file.flush()?;
+
+    Ok([csv_path, json_path])
+}
+
+/// Writes the totals of each transaction type as a text bar chart to the mantra data directory,
+/// for pasting somewhere that can't show the app. Returns the path written to
+pub fn export_type_chart(
+    user: &User,
+    totals: &TransactionTypeMap<i64>,
+    amount_format: &AmountFormatter,
+    currency: &str,
+    timezone: UtcOffset,
+) -> Result<PathBuf, ExportError> {
+    let stamp = export_stamp(timezone);
+    let path =
+        crate::dirs::app_dirs().place_data_file(format!("exports/{user}-{stamp}-by-type.txt"))?;
+    let mut file = BufWriter::new(File::create(&path)?);
+    writeln!(file, "Totals by type for {user} ({currency})\n")?;
+    write!(file, "{}", type_chart(totals, amount_format))?;
+    file.flush()?;
+    Ok(path)
+}
+
+/// Draws a horizontal bar for each transaction type, scaled so the largest total fills the width.
+/// Negative totals are drawn shaded so they can't be mistaken for positive ones
+///
+/// ```
+/// use mantra_lancer::{export::type_chart, format::AmountFormatter, storage::{TransactionType, TransactionTypeMap}};
+///
+/// let mut totals = TransactionTypeMap::default();
+/// totals[TransactionType::Other] = 400;
+/// totals[TransactionType::Character] = 100;
+/// totals[TransactionType::MissionReward] = -200;
+/// let chart = type_chart(&totals, &AmountFormatter::default());
+/// let lines: Vec<_> = chart.lines().map(str::trim_end).collect();
+/// assert_eq!(lines[0], format!("Other          {}  400", "█".repeat(40)));
+/// assert_eq!(lines[1], format!("Character      {}{}  100", "█".repeat(10), " ".repeat(30)));
+/// assert_eq!(lines[2], format!("MissionReward  {}{}  -200", "▒".repeat(20), " ".repeat(20)));
+/// ```
+pub fn type_chart(totals: &TransactionTypeMap<i64>, amount_format: &AmountFormatter) -> String {
+    const BAR_WIDTH: u64 = 40;
+    // partial blocks for the last eighth of a cell
+    const EIGHTHS: [&str; 8] = ["", "▏", "▎", "▍", "▌", "▋", "▊", "▉"];
+
+    let label_width = TransactionType::VARIANTS
+        .iter()
+        .map(|name| name.len())
+        .max()
+        .unwrap_or(0);
+    let largest = totals
+        .values()
+        .map(|total| total.unsigned_abs())
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let mut chart = String::new();
+    for (transaction_type, total) in totals.kv_pairs() {
+        let eighths = (total.unsigned_abs() * BAR_WIDTH * 8 + largest / 2) / largest;
+        let bar = if *total < 0 {
+            "▒".repeat(eighths.div_ceil(8) as usize)
+        } else {
+            "█".repeat((eighths / 8) as usize) + EIGHTHS[(eighths % 8) as usize]
+        };
+        chart.push_str(&format!(
+            "{:<label_width$}  {bar:<width$}  {}\n",
+            transaction_type.to_string(),
+            amount_format.format_wide(*total),
+            width = BAR_WIDTH as usize,
+        ));
+    }
+    chart
+}
+
+/// Timestamp put in export file names so they don't overwrite each other
+fn export_stamp(timezone: UtcOffset) -> String {
+    time::OffsetDateTime::now_utc()
+        .to_offset(timezone)
+        .format(time::macros::format_description!(
+            "[year][month][day]-[hour][minute][second]"
+        ))
+        .expect("Formatting with a static description cannot fail")
+}
+
+/// Writes `#` comment lines describing the filters applied, followed by the CSV column names
+fn write_csv_header(mut writer: impl Write, filters: &[TransactionFilter]) -> io::Result<()> {
+    if filters.is_empty() {
+        writeln!(writer, "# filters: none")?;
+    }
+    for filter in filters {
+        let [condition, values] = filter.as_cells();
+        writeln!(writer, "# filter: {condition} {values}")?;
+    }
+    writeln!(writer, "id,datetime,value,type,message")
+}
+
+/// Writes a transaction as a CSV row
+fn write_csv_row(
+    mut writer: impl Write,
+    trans: &Transaction,
+    timezone: UtcOffset,
+) -> io::Result<()> {
+    writeln!(
+        writer,
+        "{},{},{},{},{}",
+        trans.trans_id,
+        format_datetime(trans, timezone),
+        trans.value,
+        trans.transaction_type,
+        csv_field(&trans.msg),
+    )
+}
+
+/// Opens the JSON object with the user and filters applied, leaving the `transactions` array open
+fn write_json_header(
+    mut writer: impl Write,
+    user: &User,
+    filters: &[TransactionFilter],
+) -> Result<(), ExportError> {
+    let filters: Vec<_> = filters
+        .iter()
+        .map(|filter| {
+            let [condition, values] = filter.as_cells();
+            ExportedFilter { condition, values }
+        })
+        .collect();
+    write!(writer, "{{\"user\":")?;
+    serde_json::to_writer(&mut writer, user.get_name())?;
+    write!(writer, ",\"filters\":")?;
+    serde_json::to_writer(&mut writer, &filters)?;
+    write!(writer, ",\"transactions\":[")?;
+    Ok(())
+}
+
+/// Writes a transaction as an element of the `transactions` array, one per line
+fn write_json_row(
+    mut writer: impl Write,
+    trans: &Transaction,
+    timezone: UtcOffset,
+    first: bool,
+) -> Result<(), ExportError> {
+    if !first {
+        write!(writer, ",")?;
+    }
+    writeln!(writer)?;
+    serde_json::to_writer(
+        writer,
+        &ExportedTransaction {
+            id: trans.trans_id,
+            datetime: format_datetime(trans, timezone),
+            value: trans.value,
+            transaction_type: trans.transaction_type.to_string(),
+            message: &trans.msg,
+        },
+    )?;
+    Ok(())
+}
+
+/// Formats the transaction's datetime in the given timezone
+fn format_datetime(trans: &Transaction, timezone: UtcOffset) -> String {
+    trans
+        .datetime
+        .assume_utc()
+        .to_offset(timezone)
+        .format(&Rfc3339)
+        .expect("Stored datetimes are always within the range RFC3339 supports")
+}
+
+/// Quotes a CSV field if it contains any characters that would break the row
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
@@ -0,0 +1,334 @@
+//! This module formats amounts and dates for display according to the user's [`Config`]
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+use time::{Date, Month, OffsetDateTime, UtcOffset, Weekday};
+
+#[cfg(doc)]
+use crate::config::Config;
+
+/// Formats amounts for display, configured through [`Config`].
+/// Stored values are whole numbers of the currency's base unit, `decimals` only changes how they're shown
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AmountFormatter {
+    /// Currency symbol to show alongside amounts, if any
+    pub symbol: Option<String>,
+    pub symbol_position: SymbolPosition,
+    /// Separator placed between each group of thousands, if any
+    pub grouping: Option<char>,
+    /// How many of the lowest digits are shown after the decimal separator
+    pub decimals: u8,
+    pub decimal_separator: char,
+    pub sign: SignStyle,
+}
+
+/// Where the currency symbol goes relative to the number
+#[derive(Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SymbolPosition {
+    #[default]
+    Prefix,
+    Suffix,
+}
+
+/// How the sign of an amount is shown
+#[derive(Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SignStyle {
+    /// Only negative amounts are marked, with a leading '-'
+    #[default]
+    Minus,
+    /// Positive amounts are also marked, with a leading '+'
+    Always,
+    /// Negative amounts are wrapped in parentheses, as in accounting
+    Parentheses,
+}
+
+impl AmountFormatter {
+    /// Formats a value, matching the plain integer display when left at the defaults
+    ///
+    /// ```
+    /// use mantra_lancer::format::{AmountFormatter, SignStyle, SymbolPosition};
+    ///
+    /// let plain = AmountFormatter::default();
+    /// assert_eq!(plain.format(-4500), "-4500");
+    ///
+    /// let fancy = AmountFormatter {
+    ///     symbol: Some(String::from("¤")),
+    ///     grouping: Some(','),
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(fancy.format(1234567), "¤1,234,567");
+    /// assert_eq!(fancy.format(-999), "-¤999");
+    ///
+    /// let ledger = AmountFormatter {
+    ///     symbol: Some(String::from("m")),
+    ///     symbol_position: SymbolPosition::Suffix,
+    ///     decimals: 2,
+    ///     sign: SignStyle::Parentheses,
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(ledger.format(-5), "(0.05m)");
+    /// assert_eq!(ledger.format(12345), "123.45m");
+    ///
+    /// let signed = AmountFormatter {
+    ///     sign: SignStyle::Always,
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(signed.format(20), "+20");
+    /// assert_eq!(signed.format(0), "0");
+    /// ```
+    pub fn format(&self, value: i32) -> String {
+        self.format_wide(value.into())
+    }
+
+    /// Formats a value too large for an `i32`, such as a sum of many transactions
+    pub fn format_wide(&self, value: i64) -> String {
+        let digits = value.unsigned_abs().to_string();
+        let decimals = usize::from(self.decimals);
+        // pad so there is always at least one digit before the decimal separator
+        let digits = format!("{digits:0>width$}", width = decimals + 1);
+        let (whole, fraction) = digits.split_at(digits.len() - decimals);
+
+        let mut number = String::with_capacity(digits.len() + digits.len() / 3 + 1);
+        for (i, c) in whole.chars().enumerate() {
+            if let Some(separator) = self.grouping {
+                if i > 0 && (whole.len() - i) % 3 == 0 {
+                    number.push(separator);
+                }
+            }
+            number.push(c);
+        }
+        if !fraction.is_empty() {
+            number.push(self.decimal_separator);
+            number.push_str(fraction);
+        }
+
+        if let Some(symbol) = &self.symbol {
+            number = match self.symbol_position {
+                SymbolPosition::Prefix => format!("{symbol}{number}"),
+                SymbolPosition::Suffix => format!("{number}{symbol}"),
+            };
+        }
+
+        match (self.sign, value.signum()) {
+            (SignStyle::Parentheses, -1) => format!("({number})"),
+            (_, -1) => format!("-{number}"),
+            (SignStyle::Always, 1) => format!("+{number}"),
+            _ => number,
+        }
+    }
+}
+
+/// Rounds a value to the nearest multiple of `step`, with halves rounded away from zero.
+/// Only meant for display, values are always stored exactly
+///
+/// ```
+/// use mantra_lancer::format::round_to_nearest;
+///
+/// assert_eq!(round_to_nearest(1249, 100), 1200);
+/// assert_eq!(round_to_nearest(1250, 100), 1300);
+/// assert_eq!(round_to_nearest(-1250, 100), -1300);
+/// assert_eq!(round_to_nearest(7, 0), 7);
+/// ```
+pub fn round_to_nearest(value: i64, step: u32) -> i64 {
+    if step == 0 {
+        return value;
+    }
+    let step = i64::from(step);
+    let remainder = value % step;
+    let rounded = value - remainder;
+    if remainder.abs() * 2 >= step {
+        rounded + step * value.signum()
+    } else {
+        rounded
+    }
+}
+
+impl Default for AmountFormatter {
+    fn default() -> Self {
+        Self {
+            symbol: None,
+            symbol_position: SymbolPosition::default(),
+            grouping: None,
+            decimals: 0,
+            decimal_separator: '.',
+            sign: SignStyle::default(),
+        }
+    }
+}
+
+/// Languages that month and weekday names can be shown in
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    En,
+    De,
+    Es,
+    Fr,
+    It,
+    Nl,
+    Pt,
+}
+
+impl Locale {
+    /// Abbreviated name of the month
+    pub fn month(self, month: Month) -> &'static str {
+        let names = match self {
+            Locale::En => [
+                "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+            ],
+            Locale::De => [
+                "Jan", "Feb", "Mär", "Apr", "Mai", "Jun", "Jul", "Aug", "Sep", "Okt", "Nov", "Dez",
+            ],
+            Locale::Es => [
+                "ene", "feb", "mar", "abr", "may", "jun", "jul", "ago", "sep", "oct", "nov", "dic",
+            ],
+            Locale::Fr => [
+                "janv", "févr", "mars", "avr", "mai", "juin", "juil", "août", "sept", "oct", "nov",
+                "déc",
+            ],
+            Locale::It => [
+                "gen", "feb", "mar", "apr", "mag", "giu", "lug", "ago", "set", "ott", "nov", "dic",
+            ],
+            Locale::Nl => [
+                "jan", "feb", "mrt", "apr", "mei", "jun", "jul", "aug", "sep", "okt", "nov", "dec",
+            ],
+            Locale::Pt => [
+                "jan", "fev", "mar", "abr", "mai", "jun", "jul", "ago", "set", "out", "nov", "dez",
+            ],
+        };
+        names[month as usize - 1]
+    }
+
+    /// Abbreviated name of the day of the week
+    pub fn weekday(self, weekday: Weekday) -> &'static str {
+        let names = match self {
+            Locale::En => ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"],
+            Locale::De => ["Mo", "Di", "Mi", "Do", "Fr", "Sa", "So"],
+            Locale::Es => ["lun", "mar", "mié", "jue", "vie", "sáb", "dom"],
+            Locale::Fr => ["lun", "mar", "mer", "jeu", "ven", "sam", "dim"],
+            Locale::It => ["lun", "mar", "mer", "gio", "ven", "sab", "dom"],
+            Locale::Nl => ["ma", "di", "wo", "do", "vr", "za", "zo"],
+            Locale::Pt => ["seg", "ter", "qua", "qui", "sex", "sáb", "dom"],
+        };
+        names[weekday.number_days_from_monday() as usize]
+    }
+}
+
+/// Formats a date, spelling out the weekday and month if a locale is given or numerically otherwise
+///
+/// ```
+/// use mantra_lancer::format::{format_date, Locale};
+/// use time::macros::date;
+///
+/// assert_eq!(format_date(date!(2024 - 10 - 14), None), "2024-10-14");
+/// assert_eq!(format_date(date!(2024 - 10 - 14), Some(Locale::En)), "Mon 14 Oct 2024");
+/// assert_eq!(format_date(date!(2024 - 10 - 14), Some(Locale::De)), "Mo 14 Okt 2024");
+/// ```
+pub fn format_date(date: Date, locale: Option<Locale>) -> String {
+    match locale {
+        Some(locale) => format!(
+            "{} {} {} {}",
+            locale.weekday(date.weekday()),
+            date.day(),
+            locale.month(date.month()),
+            date.year()
+        ),
+        None => date
+            .format(time::macros::format_description!("[year]-[month]-[day]"))
+            .expect("Formatting with a static description cannot fail"),
+    }
+}
+
+/// Formats a datetime to the minute, with the date as [`format_date`] does
+pub fn format_datetime(datetime: OffsetDateTime, locale: Option<Locale>) -> String {
+    format!(
+        "{} {:02}:{:02}",
+        format_date(datetime.date(), locale),
+        datetime.hour(),
+        datetime.minute()
+    )
+}
+
+/// Shortens text to fit in the given number of columns, ending it with an ellipsis if anything was cut off.
+/// Each character is counted as one column, which holds for the names and messages mantra shows
+///
+/// ```
+/// use mantra_lancer::format::truncate;
+///
+/// let name = "a".repeat(200);
+/// assert_eq!(truncate(&name, 6), "aaaaa…");
+/// assert_eq!(truncate("pilot", 6), "pilot");
+/// assert_eq!(truncate(&name, 0), "");
+/// ```
+pub fn truncate(text: &str, width: usize) -> Cow<'_, str> {
+    if text.chars().count() <= width {
+        return Cow::Borrowed(text);
+    }
+    match width.checked_sub(1) {
+        Some(kept) => Cow::Owned(text.chars().take(kept).chain(['…']).collect()),
+        None => Cow::Borrowed(""),
+    }
+}
+
+/// Capitalizes the first letter of each word of a name, where words are separated by spaces, '-', or '_'.
+/// Letters without a single uppercase form, such as 'ß', become however many letters their uppercase is
+///
+/// ```
+/// use mantra_lancer::format::title_case;
+///
+/// assert_eq!(title_case("kaito"), "Kaito");
+/// assert_eq!(title_case("mary-jane o_neil"), "Mary-Jane O_Neil");
+/// assert_eq!(title_case("élodie"), "Élodie");
+/// assert_eq!(title_case("7th_squad"), "7th_Squad");
+/// ```
+pub fn title_case(name: &str) -> String {
+    let mut result = String::with_capacity(name.len());
+    let mut word_start = true;
+    for c in name.chars() {
+        if word_start {
+            result.extend(c.to_uppercase());
+        } else {
+            result.push(c);
+        }
+        word_start = c.is_whitespace() || c == '-' || c == '_';
+    }
+    result
+}
+
+/// Formats a UTC offset the way [`parse_utc_offset`] reads it, such as "+02:00"
+pub fn format_utc_offset(offset: UtcOffset) -> String {
+    let (hours, minutes, _) = offset.as_hms();
+    let sign = if offset.is_negative() { '-' } else { '+' };
+    format!("{sign}{:02}:{:02}", hours.abs(), minutes.abs())
+}
+
+/// Reads a UTC offset typed as hours with an optional sign and minutes, or "UTC"
+///
+/// ```
+/// use mantra_lancer::format::parse_utc_offset;
+/// use time::UtcOffset;
+///
+/// assert_eq!(parse_utc_offset("+02:00"), Some(UtcOffset::from_hms(2, 0, 0).unwrap()));
+/// assert_eq!(parse_utc_offset("-5"), Some(UtcOffset::from_hms(-5, 0, 0).unwrap()));
+/// assert_eq!(parse_utc_offset("-03:30"), Some(UtcOffset::from_hms(-3, -30, 0).unwrap()));
+/// assert_eq!(parse_utc_offset("utc"), Some(UtcOffset::UTC));
+/// assert_eq!(parse_utc_offset("+26"), None);
+/// ```
+pub fn parse_utc_offset(input: &str) -> Option<UtcOffset> {
+    let input = input.trim();
+    if input.eq_ignore_ascii_case("utc") {
+        return Some(UtcOffset::UTC);
+    }
+    let (negative, rest) = match input.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, input.strip_prefix('+').unwrap_or(input)),
+    };
+    let (hours, minutes) = rest.split_once(':').unwrap_or((rest, "0"));
+    let (hours, minutes): (i8, i8) = (hours.parse().ok()?, minutes.parse().ok()?);
+    if hours < 0 || !(0..60).contains(&minutes) {
+        return None;
+    }
+    let sign = if negative { -1 } else { 1 };
+    UtcOffset::from_hms(sign * hours, sign * minutes, 0).ok()
+}
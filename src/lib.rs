@@ -3,34 +3,142 @@
 
 use std::{fmt::Display, ops::Deref};
 
-use crossterm::event::KeyModifiers;
-use xdg::BaseDirectories;
+use storage::TransactionType;
+use thiserror::Error;
 
 pub mod app;
+pub mod clipboard;
 pub mod config;
+pub mod dirs;
+pub mod export;
+pub mod format;
 #[macro_use]
 pub mod macros;
 pub mod storage;
+#[cfg(test)]
+mod testing;
 
-/// Grabs the XDG dirs
-fn base_dirs() -> Result<BaseDirectories, xdg::BaseDirectoriesError> {
-    BaseDirectories::with_prefix("mantra")
+/// Possible errors when parsing an amount typed by the user
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum AmountParseError {
+    #[error("Enter an amount")]
+    Empty,
+    #[error("'{0}' isn't an amount, expected a number with an optional k or m suffix")]
+    Invalid(String),
+    #[error("'{0}' isn't a whole amount")]
+    Fractional(String),
+    #[error("'{0}' is too large")]
+    TooLarge(String),
 }
 
-/// Returns an appropriatly scaled value given the held modifier keys
-pub fn value_from_modifiers(modifiers: KeyModifiers) -> i32 {
-    let mut value = 10;
-    if modifiers.contains(KeyModifiers::SHIFT) {
-        value = 1;
+/// Parses an amount with an optional sign, a single decimal point, and an optional k (thousand) or m (million) suffix.
+/// The result must come to a whole number.
+///
+/// ```
+/// use mantra_lancer::{parse_amount, AmountParseError};
+///
+/// assert_eq!(parse_amount("4500"), Ok(4500));
+/// assert_eq!(parse_amount(" -120 "), Ok(-120));
+/// assert_eq!(parse_amount("4.5k"), Ok(4500));
+/// assert_eq!(parse_amount("2m"), Ok(2_000_000));
+/// assert_eq!(parse_amount("1.25M"), Ok(1_250_000));
+/// assert_eq!(parse_amount(".5k"), Ok(500));
+///
+/// assert_eq!(parse_amount(""), Err(AmountParseError::Empty));
+/// assert!(matches!(parse_amount("4.5"), Err(AmountParseError::Fractional(_))));
+/// assert!(matches!(parse_amount("1.2345k"), Err(AmountParseError::Fractional(_))));
+/// assert!(matches!(parse_amount("1.2.3k"), Err(AmountParseError::Invalid(_))));
+/// assert!(matches!(parse_amount("5km"), Err(AmountParseError::Invalid(_))));
+/// assert!(matches!(parse_amount("k"), Err(AmountParseError::Invalid(_))));
+/// assert!(matches!(parse_amount("1,000"), Err(AmountParseError::Invalid(_))));
+/// assert!(matches!(parse_amount("3000m"), Err(AmountParseError::TooLarge(_))));
+/// ```
+pub fn parse_amount(input: &str) -> Result<i32, AmountParseError> {
+    // enough digits to fit any i32 with room to scale, without overflowing the i128 math below
+    const MAX_DIGITS: usize = 18;
+
+    let input = input.trim();
+    if input.is_empty() {
+        return Err(AmountParseError::Empty);
+    }
+    let invalid = || AmountParseError::Invalid(input.to_owned());
+
+    let (negative, unsigned) = match input.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, input.strip_prefix('+').unwrap_or(input)),
+    };
+    let (number, scale) = match unsigned.char_indices().last() {
+        Some((i, 'k' | 'K')) => (&unsigned[..i], 1_000),
+        Some((i, 'm' | 'M')) => (&unsigned[..i], 1_000_000),
+        _ => (unsigned, 1),
+    };
+    let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+    let is_digits = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+    if (whole.is_empty() && fraction.is_empty()) || !is_digits(whole) || !is_digits(fraction) {
+        return Err(invalid());
     }
-    if modifiers.contains(KeyModifiers::CONTROL) {
-        value *= 5;
+    if whole.len() > MAX_DIGITS || fraction.len() > MAX_DIGITS {
+        return Err(AmountParseError::TooLarge(input.to_owned()));
     }
-    if modifiers.contains(KeyModifiers::ALT) {
-        value *= 20;
+
+    let digits = format!("{whole}{fraction}");
+    let divisor = 10i128.pow(fraction.len() as u32);
+    let scaled = digits.parse::<i128>().map_err(|_| invalid())? * scale;
+    if scaled % divisor != 0 {
+        return Err(AmountParseError::Fractional(input.to_owned()));
     }
+    let value = if negative {
+        -(scaled / divisor)
+    } else {
+        scaled / divisor
+    };
+    i32::try_from(value).map_err(|_| AmountParseError::TooLarge(input.to_owned()))
+}
+
+/// A transaction typed on a single line, see [`parse_quick_entry`]
+#[derive(Debug, PartialEq, Eq)]
+pub struct QuickEntry {
+    pub amount: i32,
+    pub transaction_type: TransactionType,
+    pub msg: String,
+}
 
-    value
+/// Parses a line of the form `<amount> [type] [message]`, where the amount is read as [`parse_amount`] does.
+/// A word after the amount is taken as the type if it names one (see [`TransactionType::from_keyword`]),
+/// otherwise the type is [`TransactionType::default`] and the word starts the message.
+///
+/// ```
+/// use mantra_lancer::{parse_quick_entry, storage::TransactionType, AmountParseError};
+///
+/// let entry = parse_quick_entry("500 mission cleared the site").unwrap();
+/// assert_eq!(entry.amount, 500);
+/// assert_eq!(entry.transaction_type, TransactionType::MissionReward);
+/// assert_eq!(entry.msg, "cleared the site");
+///
+/// let entry = parse_quick_entry("-1.5k bought a mech").unwrap();
+/// assert_eq!(entry.amount, -1500);
+/// assert_eq!(entry.transaction_type, TransactionType::Other);
+/// assert_eq!(entry.msg, "bought a mech");
+///
+/// assert_eq!(parse_quick_entry("20 character").unwrap().msg, "");
+/// assert_eq!(parse_quick_entry("  "), Err(AmountParseError::Empty));
+/// assert!(matches!(parse_quick_entry("lots of manna"), Err(AmountParseError::Invalid(_))));
+/// ```
+pub fn parse_quick_entry(input: &str) -> Result<QuickEntry, AmountParseError> {
+    let input = input.trim();
+    let (amount, rest) = input.split_once(char::is_whitespace).unwrap_or((input, ""));
+    let amount = parse_amount(amount)?;
+    let rest = rest.trim_start();
+    let (word, after) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    let (transaction_type, msg) = match TransactionType::from_keyword(word) {
+        Some(transaction_type) => (transaction_type, after.trim_start()),
+        None => (TransactionType::default(), rest),
+    };
+    Ok(QuickEntry {
+        amount,
+        transaction_type,
+        msg: msg.to_owned(),
+    })
 }
 
 /// A String with a cursor character based position for editing
@@ -40,14 +148,44 @@ pub struct CursoredString {
     buf: String,
     index: usize,
     pub inserting: bool,
+    /// Most characters the text can hold, with anything typed past it refused. Unlimited if unset
+    pub max_len: Option<usize>,
+    /// Text and cursor before each edit, most recent last, for undoing
+    undo: Vec<(String, usize)>,
+    /// Edits that have been undone, most recently undone last, for redoing
+    redo: Vec<(String, usize)>,
 }
 
 impl CursoredString {
+    /// How many edits can be undone
+    const HISTORY_DEPTH: usize = 100;
+
     /// Creates a new empty CursoredString
     pub fn new() -> Self {
         Self::default()
     }
 
+    /// Sets the most characters the text can hold
+    pub fn with_max_len(mut self, max_len: Option<usize>) -> Self {
+        self.max_len = max_len;
+        self
+    }
+
+    /// Whether adding `count` characters at the cursor would take the text past its maximum length,
+    /// counting the characters insert mode would replace
+    fn would_overflow(&self, count: usize) -> bool {
+        let Some(max_len) = self.max_len else {
+            return false;
+        };
+        let len = self.char_len();
+        let replaced = if self.inserting {
+            count.min(len - self.index.min(len))
+        } else {
+            0
+        };
+        len - replaced + count > max_len
+    }
+
     /// Gets the text from the internal buffer
     pub fn as_str(&self) -> &str {
         self
@@ -58,20 +196,112 @@ impl CursoredString {
         self.index
     }
 
-    /// Move the cursor to the right
+    /// Number of characters in the text, which the cursor index counts in, rather than bytes
+    pub fn char_len(&self) -> usize {
+        self.buf.chars().count()
+    }
+
+    /// The part of the text that fits in `width` columns while keeping the cursor in view,
+    /// along with where the cursor is within that part
+    ///
+    /// ```
+    /// use mantra_lancer::CursoredString;
+    ///
+    /// let mut name = CursoredString::new();
+    /// for c in "a".repeat(200).chars() {
+    ///     name.insert(c);
+    /// }
+    /// let (visible, cursor) = name.visible(10);
+    /// assert_eq!(visible, "a".repeat(9));
+    /// assert_eq!(cursor, 9);
+    /// ```
+    pub fn visible(&self, width: usize) -> (String, usize) {
+        let offset = self.index.saturating_sub(width.saturating_sub(1));
+        let visible = self.buf.chars().skip(offset).take(width).collect();
+        (visible, self.index - offset)
+    }
+
+    /// Move the cursor to the right, stopping at the end of the text
+    ///
+    /// ```
+    /// use mantra_lancer::CursoredString;
+    ///
+    /// let mut text = CursoredString::new();
+    /// for c in "café€".chars() {
+    ///     text.insert(c);
+    /// }
+    /// text.home();
+    /// for _ in 0..10 {
+    ///     text.left();
+    /// }
+    /// assert_eq!(text.cursor_index(), 5);
+    /// ```
     pub fn left(&mut self) {
-        self.index = self.index.saturating_add(1).clamp(0, self.buf.len())
+        self.index = self.index.saturating_add(1).clamp(0, self.char_len())
     }
 
     /// Move the cursor to the left
     pub fn right(&mut self) {
-        self.index = self.index.saturating_sub(1).clamp(0, self.buf.len())
+        self.index = self.index.saturating_sub(1).clamp(0, self.char_len())
+    }
+
+    /// Move the cursor to the start of the text
+    pub fn home(&mut self) {
+        self.index = 0
+    }
+
+    /// Move the cursor to the end of the text
+    pub fn end(&mut self) {
+        self.index = self.char_len()
+    }
+
+    /// Remembers the text as it is before an edit so it can be undone, forgetting anything that was undone
+    fn record(&mut self) {
+        if self.undo.len() == Self::HISTORY_DEPTH {
+            self.undo.remove(0);
+        }
+        self.undo.push((self.buf.clone(), self.index));
+        self.redo.clear();
+    }
+
+    /// Puts the text back as it was before the last edit, if there's one to undo
+    ///
+    /// ```
+    /// use mantra_lancer::CursoredString;
+    ///
+    /// let mut text = CursoredString::new();
+    /// for c in "abc".chars() {
+    ///     text.insert(c);
+    /// }
+    /// text.undo();
+    /// text.undo();
+    /// assert_eq!(text.as_str(), "a");
+    /// text.redo();
+    /// assert_eq!(text.as_str(), "ab");
+    /// assert_eq!(text.cursor_index(), 2);
+    /// ```
+    pub fn undo(&mut self) {
+        if let Some((buf, index)) = self.undo.pop() {
+            let current = (std::mem::replace(&mut self.buf, buf), self.index);
+            self.redo.push(current);
+            self.index = index;
+        }
+    }
+
+    /// Makes the last undone edit again, if nothing else has been edited since
+    pub fn redo(&mut self) {
+        if let Some((buf, index)) = self.redo.pop() {
+            let current = (std::mem::replace(&mut self.buf, buf), self.index);
+            self.undo.push(current);
+            self.index = index;
+        }
     }
 
     /// Remove a character from behind the cursor
     pub fn remove_behind(&mut self) {
         // can't delete behind index 0
         if self.index > 0 {
+            self.record();
             let old_len = self.buf.len();
             let mut index = 0;
             // retain is used to modify in place
@@ -88,7 +318,15 @@ impl CursoredString {
 
     /// Removes a character ahead (same index) of the cursor
     pub fn remove_ahead(&mut self) {
-        if self.index < self.buf.chars().count() {
+        if self.index < self.char_len() {
+            self.record();
+            self.delete_ahead();
+        }
+    }
+
+    /// Removes the character ahead of the cursor without recording it, for edits that record themselves
+    fn delete_ahead(&mut self) {
+        if self.index < self.char_len() {
             let mut index = 0;
             self.buf.retain(|_| {
                 index += 1;
@@ -100,22 +338,105 @@ impl CursoredString {
         }
     }
 
-    /// Inserts a character at the current position, moving existing characters after the cursor ahead.
-    /// Replaces the current character if insert mode is enabled.
-    pub fn insert(&mut self, value: char) {
-        if self.inserting {
-            self.remove_ahead();
+    /// Removes everything from the cursor to the end of the text
+    ///
+    /// ```
+    /// use mantra_lancer::CursoredString;
+    ///
+    /// let mut text = CursoredString::from(String::from("é€ab"));
+    /// text.left();
+    /// text.left();
+    /// text.kill_to_end();
+    /// assert_eq!(text.as_str(), "é€");
+    /// ```
+    pub fn kill_to_end(&mut self) {
+        if self.index < self.char_len() {
+            self.record();
+            let byte_index = self.cursor_byte_index();
+            self.buf.truncate(byte_index);
+        }
+    }
+
+    /// Empties the text, leaving the cursor at the start
+    pub fn clear(&mut self) {
+        if !self.buf.is_empty() {
+            self.record();
+            self.buf.clear();
+            self.index = 0;
         }
-        // String.insert is indexed by byte so we get the byte index from the char index
-        let byte_index = self
-            .buf
+    }
+
+    /// Byte position of the cursor in the text, for slicing it, as String methods are indexed by byte rather than char
+    ///
+    /// ```
+    /// use mantra_lancer::CursoredString;
+    ///
+    /// let mut text = CursoredString::from(String::from("éa"));
+    /// text.home();
+    /// text.left();
+    /// assert_eq!(text.cursor_index(), 1);
+    /// assert_eq!(text.cursor_byte_index(), 2);
+    /// ```
+    pub fn cursor_byte_index(&self) -> usize {
+        self.buf
             .char_indices()
             .map(|(i, _)| i)
             .nth(self.index)
-            .unwrap_or(self.buf.len());
+            .unwrap_or(self.buf.len())
+    }
 
+    /// Inserts a character at the current position, moving existing characters after the cursor ahead.
+    /// Replaces the current character if insert mode is enabled.
+    /// Returns whether it was inserted, which it isn't if the text is already at its maximum length
+    pub fn insert(&mut self, value: char) -> bool {
+        if self.would_overflow(1) {
+            return false;
+        }
+        self.record();
+        if self.inserting {
+            self.delete_ahead();
+        }
+        let byte_index = self.cursor_byte_index();
         self.buf.insert(byte_index, value);
-        self.index += 1
+        self.index += 1;
+        true
+    }
+
+    /// Inserts text at the current position all at once, such as when pasting, leaving the cursor after it.
+    /// Replaces as many characters as are inserted if insert mode is enabled.
+    /// Returns whether it was inserted, which none of it is if it would go past the maximum length
+    ///
+    /// ```
+    /// use mantra_lancer::CursoredString;
+    ///
+    /// let mut text = CursoredString::from(String::from("XY"));
+    /// text.left();
+    /// assert!(text.insert_str("abc"));
+    /// assert_eq!(text.as_str(), "XabcY");
+    /// assert_eq!(text.cursor_index(), 4);
+    ///
+    /// let mut text = CursoredString::new().with_max_len(Some(4));
+    /// assert!(text.insert_str("abc"));
+    /// assert!(!text.insert_str("de"));
+    /// assert!(text.insert('d'));
+    /// assert!(!text.insert('e'));
+    /// assert_eq!(text.as_str(), "abcd");
+    /// ```
+    pub fn insert_str(&mut self, value: &str) -> bool {
+        let count = value.chars().count();
+        if self.would_overflow(count) {
+            return false;
+        }
+        self.record();
+        if self.inserting {
+            for _ in 0..count {
+                self.delete_ahead();
+            }
+        }
+        let byte_index = self.cursor_byte_index();
+        self.buf.insert_str(byte_index, value);
+        self.index += count;
+        true
     }
 }
 
@@ -143,8 +464,7 @@ impl From<String> for CursoredString {
     fn from(value: String) -> Self {
         Self {
             buf: value,
-            index: 0,
-            inserting: false,
+            ..Default::default()
         }
     }
 }
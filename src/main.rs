@@ -1,8 +1,17 @@
-use mantra_lancer::app::App;
+use anyhow::bail;
+use mantra_lancer::{app::App, dirs, storage::Storage};
 
 #[async_std::main]
 async fn main() -> anyhow::Result<()> {
-    let app = App::init().await?;
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let vault = take_vault(&mut args)?;
+    // the interface takes over the screen, so this is left behind in the terminal once it's closed
+    eprintln!("mantra: using {}", dirs::app_dirs());
+    if !args.is_empty() {
+        return run_command(&args, vault.as_deref()).await;
+    }
+
+    let app = App::init(vault.as_deref()).await?;
 
     let terminal = ratatui::init();
     let app_result = app.run(terminal).await;
@@ -10,3 +19,30 @@ async fn main() -> anyhow::Result<()> {
     ratatui::restore();
     Ok(app_result?)
 }
+
+/// Removes `--vault <name>` from the arguments, returning the name if it was given
+fn take_vault(args: &mut Vec<String>) -> anyhow::Result<Option<String>> {
+    let Some(index) = args.iter().position(|arg| arg == "--vault") else {
+        return Ok(None);
+    };
+    if index + 1 >= args.len() {
+        bail!("--vault needs the name of a vault");
+    }
+    let vault = args.remove(index + 1);
+    args.remove(index);
+    Ok(Some(vault))
+}
+
+/// Runs a subcommand without starting the interface
+async fn run_command(args: &[String], vault: Option<&str>) -> anyhow::Result<()> {
+    match args {
+        [command, from, into] if command == "merge-users" => {
+            let storage = Storage::load_or_create(vault).await?;
+            let moved = storage.merge_users(from, into).await?;
+            storage.close().await?;
+            println!("Merged '{from}' into '{into}', moving {moved} transactions");
+            Ok(())
+        }
+        _ => bail!("Usage: mantra [--vault <name>] [merge-users <from> <into>]"),
+    }
+}
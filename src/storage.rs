@@ -1,24 +1,51 @@
 //! This module interfaces with the local sqlite database
 use std::{fmt::Display, marker::PhantomData};
 
-use async_std::stream::StreamExt;
-use sqlx::{migrate::MigrateDatabase, QueryBuilder, Row, Sqlite, SqlitePool, Type};
+use argon2::{
+    password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
+use async_std::stream::{Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use sqlx::{
+    migrate::MigrateDatabase,
+    query::{Query, QueryScalar},
+    sqlite::{SqliteArguments, SqliteRow},
+    QueryBuilder, Row, Sqlite, SqlitePool, Type,
+};
 use strum::{Display, EnumCount, EnumIter, FromRepr, IntoEnumIterator, VariantNames};
 use thiserror::Error;
-use time::PrimitiveDateTime;
+use time::{OffsetDateTime, PrimitiveDateTime};
 
+mod backup;
 mod filter;
 pub use filter::*;
+mod legacy;
+pub use legacy::*;
+mod note;
+pub use note::*;
+mod reason;
+pub use reason::*;
+mod recurring;
+pub use recurring::*;
+mod session;
+pub use session::*;
+mod transfer;
+pub use transfer::*;
+mod vault;
 
 /// Wrapper for the sqlite database
 pub struct Storage {
     db: SqlitePool,
+    vault: Option<String>,
 }
 
 /// A valid user from the database
+#[derive(Clone)]
 pub struct User {
     id: i32,
     name: String,
+    pin_hash: Option<String>,
 }
 
 /// Transaction from the database
@@ -29,6 +56,27 @@ pub struct Transaction {
     pub value: i32,
     pub transaction_type: TransactionType,
     pub msg: String,
+    pub status: TransactionStatus,
+    /// Id of the [`ReasonCode`] given for the transaction, if any
+    pub reason_id: Option<i32>,
+    /// When the transaction was soft deleted, if it has been
+    pub deleted_at: Option<PrimitiveDateTime>,
+    /// Id of the [`Transfer`] the transaction is one side of, if any
+    pub transfer_id: Option<i32>,
+}
+
+/// One of the transactions a transaction is split into by [`Storage::split_transaction`]
+pub struct SplitPart {
+    pub value: i32,
+    pub transaction_type: TransactionType,
+    pub msg: String,
+}
+
+/// Transactions read from the database by [`Storage::get_transactions`]
+pub struct TransactionRows {
+    pub transactions: Vec<Transaction>,
+    /// How many matching rows couldn't be read, and so are missing from `transactions`
+    pub failed: usize,
 }
 
 /// Error that may occur when converting type id to the enum variant
@@ -37,7 +85,10 @@ pub struct MissingVariant<T, U>(T, PhantomData<U>);
 
 mapped_enum! {
     /// The type of a transaction, used for filtering
-    #[derive(Default, VariantNames, EnumCount, EnumIter, Clone, Copy, Display, FromRepr, Type)]
+    #[derive(
+        Default, VariantNames, EnumCount, EnumIter, Clone, Copy, PartialEq, Eq, Debug, Display,
+        FromRepr, Type, Serialize, Deserialize,
+    )]
     #[repr(i32)]
     pub enum TransactionType {
         #[default]
@@ -47,19 +98,48 @@ mapped_enum! {
     }
 
     /// Mapping of [`TransactionType`]
-    #[derive(Clone)]
+    #[derive(Clone, PartialEq, Eq)]
     pub struct TransactionTypeMap;
 }
 
+mapped_enum! {
+    /// Whether a transaction has actually happened yet, such as a promised reward that's still to be paid
+    #[derive(
+        Default, VariantNames, EnumCount, EnumIter, Clone, Copy, PartialEq, Eq, Debug, Display,
+        FromRepr, Type, Serialize, Deserialize,
+    )]
+    #[repr(i32)]
+    pub enum TransactionStatus {
+        #[default]
+        Cleared = 0,
+        Pending,
+    }
+
+    /// Mapping of [`TransactionStatus`]
+    #[derive(Clone, PartialEq, Eq)]
+    pub struct TransactionStatusMap;
+}
+
 /// Possible errors that may occur when first loading the db from the sqlite file
 #[derive(Error, Debug)]
 pub enum StorageLoadError {
-    #[error(transparent)]
-    BaseDirs(#[from] xdg::BaseDirectoriesError),
     #[error(transparent)]
     Io(#[from] std::io::Error),
     #[error(transparent)]
     DB(#[from] sqlx::Error),
+    #[error("'{0}' isn't a valid vault name, use letters, numbers, '-' and '_'")]
+    VaultName(String),
+}
+
+/// Reasons a new username may be rejected
+#[derive(Error, Debug)]
+pub enum UserNameError {
+    #[error("Username can't be empty")]
+    Empty,
+    #[error("Username can't be longer than {} characters", User::MAX_NAME_LEN)]
+    TooLong,
+    #[error("Username can't contain '{0}'")]
+    InvalidChar(char),
 }
 
 /// Possible errors that may occur when accessing the active db
@@ -69,12 +149,31 @@ pub enum StorageRunError {
     DBError(#[from] sqlx::Error),
     #[error("Expected record could not be found")]
     RecordMissing,
+    #[error(transparent)]
+    PinHash(#[from] argon2::password_hash::Error),
+    #[error("Can't merge a user into themselves")]
+    MergeIntoSelf,
+    #[error("Pilots who've transferred to each other can't be merged, their transfers would be to themselves")]
+    MergeSharedTransfers,
+    #[error("Built-in reason codes can't be changed")]
+    BuiltinReason,
+    #[error("A split needs at least two parts adding up to {expected}, these add up to {actual}")]
+    SplitMismatch { expected: i64, actual: i64 },
+    #[error("Transfers can't be split, the other pilot's side would be left without its match")]
+    SplitTransfer,
+    #[error("Can't transfer to yourself")]
+    TransferToSelf,
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error("{0} transactions couldn't be read, so they'd be missing from the backup")]
+    UnreadableRows(usize),
 }
 
 impl Storage {
-    /// Load the db from known location, or create new with table set up
-    pub async fn load_or_create() -> Result<Self, StorageLoadError> {
-        let db_path = super::base_dirs()?.place_data_file("log.db")?;
+    /// Load the db of the given vault, or the default one if None, creating it with tables set up if it doesn't exist.
+    /// Each vault is its own database, so nothing is shared between them
+    pub async fn load_or_create(vault: Option<&str>) -> Result<Self, StorageLoadError> {
+        let db_path = vault::vault_path(vault)?;
         let db_url = format!("sqlite://{}", db_path.display());
 
         if !Sqlite::database_exists(&db_url).await.unwrap_or(false) {
@@ -96,6 +195,16 @@ impl Storage {
         )
         .execute(&db)
         .await?;
+        // transactions from before statuses existed have all happened
+        add_column_if_missing(&db, "transactions", "status", "INTEGER NOT NULL DEFAULT 0").await?;
+        // reasons are optional, and point into the reason codes table
+        add_column_if_missing(&db, "transactions", "reason_id", "INTEGER").await?;
+        // deleting only sets when it happened so it can be undone, rows that are still around have it null
+        add_column_if_missing(&db, "transactions", "deleted_at", "INTEGER").await?;
+        // transactions added outside of a session have it null
+        add_column_if_missing(&db, "transactions", "session_id", "INTEGER").await?;
+        // both sides of a transfer between users share one, everything else has it null
+        add_column_if_missing(&db, "transactions", "transfer_id", "INTEGER").await?;
 
         // user table, usernames must be unique, but still better to identify by an id internally
         sqlx::query(
@@ -106,7 +215,83 @@ impl Storage {
         )
         .execute(&db)
         .await?;
-        Ok(Storage { db })
+        // PINs are optional, so users without one have a null hash
+        add_column_if_missing(&db, "users", "pin_hash", "TEXT").await?;
+
+        // recurring rules, last_processed is the datetime of the most recent occurrence inserted
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS recurring_rules (\
+                id INTEGER PRIMARY KEY NOT NULL,\
+                user_id INTEGER NOT NULL,\
+                value INTEGER NOT NULL,\
+                type INTEGER NOT NULL,\
+                message TEXT,\
+                cadence INTEGER NOT NULL,\
+                start INTEGER NOT NULL,\
+                last_processed INTEGER NOT NULL\
+            )",
+        )
+        .execute(&db)
+        .await?;
+
+        // built-in reason codes are added on every load so new ones reach existing databases
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS reason_codes (\
+                id INTEGER PRIMARY KEY NOT NULL,\
+                name TEXT UNIQUE NOT NULL\
+            )",
+        )
+        .execute(&db)
+        .await?;
+        seed_builtin_reasons(&db).await?;
+
+        // sessions of play, the one still open has ended_at null
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS sessions (\
+                id INTEGER PRIMARY KEY NOT NULL,\
+                user_id INTEGER NOT NULL,\
+                number INTEGER NOT NULL,\
+                started_at INTEGER NOT NULL,\
+                ended_at INTEGER\
+            )",
+        )
+        .execute(&db)
+        .await?;
+
+        // notes appended to transactions over time, the transaction's own message stays as the first one
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS transaction_notes (\
+                id INTEGER PRIMARY KEY NOT NULL,\
+                transaction_id INTEGER NOT NULL,\
+                datetime INTEGER NOT NULL,\
+                text TEXT NOT NULL\
+            )",
+        )
+        .execute(&db)
+        .await?;
+
+        // apps whose legacy data has been imported or declined, so it's only offered once
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS migrations (\
+                app TEXT PRIMARY KEY NOT NULL\
+            )",
+        )
+        .execute(&db)
+        .await?;
+        Ok(Storage {
+            db,
+            vault: vault.map(str::to_owned),
+        })
+    }
+
+    /// Closes the database once everything using it is done, first folding the write-ahead log back into
+    /// the database file so it's whole on its own for backups and copies, rather than leaving a `-wal` file beside it
+    pub async fn close(self) -> Result<(), StorageRunError> {
+        sqlx::query("PRAGMA wal_checkpoint(TRUNCATE)")
+            .execute(&self.db)
+            .await?;
+        self.db.close().await;
+        Ok(())
     }
 
     /// Adds a new transaction to the database using the current time
@@ -117,30 +302,89 @@ impl Storage {
         transaction_type: TransactionType,
         msg: &str,
     ) -> Result<(), StorageRunError> {
-        sqlx::query(
-            "INSERT INTO transactions (\
-                datetime, user_id,\
-                value, type,\
-                message\
-            ) VALUES (unixepoch(), $1, $2, $3, $4)",
-        )
-        .bind(user)
-        .bind(amount)
-        .bind(transaction_type as i32)
-        .bind(msg)
-        .execute(&self.db)
-        .await?;
+        self.add_transaction_at(user, amount, transaction_type, msg, now_utc())
+            .await?;
         Ok(())
     }
 
-    /// Removes all transactions that match a filter.
+    /// Adds a new transaction to the database at the given UTC datetime, returning its id.
+    /// Datetimes are stored to the second, so anything finer is dropped
+    pub async fn add_transaction_at(
+        &self,
+        user: i32,
+        amount: i32,
+        transaction_type: TransactionType,
+        msg: &str,
+        datetime: PrimitiveDateTime,
+    ) -> Result<i32, StorageRunError> {
+        let result = insert_transaction_query(user, amount, transaction_type, msg, datetime)
+            .execute(&self.db)
+            .await?;
+        Ok(result.last_insert_rowid() as i32)
+    }
+
+    /// Sets the type of every transaction with one of the given ids, returning how many were changed
+    pub async fn set_transaction_types(
+        &self,
+        ids: &[i32],
+        transaction_type: TransactionType,
+    ) -> Result<u64, StorageRunError> {
+        if ids.is_empty() {
+            return Ok(0);
+        }
+        let mut query_builder = QueryBuilder::new("UPDATE transactions SET type = ");
+        query_builder.push_bind(transaction_type as i32);
+        query_builder.push(" WHERE id IN (");
+        let mut separated = query_builder.separated(", ");
+        for id in ids {
+            separated.push_bind(*id);
+        }
+        query_builder.push(")");
+
+        Ok(query_builder
+            .build()
+            .execute(&self.db)
+            .await?
+            .rows_affected())
+    }
+
+    /// Sets the status of every transaction with one of the given ids, returning how many were changed
+    pub async fn set_status(
+        &self,
+        ids: &[i32],
+        status: TransactionStatus,
+    ) -> Result<u64, StorageRunError> {
+        if ids.is_empty() {
+            return Ok(0);
+        }
+        let mut query_builder = QueryBuilder::new("UPDATE transactions SET status = ");
+        query_builder.push_bind(status as i32);
+        query_builder.push(" WHERE id IN (");
+        let mut separated = query_builder.separated(", ");
+        for id in ids {
+            separated.push_bind(*id);
+        }
+        query_builder.push(")");
+
+        Ok(query_builder
+            .build()
+            .execute(&self.db)
+            .await?
+            .rows_affected())
+    }
+
+    /// Soft deletes all transactions that match a filter, hiding them from every other query
+    /// until they're brought back with [`Storage::restore_transactions`].
     /// Do not pass user input directly into this function.
     pub async fn remove_transactions(
         &self,
         filter: TransactionFilter,
     ) -> Result<(), StorageRunError> {
-        let mut query_builder = QueryBuilder::new("DELETE FROM transactions WHERE ");
+        let mut query_builder = QueryBuilder::new("UPDATE transactions SET deleted_at = ");
+        query_builder.push_bind(now_utc().assume_utc().unix_timestamp());
+        query_builder.push(" WHERE deleted_at IS NULL AND (");
         filter.add_to_builder(&mut query_builder);
+        query_builder.push(")");
 
         let query = query_builder.build();
 
@@ -148,39 +392,366 @@ impl Storage {
         Ok(())
     }
 
-    /// Get all transactions for a user within a date range
+    /// Brings back soft deleted transactions that match a filter, returning how many were restored.
+    /// Do not pass user input directly into this function.
+    pub async fn restore_transactions(
+        &self,
+        filter: TransactionFilter,
+    ) -> Result<u64, StorageRunError> {
+        let mut query_builder =
+            QueryBuilder::new("UPDATE transactions SET deleted_at = NULL WHERE (");
+        filter.add_to_builder(&mut query_builder);
+        query_builder.push(")");
+
+        Ok(query_builder
+            .build()
+            .execute(&self.db)
+            .await?
+            .rows_affected())
+    }
+
+    /// The user's most recent transaction that hasn't been deleted, if they have any
+    pub async fn latest_transaction(
+        &self,
+        user: i32,
+    ) -> Result<Option<Transaction>, StorageRunError> {
+        let filters = [TransactionFilter::UserId(vec![user])];
+        let mut query_builder = transactions_query(&filters);
+        query_builder.push(" LIMIT 1");
+        let row = query_builder.build().fetch_optional(&self.db).await?;
+        Ok(row.map(Transaction::from_row).transpose()?)
+    }
+
+    /// Permanently deletes soft deleted transactions that match a filter, returning how many were purged.
+    /// Transactions that haven't been soft deleted are left alone.
+    /// Do not pass user input directly into this function.
+    pub async fn purge_transactions(
+        &self,
+        filter: TransactionFilter,
+    ) -> Result<u64, StorageRunError> {
+        let mut query_builder =
+            QueryBuilder::new("DELETE FROM transactions WHERE deleted_at IS NOT NULL AND (");
+        filter.add_to_builder(&mut query_builder);
+        query_builder.push(")");
+
+        let purged = query_builder
+            .build()
+            .execute(&self.db)
+            .await?
+            .rows_affected();
+        sqlx::query(
+            "DELETE FROM transaction_notes WHERE transaction_id NOT IN (SELECT id FROM transactions)",
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(purged)
+    }
+
+    /// Get transactions matching the filters, newest first, only those in the page if one is given as an offset and limit.
+    /// Rows that can't be read are counted rather than failing the whole query.
+    /// With no filters, every live transaction of every user is returned.
+    /// Transactions at the same time are ordered newest added first, whatever order they were backdated in
     pub async fn get_transactions(
         &self,
         filters: Vec<TransactionFilter>,
-    ) -> Result<Vec<Transaction>, StorageRunError> {
-        let mut query_builder = QueryBuilder::new(
-            "SELECT id, datetime, user_id, value, type, message FROM transactions WHERE ",
-        );
+        page: Option<(usize, usize)>,
+    ) -> Result<TransactionRows, StorageRunError> {
+        let mut query_builder = transactions_query(&filters);
+        if let Some((offset, limit)) = page {
+            query_builder
+                .push(" LIMIT ")
+                .push_bind(limit as i64)
+                .push(" OFFSET ")
+                .push_bind(offset as i64);
+        }
+        let mut rows = query_builder.build().fetch(&self.db);
 
-        query_builder.push("(");
-        filters[0].add_to_builder(&mut query_builder);
-        for filter in filters {
-            query_builder.push(") AND (");
+        let mut result = TransactionRows {
+            transactions: Vec::new(),
+            failed: 0,
+        };
+        while let Some(row) = rows.next().await {
+            match row.and_then(Transaction::from_row) {
+                Ok(transaction) => result.transactions.push(transaction),
+                Err(_) => result.failed += 1,
+            }
+        }
+        Ok(result)
+    }
+
+    /// Counts the transactions matching the filters
+    pub async fn count_transactions(
+        &self,
+        filters: &[TransactionFilter],
+    ) -> Result<i64, StorageRunError> {
+        let mut query_builder = QueryBuilder::new("SELECT COUNT(*) FROM transactions WHERE ");
+        push_filters(&mut query_builder, filters);
+        Ok(query_builder
+            .build_query_scalar()
+            .fetch_one(&self.db)
+            .await?)
+    }
+
+    /// Adds up the values of the transactions matching all of the filters, being 0 if none match.
+    /// Summed as an `i64` so a long log can't overflow
+    pub async fn sum_transactions(
+        &self,
+        filters: &[TransactionFilter],
+    ) -> Result<i64, StorageRunError> {
+        let mut query_builder =
+            QueryBuilder::new("SELECT COALESCE(SUM(value), 0) FROM transactions WHERE ");
+        push_filters(&mut query_builder, filters);
+        Ok(query_builder
+            .build_query_scalar()
+            .fetch_one(&self.db)
+            .await?)
+    }
+
+    /// The user's most recently used distinct messages that start with the given text, ignoring ASCII case
+    pub async fn recent_messages(
+        &self,
+        user: i32,
+        prefix: &str,
+        limit: u32,
+    ) -> Result<Vec<String>, StorageRunError> {
+        // grouping rather than DISTINCT so each message can be ordered by its latest use
+        Ok(sqlx::query_scalar(
+            "SELECT message FROM transactions \
+            WHERE user_id = $1 AND deleted_at IS NULL AND message LIKE $2 || '%' ESCAPE '\\' \
+            GROUP BY message ORDER BY MAX(datetime) DESC LIMIT $3",
+        )
+        .bind(user)
+        .bind(escape_like(prefix))
+        .bind(limit)
+        .fetch_all(&self.db)
+        .await?)
+    }
+
+    /// Gets a single transaction by its id
+    pub async fn get_transaction(&self, trans_id: i32) -> Result<Transaction, StorageRunError> {
+        let filters = [TransactionFilter::Id(vec![trans_id])];
+        let mut query_builder = transactions_query(&filters);
+        let row = query_builder
+            .build()
+            .fetch_optional(&self.db)
+            .await?
+            .ok_or(StorageRunError::RecordMissing)?;
+        Ok(Transaction::from_row(row)?)
+    }
+
+    /// Changes the amount, type, and message of an existing transaction, failing with
+    /// [`StorageRunError::RecordMissing`] if there's none with the id
+    pub async fn update_transaction(
+        &self,
+        trans_id: i32,
+        amount: i32,
+        transaction_type: TransactionType,
+        msg: &str,
+    ) -> Result<(), StorageRunError> {
+        let result = sqlx::query(
+            "UPDATE transactions SET value = $1, type = $2, message = $3 WHERE id = $4",
+        )
+        .bind(amount)
+        .bind(transaction_type as i32)
+        .bind(msg)
+        .bind(trans_id)
+        .execute(&self.db)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Err(StorageRunError::RecordMissing);
+        }
+        Ok(())
+    }
+
+    /// Replaces a transaction with at least two parts, which must add up to its value.
+    /// Each part keeps the original's user, datetime, status, reason, and session.
+    /// Transfers are refused, as the other side would be left pointing at a transaction that's gone.
+    /// Done as a single SQL transaction so the original is never lost without its parts, returning their ids
+    pub async fn split_transaction(
+        &self,
+        trans_id: i32,
+        parts: &[SplitPart],
+    ) -> Result<Vec<i32>, StorageRunError> {
+        let mut tx = self.db.begin().await?;
+        let original = sqlx::query(
+            "SELECT user_id, datetime, value, status, reason_id, session_id, transfer_id \
+            FROM transactions WHERE id = $1 AND deleted_at IS NULL",
+        )
+        .bind(trans_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(StorageRunError::RecordMissing)?;
+        if original.get::<Option<i32>, _>("transfer_id").is_some() {
+            return Err(StorageRunError::SplitTransfer);
+        }
+
+        let expected = i64::from(original.get::<i32, _>("value"));
+        let actual = parts.iter().map(|part| i64::from(part.value)).sum();
+        if actual != expected || parts.len() < 2 {
+            return Err(StorageRunError::SplitMismatch { expected, actual });
+        }
+
+        let mut ids = Vec::with_capacity(parts.len());
+        for part in parts {
+            let result = sqlx::query(
+                "INSERT INTO transactions (\
+                    datetime, user_id,\
+                    value, type,\
+                    message, status, reason_id, session_id\
+                ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+            )
+            .bind(original.get::<i64, _>("datetime"))
+            .bind(original.get::<i32, _>("user_id"))
+            .bind(part.value)
+            .bind(part.transaction_type as i32)
+            .bind(&part.msg)
+            .bind(original.get::<i32, _>("status"))
+            .bind(original.get::<Option<i32>, _>("reason_id"))
+            .bind(original.get::<Option<i32>, _>("session_id"))
+            .execute(&mut *tx)
+            .await?;
+            ids.push(result.last_insert_rowid() as i32);
+        }
+        // the parts replace the original outright, restoring it would count its value twice
+        sqlx::query("DELETE FROM transactions WHERE id = $1")
+            .bind(trans_id)
+            .execute(&mut *tx)
+            .await?;
+        // its notes carry over to the first part rather than being left pointing at nothing
+        if let Some(first) = ids.first() {
+            sqlx::query(
+                "UPDATE transaction_notes SET transaction_id = $1 WHERE transaction_id = $2",
+            )
+            .bind(first)
+            .bind(trans_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(ids)
+    }
+
+    /// Get transactions matching the filters one at a time as they're read from the database,
+    /// rather than collecting them all into memory like [`Storage::get_transactions`].
+    /// Rows are read ahead in small batches, and reading stops if the stream is dropped
+    pub fn stream_transactions(
+        &self,
+        filters: Vec<TransactionFilter>,
+    ) -> impl Stream<Item = Result<Transaction, StorageRunError>> {
+        const READ_AHEAD: usize = 64;
+
+        let (sender, receiver) = async_std::channel::bounded(READ_AHEAD);
+        let db = self.db.clone();
+        // the query borrows its builder, so it's run in its own task that owns both
+        async_std::task::spawn(async move {
+            let mut query_builder = transactions_query(&filters);
+            let mut rows = query_builder.build().fetch(&db);
+            while let Some(row) = rows.next().await {
+                let transaction = row.and_then(Transaction::from_row).map_err(Into::into);
+                if sender.send(transaction).await.is_err() {
+                    break;
+                }
+            }
+        });
+        receiver
+    }
+
+    /// Gets the sum of a user's transactions up to and including the given UTC datetime,
+    /// leaving out pending transactions unless asked to include them
+    pub async fn balance_as_of(
+        &self,
+        user: i32,
+        at: PrimitiveDateTime,
+        include_pending: bool,
+    ) -> Result<i32, StorageRunError> {
+        Ok(sqlx::query_scalar(
+            "SELECT COALESCE(SUM(value), 0) FROM transactions \
+            WHERE user_id = $1 AND deleted_at IS NULL AND datetime <= $2 AND ($3 OR status != $4)",
+        )
+        .bind(user)
+        .bind(at.assume_utc().unix_timestamp())
+        .bind(include_pending)
+        .bind(TransactionStatus::Pending as i32)
+        .fetch_one(&self.db)
+        .await?)
+    }
+
+    /// The net change to a user's balance from transactions within the given range
+    pub async fn net_for_range(&self, user: i32, range: DateRange) -> Result<i32, StorageRunError> {
+        let mut query_builder =
+            QueryBuilder::new("SELECT COALESCE(SUM(value), 0) FROM transactions WHERE user_id = ");
+        query_builder.push_bind(user);
+        query_builder.push(" AND deleted_at IS NULL");
+        if let Some(filter) = TransactionFilter::DateRange(range).get_useful() {
+            query_builder.push(" AND (");
             filter.add_to_builder(&mut query_builder);
+            query_builder.push(")");
         }
-        query_builder.push(")");
+        Ok(query_builder
+            .build_query_scalar()
+            .fetch_one(&self.db)
+            .await?)
+    }
 
-        let query = query_builder.build();
+    /// Counts how many transactions a user has of each type, skipping any type id this version doesn't know
+    pub async fn count_by_type(
+        &self,
+        user: i32,
+    ) -> Result<TransactionTypeMap<i64>, StorageRunError> {
+        let mut counts = TransactionTypeMap::default();
+        let rows: Vec<(i32, i64)> = sqlx::query_as(
+            "SELECT type, COUNT(*) FROM transactions \
+            WHERE user_id = $1 AND deleted_at IS NULL GROUP BY type",
+        )
+        .bind(user)
+        .fetch_all(&self.db)
+        .await?;
+        for (type_id, count) in rows {
+            if let Ok(transaction_type) = TransactionType::try_from(type_id) {
+                counts[transaction_type] = count;
+            }
+        }
+        Ok(counts)
+    }
 
-        Ok(query
-            .fetch(&self.db)
-            .filter_map(|row| {
-                row.ok().map(|row| Transaction {
-                    trans_id: row.get("id"),
-                    datetime: row.get("datetime"),
-                    user_id: row.get("user_id"),
-                    value: row.get("value"),
-                    transaction_type: row.get("type"),
-                    msg: row.get("message"),
-                })
-            })
-            .collect()
-            .await)
+    /// Sums all of a user's transactions of each type, pending ones included.
+    /// Types with no transactions are left at 0, and rows with a type id this version doesn't know are skipped
+    pub async fn sum_by_type(
+        &self,
+        user_id: i32,
+    ) -> Result<TransactionTypeMap<i64>, StorageRunError> {
+        self.totals_by_type(user_id, true).await
+    }
+
+    /// Like [`Storage::sum_by_type`], but leaving out pending transactions unless asked for, as the balance does
+    pub async fn totals_by_type(
+        &self,
+        user: i32,
+        include_pending: bool,
+    ) -> Result<TransactionTypeMap<i64>, StorageRunError> {
+        let mut totals = TransactionTypeMap::default();
+        let rows: Vec<(i32, i64)> = sqlx::query_as(
+            "SELECT type, SUM(value) FROM transactions \
+            WHERE user_id = $1 AND deleted_at IS NULL AND ($2 OR status != $3) GROUP BY type",
+        )
+        .bind(user)
+        .bind(include_pending)
+        .bind(TransactionStatus::Pending as i32)
+        .fetch_all(&self.db)
+        .await?;
+        for (type_id, total) in rows {
+            if let Ok(transaction_type) = TransactionType::try_from(type_id) {
+                totals[transaction_type] = total;
+            }
+        }
+        Ok(totals)
+    }
+
+    /// Whether any users have been created yet
+    pub async fn has_users(&self) -> Result<bool, StorageRunError> {
+        Ok(sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM users)")
+            .fetch_one(&self.db)
+            .await?)
     }
 
     /// Creates a new user, doing nothing if one already exists with the same name
@@ -196,7 +767,7 @@ impl Storage {
 
     /// Gets a user if they exist, otherwise errors
     pub async fn get_user(&self, username: &str) -> Result<User, StorageRunError> {
-        let query_statement = "SELECT id, name FROM users WHERE name=$1";
+        let query_statement = "SELECT id, name, pin_hash FROM users WHERE name=$1";
         let query = sqlx::query(query_statement).bind(username);
 
         let user_record = query
@@ -207,11 +778,291 @@ impl Storage {
         Ok(User {
             id: user_record.get("id"),
             name: user_record.get("name"),
+            pin_hash: user_record.get("pin_hash"),
+        })
+    }
+
+    /// Every user, ordered by name ignoring case
+    pub async fn list_users(&self) -> Result<Vec<User>, StorageRunError> {
+        let rows = sqlx::query("SELECT id, name, pin_hash FROM users ORDER BY name COLLATE NOCASE")
+            .fetch_all(&self.db)
+            .await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| User {
+                id: row.get("id"),
+                name: row.get("name"),
+                pin_hash: row.get("pin_hash"),
+            })
+            .collect())
+    }
+
+    /// Moves every transaction, recurring rule, and session from one user to another, then deletes the emptied user.
+    /// Sessions are numbered on from the other user's, and one left open is ended.
+    /// Users who share transfers are refused, see [`Storage::share_transfers`].
+    /// Done as a single SQL transaction so a failure leaves both users untouched.
+    /// Returns the number of transactions moved
+    pub async fn merge_users(&self, from: &str, into: &str) -> Result<u64, StorageRunError> {
+        let user_id_query = "SELECT id FROM users WHERE name = $1";
+        let mut tx = self.db.begin().await?;
+        let from_id: i32 = sqlx::query_scalar(user_id_query)
+            .bind(from)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(StorageRunError::RecordMissing)?;
+        let into_id: i32 = sqlx::query_scalar(user_id_query)
+            .bind(into)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(StorageRunError::RecordMissing)?;
+        if from_id == into_id {
+            return Err(StorageRunError::MergeIntoSelf);
+        }
+        if shared_transfers_query(from_id, into_id)
+            .fetch_one(&mut *tx)
+            .await?
+        {
+            return Err(StorageRunError::MergeSharedTransfers);
+        }
+
+        let moved = sqlx::query("UPDATE transactions SET user_id = $1 WHERE user_id = $2")
+            .bind(into_id)
+            .bind(from_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        sqlx::query("UPDATE recurring_rules SET user_id = $1 WHERE user_id = $2")
+            .bind(into_id)
+            .bind(from_id)
+            .execute(&mut *tx)
+            .await?;
+        // only the latest open session is ever used, so one the merged user left open would be stuck open
+        sqlx::query("UPDATE sessions SET ended_at = $1 WHERE user_id = $2 AND ended_at IS NULL")
+            .bind(now_utc().assume_utc().unix_timestamp())
+            .bind(from_id)
+            .execute(&mut *tx)
+            .await?;
+        // the moved sessions keep their order, numbered after the ones the user already has
+        let last_number: i32 =
+            sqlx::query_scalar("SELECT COALESCE(MAX(number), 0) FROM sessions WHERE user_id = $1")
+                .bind(into_id)
+                .fetch_one(&mut *tx)
+                .await?;
+        sqlx::query("UPDATE sessions SET user_id = $1, number = number + $2 WHERE user_id = $3")
+            .bind(into_id)
+            .bind(last_number)
+            .bind(from_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(from_id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(moved)
+    }
+
+    /// Sets the PIN for a user, stored as an argon2 hash. Passing `None` removes the PIN
+    pub async fn set_pin(&self, user: i32, pin: Option<&str>) -> Result<(), StorageRunError> {
+        let pin_hash = match pin {
+            Some(pin) => Some(
+                Argon2::default()
+                    .hash_password(pin.as_bytes(), &SaltString::generate(&mut OsRng))?
+                    .to_string(),
+            ),
+            None => None,
+        };
+        sqlx::query("UPDATE users SET pin_hash = $1 WHERE id = $2")
+            .bind(pin_hash)
+            .bind(user)
+            .execute(&self.db)
+            .await?;
+        Ok(())
+    }
+}
+
+/// Adds a column to a table created by an older version if it doesn't have it yet
+async fn add_column_if_missing(
+    db: &SqlitePool,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> Result<(), sqlx::Error> {
+    let exists: bool =
+        sqlx::query_scalar("SELECT COUNT(*) > 0 FROM pragma_table_info($1) WHERE name = $2")
+            .bind(table)
+            .bind(column)
+            .fetch_one(db)
+            .await?;
+    if !exists {
+        sqlx::query(&format!(
+            "ALTER TABLE {table} ADD COLUMN {column} {definition}"
+        ))
+        .execute(db)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Builds the insert for a transaction at an explicit datetime so it can be run inside sql transactions
+fn insert_transaction_query(
+    user: i32,
+    amount: i32,
+    transaction_type: TransactionType,
+    msg: &str,
+    datetime: PrimitiveDateTime,
+) -> Query<'_, Sqlite, SqliteArguments<'_>> {
+    sqlx::query(
+        "INSERT INTO transactions (\
+            datetime, user_id,\
+            value, type,\
+            message\
+        ) VALUES ($1, $2, $3, $4, $5)",
+    )
+    .bind(datetime.assume_utc().unix_timestamp())
+    .bind(user)
+    .bind(amount)
+    .bind(transaction_type as i32)
+    .bind(msg)
+}
+
+/// Builds the query for whether two users have any transfers between them, deleted ones included,
+/// so it can be run inside sql transactions
+fn shared_transfers_query(
+    user: i32,
+    other: i32,
+) -> QueryScalar<'static, Sqlite, bool, SqliteArguments<'static>> {
+    sqlx::query_scalar(
+        "SELECT EXISTS (\
+            SELECT 1 FROM transactions side JOIN transactions other_side \
+            ON side.transfer_id = other_side.transfer_id AND side.id != other_side.id \
+            WHERE side.user_id = $1 AND other_side.user_id = $2\
+        )",
+    )
+    .bind(user)
+    .bind(other)
+}
+
+/// Builds the query selecting every transaction that matches all of the filters, newest first,
+/// or most recently deleted first when selecting deleted transactions
+fn transactions_query(filters: &[TransactionFilter]) -> QueryBuilder<'_, Sqlite> {
+    let mut query_builder = QueryBuilder::new(
+        "SELECT id, datetime, user_id, value, type, message, status, reason_id, deleted_at, \
+        transfer_id FROM transactions WHERE ",
+    );
+    push_filters(&mut query_builder, filters);
+    // deleted transactions are listed most recently deleted first, like a recycle bin
+    if filters
+        .iter()
+        .any(|filter| matches!(filter, TransactionFilter::Deleted))
+    {
+        query_builder.push(" ORDER BY deleted_at DESC,");
+    } else if filters
+        .iter()
+        .any(|filter| matches!(filter, TransactionFilter::TransferBetween { .. }))
+    {
+        // both sides of a transfer are listed together, sending side first
+        query_builder.push(" ORDER BY transfer_id DESC, value ASC,");
+    } else {
+        query_builder.push(" ORDER BY");
+    }
+    // newest first, with the id settling ties so the order never changes between runs
+    query_builder.push(" datetime DESC, id DESC");
+    query_builder
+}
+
+/// Pushes a condition requiring all of the filters to match,
+/// leaving out soft deleted transactions unless [`TransactionFilter::Deleted`] is one of them.
+/// Filters that don't filter anything are skipped, as they'd leave an empty "()" in the query
+fn push_filters<'a>(
+    query_builder: &mut QueryBuilder<'a, Sqlite>,
+    filters: &'a [TransactionFilter],
+) {
+    // soft deleted transactions are only wanted when asked for
+    let shows_deleted = filters
+        .iter()
+        .any(|filter| matches!(filter, TransactionFilter::Deleted));
+    if !shows_deleted {
+        query_builder.push("deleted_at IS NULL");
+    }
+    let mut needs_and = !shows_deleted;
+    for filter in filters.iter().filter(|filter| filter.is_useful()) {
+        if needs_and {
+            query_builder.push(" AND ");
+        }
+        needs_and = true;
+        query_builder.push("(");
+        filter.add_to_builder(query_builder);
+        query_builder.push(")");
+    }
+}
+
+/// Escapes LIKE wildcards so the text only matches literally, for patterns using `ESCAPE '\'`.
+/// Used for any filter or lookup matching user input with LIKE
+///
+/// ```
+/// use mantra_lancer::storage::escape_like;
+///
+/// assert_eq!(escape_like("50%"), r"50\%");
+/// assert_eq!(escape_like("one_off"), r"one\_off");
+/// assert_eq!(escape_like(r"a\b"), r"a\\b");
+/// assert_eq!(escape_like("stipend"), "stipend");
+/// ```
+pub fn escape_like(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// The current time as a UTC [`PrimitiveDateTime`], matching how datetimes are stored
+pub fn now_utc() -> PrimitiveDateTime {
+    let now = OffsetDateTime::now_utc();
+    PrimitiveDateTime::new(now.date(), now.time())
+}
+
+impl Transaction {
+    /// Reads a transaction from a row selected by [`transactions_query`]
+    fn from_row(row: SqliteRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            trans_id: row.try_get("id")?,
+            datetime: row.try_get("datetime")?,
+            user_id: row.try_get("user_id")?,
+            value: row.try_get("value")?,
+            transaction_type: row.try_get("type")?,
+            msg: row.try_get("message")?,
+            status: row.try_get("status")?,
+            reason_id: row.try_get("reason_id")?,
+            deleted_at: row.try_get("deleted_at")?,
+            transfer_id: row.try_get("transfer_id")?,
         })
     }
 }
 
 impl User {
+    pub const MAX_NAME_LEN: usize = 32;
+
+    /// Checks that a name is suitable for a new user, allowing letters, numbers, '-' and '_'
+    pub fn validate_name(name: &str) -> Result<(), UserNameError> {
+        if name.is_empty() {
+            return Err(UserNameError::Empty);
+        }
+        if name.chars().count() > Self::MAX_NAME_LEN {
+            return Err(UserNameError::TooLong);
+        }
+        match name
+            .chars()
+            .find(|c| !(c.is_alphanumeric() || *c == '-' || *c == '_'))
+        {
+            Some(c) => Err(UserNameError::InvalidChar(c)),
+            None => Ok(()),
+        }
+    }
+
     /// Returns the table id of the user
     pub fn get_id(&self) -> i32 {
         self.id
@@ -221,6 +1072,21 @@ impl User {
     pub fn get_name(&self) -> &str {
         &self.name
     }
+
+    /// Whether the user must enter a PIN to log in
+    pub fn has_pin(&self) -> bool {
+        self.pin_hash.is_some()
+    }
+
+    /// Checks an entered PIN against the stored hash, always passing if the user has no PIN
+    pub fn verify_pin(&self, pin: &str) -> bool {
+        match &self.pin_hash {
+            Some(pin_hash) => PasswordHash::new(pin_hash)
+                .and_then(|hash| Argon2::default().verify_password(pin.as_bytes(), &hash))
+                .is_ok(),
+            None => true,
+        }
+    }
 }
 
 impl TransactionType {
@@ -235,6 +1101,19 @@ impl TransactionType {
         Self::from_repr((self as i32 - 1).rem_euclid(<Self as EnumCount>::COUNT as i32))
             .expect("TransactionType is non-zero count so will always succeed")
     }
+
+    /// Finds the type a word names, ignoring case.
+    /// Any unambiguous start of a name at least three letters long also counts, such as "mission"
+    pub fn from_keyword(word: &str) -> Option<Self> {
+        if word.chars().count() < 3 {
+            return None;
+        }
+        let word = word.to_lowercase();
+        let mut matches =
+            Self::iter().filter(|variant| variant.to_string().to_lowercase().starts_with(&word));
+        let found = matches.next()?;
+        matches.next().is_none().then_some(found)
+    }
 }
 
 impl<T> TransactionTypeMap<T> {
@@ -247,6 +1126,26 @@ impl<T> TransactionTypeMap<T> {
     }
 }
 
+impl TransactionStatus {
+    /// The other status, for toggling between them
+    pub fn toggled(self) -> Self {
+        match self {
+            TransactionStatus::Cleared => TransactionStatus::Pending,
+            TransactionStatus::Pending => TransactionStatus::Cleared,
+        }
+    }
+}
+
+impl<T> TransactionStatusMap<T> {
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        TransactionStatus::iter().map(|v| &self[v])
+    }
+
+    pub fn kv_pairs(&self) -> impl Iterator<Item = (TransactionStatus, &T)> {
+        TransactionStatus::iter().map(|v| (v, &self[v]))
+    }
+}
+
 impl TryFrom<i32> for TransactionType {
     type Error = MissingVariant<i32, Self>;
 
@@ -280,3 +1179,241 @@ where
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use time::macros::datetime;
+
+    use super::*;
+    use crate::testing::temp_storage;
+
+    #[async_std::test]
+    async fn add_transaction_at_keeps_the_datetime() -> Result<(), StorageRunError> {
+        let (storage, pilot) = temp_storage("add-transaction-at").await;
+        let at = datetime!(2024-03-01 18:30:15);
+        let id = storage
+            .add_transaction_at(pilot, 120, TransactionType::Character, "Backdated", at)
+            .await?;
+        assert_eq!(storage.get_transaction(id).await?.datetime, at);
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn get_transactions_without_filters_returns_every_user() -> Result<(), StorageRunError> {
+        let (storage, pilot) = temp_storage("get-transactions").await;
+        storage.create_user("other").await?;
+        let other = storage.get_user("other").await?.get_id();
+        for (user, value) in [(pilot, 100), (pilot, -20), (other, 5)] {
+            storage
+                .add_transaction_at(user, value, TransactionType::Other, "", now_utc())
+                .await?;
+        }
+
+        let rows = storage.get_transactions(vec![], None).await?;
+        assert_eq!(rows.transactions.len(), 3);
+        assert_eq!(rows.failed, 0);
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn get_transactions_orders_equal_datetimes_newest_added_first(
+    ) -> Result<(), StorageRunError> {
+        let (storage, pilot) = temp_storage("get-transactions-order").await;
+        for (msg, datetime) in [
+            ("later", datetime!(2024-05-02 12:00)),
+            ("earlier", datetime!(2024-05-01 12:00)),
+            ("later again", datetime!(2024-05-02 12:00)),
+        ] {
+            storage
+                .add_transaction_at(pilot, 10, TransactionType::Other, msg, datetime)
+                .await?;
+        }
+
+        let rows = storage
+            .get_transactions(vec![TransactionFilter::UserId(vec![pilot])], None)
+            .await?;
+        let order: Vec<_> = rows
+            .transactions
+            .iter()
+            .map(|trans| trans.msg.as_str())
+            .collect();
+        assert_eq!(order, ["later again", "later", "earlier"]);
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn sum_transactions_doesnt_overflow() -> Result<(), StorageRunError> {
+        let (storage, pilot) = temp_storage("sum-transactions").await;
+        let filters = [TransactionFilter::UserId(vec![pilot])];
+        assert_eq!(storage.sum_transactions(&filters).await?, 0);
+
+        for value in [i32::MAX, i32::MAX, -300] {
+            storage
+                .add_transaction_at(pilot, value, TransactionType::Other, "", now_utc())
+                .await?;
+        }
+        assert_eq!(
+            storage.sum_transactions(&filters).await?,
+            2 * i64::from(i32::MAX) - 300
+        );
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn update_transaction_changes_it_or_reports_it_missing() -> Result<(), StorageRunError> {
+        let (storage, pilot) = temp_storage("update-transaction").await;
+        let id = storage
+            .add_transaction_at(pilot, 100, TransactionType::Other, "before", now_utc())
+            .await?;
+
+        storage
+            .update_transaction(id, -250, TransactionType::MissionReward, "after")
+            .await?;
+        let updated = storage.get_transaction(id).await?;
+        assert_eq!(updated.value, -250);
+        assert_eq!(updated.transaction_type, TransactionType::MissionReward);
+        assert_eq!(updated.msg, "after");
+
+        let missing = storage
+            .update_transaction(id + 1, 0, TransactionType::Other, "")
+            .await;
+        assert!(matches!(missing, Err(StorageRunError::RecordMissing)));
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn sum_by_type_includes_pending() -> Result<(), StorageRunError> {
+        let (storage, pilot) = temp_storage("sum-by-type").await;
+        for (value, transaction_type) in [
+            (500, TransactionType::MissionReward),
+            (250, TransactionType::MissionReward),
+            (-100, TransactionType::Other),
+        ] {
+            storage
+                .add_transaction_at(pilot, value, transaction_type, "", now_utc())
+                .await?;
+        }
+        let pending = storage
+            .add_transaction_at(pilot, 40, TransactionType::Other, "", now_utc())
+            .await?;
+        storage
+            .set_status(&[pending], TransactionStatus::Pending)
+            .await?;
+
+        let totals = storage.sum_by_type(pilot).await?;
+        assert_eq!(totals[TransactionType::MissionReward], 750);
+        assert_eq!(totals[TransactionType::Other], -60);
+        assert_eq!(totals[TransactionType::Character], 0);
+        let cleared = storage.totals_by_type(pilot, false).await?;
+        assert_eq!(cleared[TransactionType::Other], -100);
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn split_keeps_the_session_and_refuses_transfers() -> Result<(), StorageRunError> {
+        let (storage, pilot) = temp_storage("split-transaction").await;
+        let part = |value| SplitPart {
+            value,
+            transaction_type: TransactionType::Other,
+            msg: String::new(),
+        };
+        let id = storage
+            .add_transaction_at(pilot, 100, TransactionType::Other, "", now_utc())
+            .await?;
+        let session = storage.start_session(pilot).await?;
+        storage.set_session(&[id], session.id).await?;
+
+        // one part would only delete the original, and none would lose it entirely
+        for parts in [vec![part(100)], vec![]] {
+            let split = storage.split_transaction(id, &parts).await;
+            assert!(matches!(split, Err(StorageRunError::SplitMismatch { .. })));
+        }
+        let ids = storage.split_transaction(id, &[part(60), part(40)]).await?;
+        for id in ids {
+            let session_id: Option<i32> =
+                sqlx::query_scalar("SELECT session_id FROM transactions WHERE id = $1")
+                    .bind(id)
+                    .fetch_one(&storage.db)
+                    .await?;
+            assert_eq!(session_id, Some(session.id));
+        }
+
+        storage.create_user("other").await?;
+        let other = storage.get_user("other").await?.get_id();
+        let sent = storage.add_transfer(pilot, other, 50, "").await?;
+        let split = storage
+            .split_transaction(sent, &[part(-25), part(-25)])
+            .await;
+        assert!(matches!(split, Err(StorageRunError::SplitTransfer)));
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn filters_that_filter_nothing_are_skipped() -> Result<(), StorageRunError> {
+        let (storage, pilot) = temp_storage("unbounded-filters").await;
+        storage
+            .add_transaction_at(pilot, 10, TransactionType::Other, "", now_utc())
+            .await?;
+        let filters = vec![
+            TransactionFilter::UserId(vec![pilot]),
+            TransactionFilter::DateRange((..).into()),
+            TransactionFilter::ValueRange {
+                min: None,
+                max: None,
+            },
+        ];
+        assert_eq!(storage.count_transactions(&filters).await?, 1);
+        assert_eq!(
+            storage
+                .get_transactions(filters, None)
+                .await?
+                .transactions
+                .len(),
+            1
+        );
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn merge_users_moves_sessions_and_refuses_shared_transfers() -> Result<(), StorageRunError>
+    {
+        let (storage, pilot) = temp_storage("merge-users").await;
+        for name in ["alt", "friend"] {
+            storage.create_user(name).await?;
+        }
+        let alt = storage.get_user("alt").await?.get_id();
+        let friend = storage.get_user("friend").await?.get_id();
+        let first = storage.start_session(pilot).await?;
+        storage.end_session(first.id).await?;
+        let left_open = storage.start_session(alt).await?;
+
+        storage.add_transfer(friend, pilot, 20, "").await?;
+        assert!(storage.share_transfers(pilot, friend).await?);
+        let merge = storage.merge_users("friend", "pilot").await;
+        assert!(matches!(merge, Err(StorageRunError::MergeSharedTransfers)));
+
+        storage.merge_users("alt", "pilot").await?;
+        // the moved session follows on from the pilot's own, and isn't left open
+        let sessions: Vec<(i32, i32, bool)> = sqlx::query_as(
+            "SELECT id, number, ended_at IS NOT NULL FROM sessions WHERE user_id = $1 ORDER BY id",
+        )
+        .bind(pilot)
+        .fetch_all(&storage.db)
+        .await?;
+        assert_eq!(sessions, [(first.id, 1, true), (left_open.id, 2, true)]);
+        assert_eq!(storage.active_session(pilot).await?, None);
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn list_users_orders_by_name_ignoring_case() -> Result<(), StorageRunError> {
+        let (storage, _) = temp_storage("list-users").await;
+        for name in ["bravo", "Charlie", "alpha"] {
+            storage.create_user(name).await?;
+        }
+        let users = storage.list_users().await?;
+        let names: Vec<_> = users.iter().map(|user| user.get_name()).collect();
+        assert_eq!(names, ["alpha", "bravo", "Charlie", "pilot"]);
+        Ok(())
+    }
+}
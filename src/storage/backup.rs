@@ -0,0 +1,142 @@
+use serde::{Deserialize, Serialize};
+
+use super::{Storage, StorageRunError, TransactionFilter, TransactionStatus, TransactionType};
+
+/// A transaction as kept in a JSON backup, without its id so importing never clashes with existing ones
+#[derive(Serialize, Deserialize)]
+struct BackupTransaction {
+    /// Seconds since the Unix epoch in UTC, as it's stored
+    datetime: i64,
+    value: i32,
+    #[serde(rename = "type")]
+    transaction_type: TransactionType,
+    message: String,
+    #[serde(default)]
+    status: TransactionStatus,
+}
+
+impl Storage {
+    /// Writes every live transaction of the user to a JSON array, oldest first, for restoring with [`Storage::import_json`].
+    /// Reasons, notes, and transfers are left out, as their ids only mean something in this vault.
+    /// Fails with [`StorageRunError::UnreadableRows`] if any of them can't be read, rather than leaving them out of a backup
+    /// that would then be mistaken for a full one
+    pub async fn export_json(&self, user_id: i32) -> Result<String, StorageRunError> {
+        let rows = self
+            .get_transactions(vec![TransactionFilter::UserId(vec![user_id])], None)
+            .await?;
+        if rows.failed > 0 {
+            return Err(StorageRunError::UnreadableRows(rows.failed));
+        }
+        let backup: Vec<_> = rows
+            .transactions
+            .into_iter()
+            .rev()
+            .map(|trans| BackupTransaction {
+                datetime: trans.datetime.assume_utc().unix_timestamp(),
+                value: trans.value,
+                transaction_type: trans.transaction_type,
+                message: trans.msg,
+                status: trans.status,
+            })
+            .collect();
+        Ok(serde_json::to_string_pretty(&backup)?)
+    }
+
+    /// Adds the transactions of a JSON backup from [`Storage::export_json`] to the user, each getting a new id.
+    /// Done as a single SQL transaction so a bad backup adds nothing, returning how many were added
+    pub async fn import_json(&self, user_id: i32, json: &str) -> Result<usize, StorageRunError> {
+        let backup: Vec<BackupTransaction> = serde_json::from_str(json)?;
+        let mut tx = self.db.begin().await?;
+        for trans in &backup {
+            sqlx::query(
+                "INSERT INTO transactions (datetime, user_id, value, type, message, status) \
+                VALUES ($1, $2, $3, $4, $5, $6)",
+            )
+            .bind(trans.datetime)
+            .bind(user_id)
+            .bind(trans.value)
+            .bind(trans.transaction_type as i32)
+            .bind(&trans.message)
+            .bind(trans.status as i32)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(backup.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{storage::now_utc, testing::temp_storage};
+
+    #[async_std::test]
+    async fn json_backup_round_trips() -> Result<(), StorageRunError> {
+        let (storage, pilot) = temp_storage("json-backup").await;
+        storage.create_user("copy").await?;
+        let copy = storage.get_user("copy").await?.get_id();
+        storage
+            .add_transaction_at(
+                pilot,
+                300,
+                TransactionType::MissionReward,
+                "Reward",
+                now_utc(),
+            )
+            .await?;
+        storage
+            .add_transaction_at(pilot, -40, TransactionType::Other, "Repairs", now_utc())
+            .await?;
+
+        let backup = storage.export_json(pilot).await?;
+        assert_eq!(storage.import_json(copy, &backup).await?, 2);
+
+        let mut read = Vec::new();
+        for user in [pilot, copy] {
+            let rows = storage
+                .get_transactions(vec![TransactionFilter::UserId(vec![user])], None)
+                .await?;
+            read.push(
+                rows.transactions
+                    .into_iter()
+                    .map(|trans| {
+                        (
+                            trans.datetime,
+                            trans.value,
+                            trans.transaction_type,
+                            trans.msg,
+                        )
+                    })
+                    .collect::<Vec<_>>(),
+            );
+        }
+        assert_eq!(read[0], read[1]);
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn json_backup_refuses_unreadable_rows() -> Result<(), StorageRunError> {
+        let (storage, pilot) = temp_storage("json-backup-unreadable").await;
+        storage
+            .add_transaction_at(
+                pilot,
+                300,
+                TransactionType::MissionReward,
+                "Reward",
+                now_utc(),
+            )
+            .await?;
+        storage
+            .add_transaction_at(pilot, -40, TransactionType::Other, "Repairs", now_utc())
+            .await?;
+        // a type id this version doesn't know, as a newer one might have written
+        sqlx::query("UPDATE transactions SET type = 99 WHERE value = -40")
+            .execute(&storage.db)
+            .await?;
+
+        let backup = storage.export_json(pilot).await;
+        assert!(matches!(backup, Err(StorageRunError::UnreadableRows(1))));
+        Ok(())
+    }
+}
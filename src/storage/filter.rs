@@ -2,30 +2,89 @@ use core::{
     fmt::{self, Formatter},
     ops::{Bound, RangeBounds},
 };
+use std::borrow::Cow;
 
 use itertools::Itertools;
 use sqlx::{QueryBuilder, Sqlite};
 
-use super::TransactionTypeMap;
+use super::{escape_like, TransactionStatusMap, TransactionTypeMap};
 
 /// Types of Filters usable for queries
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq)]
 pub enum TransactionFilter {
     UserId(Vec<i32>),
     Type(TransactionTypeMap<bool>),
+    Status(TransactionStatusMap<bool>),
     DateRange(DateRange),
     Id(Vec<i32>),
+    /// Transactions given one of these reason codes
+    Reason(Vec<i32>),
+    /// Soft deleted transactions, which are otherwise left out of every query
+    Deleted,
+    /// Transactions whose message contains this text anywhere. LIKE wildcards in it only match themselves
+    MessageContains(String),
+    /// Transactions whose message is exactly this text, such as ones added by a script
+    MessageEquals(String),
+    /// Both sides of the transfers between these two users, in either direction.
+    /// Their names are kept alongside so the filter can be shown without looking them up
+    TransferBetween {
+        users: [i32; 2],
+        names: [String; 2],
+    },
+    /// Transactions whose value is within these bounds, both inclusive, with an unset bound left open
+    ValueRange {
+        min: Option<i32>,
+        max: Option<i32>,
+    },
     Not(Box<TransactionFilter>),
 }
 
 /// Allows storing a range because RangeBound is not dyn compatible
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq)]
 pub struct DateRange {
     start: Bound<time::PrimitiveDateTime>,
     end: Bound<time::PrimitiveDateTime>,
 }
 
 impl TransactionFilter {
+    /// Pushes this filter's condition onto the builder, binding its values.
+    /// A date range only compares against the bounds it has, so a fully unbounded one contributes no clause.
+    /// Queries skip filters like that altogether, see [`TransactionFilter::get_useful`]
+    ///
+    /// ```
+    /// use mantra_lancer::storage::TransactionFilter;
+    /// use sqlx::{QueryBuilder, Sqlite};
+    /// use time::macros::datetime;
+    ///
+    /// let sql = |filter: TransactionFilter| {
+    ///     let mut builder = QueryBuilder::<Sqlite>::new("");
+    ///     filter.add_to_builder(&mut builder);
+    ///     builder.sql().to_owned()
+    /// };
+    /// let (start, end) = (datetime!(2024-01-01 0:00), datetime!(2024-02-01 0:00));
+    ///
+    /// let both = TransactionFilter::DateRange((start..end).into());
+    /// assert_eq!(sql(both), "datetime >= ? AND datetime < ?");
+    /// let start_only = TransactionFilter::DateRange((start..).into());
+    /// assert_eq!(sql(start_only), "datetime >= ?");
+    /// let end_only = TransactionFilter::DateRange((..=end).into());
+    /// assert_eq!(sql(end_only), "datetime <= ?");
+    /// let neither = TransactionFilter::DateRange((..).into());
+    /// assert_eq!(sql(neither), "");
+    ///
+    /// // the bound text has its wildcards escaped, so "50%" only matches a literal "50%"
+    /// let contains = TransactionFilter::MessageContains(String::from("50%"));
+    /// assert_eq!(sql(contains), r"message LIKE '%' || ? || '%' ESCAPE '\'");
+    ///
+    /// let both = TransactionFilter::ValueRange { min: Some(100), max: Some(500) };
+    /// assert_eq!(sql(both), "value >= ? AND value <= ?");
+    /// let min_only = TransactionFilter::ValueRange { min: Some(100), max: None };
+    /// assert_eq!(sql(min_only), "value >= ?");
+    /// // with neither bound it's no clause at all, and queries leave it out
+    /// let neither = TransactionFilter::ValueRange { min: None, max: None };
+    /// assert_eq!(sql(neither.clone()), "");
+    /// assert!(neither.get_useful().is_none());
+    /// ```
     pub fn add_to_builder(&self, builder: &mut QueryBuilder<'_, Sqlite>) {
         match self {
             TransactionFilter::UserId(ids) => {
@@ -45,27 +104,42 @@ impl TransactionFilter {
                     }
                 }
             }
+            TransactionFilter::Status(statuses) => {
+                let mut iter = statuses.kv_pairs().filter(|(_, selected)| **selected);
+                if let Some((status, _)) = iter.next() {
+                    builder.push("status = ").push_bind(status);
+                    for (status, _) in iter {
+                        builder.push(" OR status = ").push_bind(status);
+                    }
+                }
+            }
             TransactionFilter::DateRange(date_range) => {
                 let mut separated = builder.separated(" AND ");
                 match date_range.start {
                     Bound::Included(start) => {
-                        separated.push("datetime >= ").push_bind_unseparated(start);
+                        separated
+                            .push("datetime >= ")
+                            .push_bind_unseparated(start.assume_utc().unix_timestamp());
                     }
                     Bound::Excluded(start) => {
-                        separated.push("datetime > ").push_bind_unseparated(start);
+                        separated
+                            .push("datetime > ")
+                            .push_bind_unseparated(start.assume_utc().unix_timestamp());
                     }
                     Bound::Unbounded => {}
                 }
                 match date_range.end {
                     Bound::Included(end) => {
-                        separated.push("datetime <= ").push_bind_unseparated(end);
+                        separated
+                            .push("datetime <= ")
+                            .push_bind_unseparated(end.assume_utc().unix_timestamp());
                     }
                     Bound::Excluded(end) => {
-                        separated.push("datetime < ").push_bind_unseparated(end);
-                    }
-                    Bound::Unbounded => {
-                        separated.push("1=1");
+                        separated
+                            .push("datetime < ")
+                            .push_bind_unseparated(end.assume_utc().unix_timestamp());
                     }
+                    Bound::Unbounded => {}
                 }
             }
             TransactionFilter::Not(filter) => {
@@ -79,9 +153,153 @@ impl TransactionFilter {
                     builder.push(" OR id = ").push_bind(*id);
                 }
             }
+            TransactionFilter::Deleted => {
+                builder.push("deleted_at IS NOT NULL");
+            }
+            TransactionFilter::Reason(ids) => {
+                builder.push("reason_id = ").push_bind(ids[0]);
+                for id in &ids[1..] {
+                    builder.push(" OR reason_id = ").push_bind(*id);
+                }
+            }
+            TransactionFilter::MessageContains(text) => {
+                builder
+                    .push("message LIKE '%' || ")
+                    .push_bind(escape_like(text))
+                    .push(" || '%' ESCAPE '\\'");
+            }
+            TransactionFilter::MessageEquals(text) => {
+                builder.push("message = ").push_bind(text.clone());
+            }
+            TransactionFilter::ValueRange { min, max } => {
+                let mut separated = builder.separated(" AND ");
+                if let Some(min) = min {
+                    separated.push("value >= ").push_bind_unseparated(*min);
+                }
+                if let Some(max) = max {
+                    separated.push("value <= ").push_bind_unseparated(*max);
+                }
+            }
+            TransactionFilter::TransferBetween {
+                users: [user, other],
+                ..
+            } => {
+                builder
+                    .push("transfer_id IN (SELECT transfer_id FROM transactions WHERE user_id = ")
+                    .push_bind(*user)
+                    .push(") AND transfer_id IN (SELECT transfer_id FROM transactions WHERE user_id = ")
+                    .push_bind(*other)
+                    .push(")");
+            }
         };
     }
 
+    /// Describes the filter as a condition and the values it applies to, for displaying to the user
+    ///
+    /// ```
+    /// use mantra_lancer::storage::TransactionFilter;
+    ///
+    /// let contains = TransactionFilter::MessageContains(String::from("stipend"));
+    /// assert_eq!(contains.as_cells(), ["message must contain", "\"stipend\""]);
+    /// let not_contains = TransactionFilter::Not(Box::new(contains));
+    /// assert_eq!(not_contains.as_cells(), ["message must not contain", "\"stipend\""]);
+    ///
+    /// let equals = TransactionFilter::MessageEquals(String::from("stipend"));
+    /// assert_eq!(equals.as_cells(), ["message must be", "\"stipend\""]);
+    /// let not_equals = TransactionFilter::Not(Box::new(equals));
+    /// assert_eq!(not_equals.as_cells(), ["message must not be", "\"stipend\""]);
+    ///
+    /// // pilots are named, not left as their ids
+    /// let transfers = TransactionFilter::TransferBetween {
+    ///     users: [1, 2],
+    ///     names: [String::from("alice"), String::from("bob")],
+    /// };
+    /// assert_eq!(transfers.as_cells(), ["transaction must be", "a transfer between alice and bob"]);
+    /// ```
+    pub fn as_cells(&self) -> [String; 2] {
+        match self {
+            TransactionFilter::UserId(ids) => [
+                String::from("user id must be"),
+                Itertools::intersperse(
+                    ids.iter().map(|v| Cow::from(v.to_string())),
+                    Cow::from(" or "),
+                )
+                .collect(),
+            ],
+            TransactionFilter::Type(transaction_types) => [
+                String::from("transaction type must be"),
+                Itertools::intersperse(
+                    transaction_types
+                        .kv_pairs()
+                        .filter(|&(_, selected)| *selected)
+                        .map(|(t_type, _)| Cow::from(t_type.to_string())),
+                    Cow::from(" or "),
+                )
+                .collect(),
+            ],
+            TransactionFilter::Status(statuses) => [
+                String::from("status must be"),
+                Itertools::intersperse(
+                    statuses
+                        .kv_pairs()
+                        .filter(|&(_, selected)| *selected)
+                        .map(|(status, _)| Cow::from(status.to_string())),
+                    Cow::from(" or "),
+                )
+                .collect(),
+            ],
+            TransactionFilter::DateRange(date_range) => {
+                [String::from("date must be within"), date_range.to_string()]
+            }
+            TransactionFilter::Id(ids) => [
+                String::from("transaction id must be"),
+                Itertools::intersperse(
+                    ids.iter().map(|v| Cow::from(v.to_string())),
+                    Cow::from(" or "),
+                )
+                .collect(),
+            ],
+            TransactionFilter::Reason(ids) => [
+                String::from("reason code must be"),
+                Itertools::intersperse(
+                    ids.iter().map(|v| Cow::from(v.to_string())),
+                    Cow::from(" or "),
+                )
+                .collect(),
+            ],
+            TransactionFilter::Deleted => {
+                [String::from("transaction must be"), String::from("deleted")]
+            }
+            TransactionFilter::MessageContains(text) => {
+                [String::from("message must contain"), format!("\"{text}\"")]
+            }
+            TransactionFilter::MessageEquals(text) => {
+                [String::from("message must be"), format!("\"{text}\"")]
+            }
+            TransactionFilter::TransferBetween {
+                names: [user, other],
+                ..
+            } => [
+                String::from("transaction must be"),
+                format!("a transfer between {user} and {other}"),
+            ],
+            TransactionFilter::ValueRange { min, max } => [
+                String::from("value must be"),
+                match (min, max) {
+                    (Some(min), Some(max)) => format!("from {min} to {max}"),
+                    (Some(min), None) => format!("at least {min}"),
+                    (None, Some(max)) => format!("at most {max}"),
+                    (None, None) => String::from("anything"),
+                },
+            ],
+            TransactionFilter::Not(filter) => {
+                let mut cells = filter.as_cells();
+                cells[0] = cells[0].replace("must ", "must not ");
+                cells
+            }
+        }
+    }
+
     pub fn get_useful(self) -> Option<TransactionFilter> {
         if self.is_useful() {
             Some(self)
@@ -90,22 +308,47 @@ impl TransactionFilter {
         }
     }
 
-    fn is_useful(&self) -> bool {
+    pub(super) fn is_useful(&self) -> bool {
         match self {
             TransactionFilter::UserId(ids) => !ids.is_empty(),
             TransactionFilter::Type(transaction_type_map) => {
                 transaction_type_map.values().contains(&true)
             }
+            TransactionFilter::Status(statuses) => statuses.values().contains(&true),
             TransactionFilter::DateRange(date_range) => {
                 !(matches!(date_range.start, Bound::Unbounded)
                     && matches!(date_range.end, Bound::Unbounded))
             }
             TransactionFilter::Id(ids) => !ids.is_empty(),
+            TransactionFilter::Reason(ids) => !ids.is_empty(),
+            TransactionFilter::Deleted | TransactionFilter::TransferBetween { .. } => true,
+            TransactionFilter::ValueRange { min, max } => min.is_some() || max.is_some(),
+            TransactionFilter::MessageContains(text) | TransactionFilter::MessageEquals(text) => {
+                !text.is_empty()
+            }
             TransactionFilter::Not(transaction_filter) => transaction_filter.is_useful(),
         }
     }
 }
 
+impl DateRange {
+    /// The whole of the given day in a timezone, as a range of UTC datetimes
+    pub fn day(date: time::Date, timezone: time::UtcOffset) -> Self {
+        let to_utc = |date: time::Date| {
+            let utc = date
+                .midnight()
+                .assume_offset(timezone)
+                .to_offset(time::UtcOffset::UTC);
+            time::PrimitiveDateTime::new(utc.date(), utc.time())
+        };
+        let start = to_utc(date);
+        match date.next_day() {
+            Some(next) => (start..to_utc(next)).into(),
+            None => (start..).into(),
+        }
+    }
+}
+
 impl<T> From<T> for DateRange
 where
     T: RangeBounds<time::PrimitiveDateTime>,
@@ -133,3 +376,44 @@ impl std::fmt::Display for DateRange {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        storage::{now_utc, StorageRunError, TransactionType},
+        testing::temp_storage,
+    };
+
+    #[async_std::test]
+    async fn message_contains_matches_wildcards_literally() -> Result<(), StorageRunError> {
+        let (storage, pilot) = temp_storage("message-contains").await;
+        for msg in [
+            "50% off",
+            "500 off",
+            "a_b",
+            "axb",
+            r"C:\hangar",
+            r"C:hangar",
+        ] {
+            storage
+                .add_transaction_at(pilot, 1, TransactionType::Other, msg, now_utc())
+                .await?;
+        }
+
+        for (text, expected) in [("50%", "50% off"), ("a_b", "a_b"), (r"C:\", r"C:\hangar")] {
+            let filters = vec![
+                TransactionFilter::UserId(vec![pilot]),
+                TransactionFilter::MessageContains(text.to_owned()),
+            ];
+            let rows = storage.get_transactions(filters, None).await?;
+            let msgs: Vec<_> = rows
+                .transactions
+                .iter()
+                .map(|trans| trans.msg.as_str())
+                .collect();
+            assert_eq!(msgs, [expected]);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,93 @@
+use std::path::PathBuf;
+
+use sqlx::Connection;
+use xdg::BaseDirectories;
+
+use super::{Storage, StorageRunError};
+
+/// The database of an app mantra replaces, found in that app's XDG data directory
+pub struct LegacyData {
+    /// Name of the app, which is also its XDG prefix
+    pub app: String,
+    pub path: PathBuf,
+}
+
+impl LegacyData {
+    /// Looks for the `log.db` of each named app, skipping any that aren't installed
+    pub fn find(apps: &[String]) -> Vec<Self> {
+        apps.iter()
+            .filter_map(|app| {
+                let path = BaseDirectories::with_prefix(app)
+                    .ok()?
+                    .find_data_file("log.db")?;
+                Some(Self {
+                    app: app.clone(),
+                    path,
+                })
+            })
+            .collect()
+    }
+}
+
+impl Storage {
+    /// Whether the given app's data has already been imported or turned down
+    pub async fn is_migrated(&self, app: &str) -> Result<bool, StorageRunError> {
+        Ok(
+            sqlx::query_scalar("SELECT COUNT(*) > 0 FROM migrations WHERE app = $1")
+                .bind(app)
+                .fetch_one(&self.db)
+                .await?,
+        )
+    }
+
+    /// Records the given app as migrated so it isn't offered again, without importing anything
+    pub async fn mark_migrated(&self, app: &str) -> Result<(), StorageRunError> {
+        sqlx::query("INSERT OR IGNORE INTO migrations (app) VALUES ($1)")
+            .bind(app)
+            .execute(&self.db)
+            .await?;
+        Ok(())
+    }
+
+    /// Copies the users and transactions of a legacy database into this one and marks it migrated.
+    /// Users are matched by name, so a pilot that exists in both ends up with the transactions of both.
+    /// Returns the number of transactions imported
+    pub async fn import_legacy(&self, legacy: &LegacyData) -> Result<u64, StorageRunError> {
+        // attached databases only exist on the connection that attached them, and can't be attached mid-transaction
+        let mut conn = self.db.acquire().await?;
+        sqlx::query("ATTACH DATABASE $1 AS legacy")
+            .bind(legacy.path.to_string_lossy())
+            .execute(&mut *conn)
+            .await?;
+
+        let result = async {
+            let mut tx = conn.begin().await?;
+            sqlx::query("INSERT OR IGNORE INTO users (name) SELECT name FROM legacy.users")
+                .execute(&mut *tx)
+                .await?;
+            let imported = sqlx::query(
+                "INSERT INTO transactions (datetime, user_id, value, type, message) \
+                SELECT old.datetime, users.id, old.value, old.type, old.message \
+                FROM legacy.transactions old \
+                JOIN legacy.users old_users ON old_users.id = old.user_id \
+                JOIN users ON users.name = old_users.name \
+                ORDER BY old.id",
+            )
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+            sqlx::query("INSERT OR IGNORE INTO migrations (app) VALUES ($1)")
+                .bind(&legacy.app)
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
+            Ok::<_, sqlx::Error>(imported)
+        }
+        .await;
+
+        sqlx::query("DETACH DATABASE legacy")
+            .execute(&mut *conn)
+            .await?;
+        Ok(result?)
+    }
+}
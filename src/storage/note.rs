@@ -0,0 +1,59 @@
+use sqlx::Row;
+use time::PrimitiveDateTime;
+
+use super::{now_utc, Storage, StorageRunError};
+
+/// A note appended to a transaction after it was added, kept alongside its original message
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct TransactionNote {
+    pub id: i32,
+    pub datetime: PrimitiveDateTime,
+    pub text: String,
+}
+
+impl Storage {
+    /// The notes appended to a transaction, oldest first
+    pub async fn transaction_notes(
+        &self,
+        trans_id: i32,
+    ) -> Result<Vec<TransactionNote>, StorageRunError> {
+        let rows = sqlx::query(
+            "SELECT id, datetime, text FROM transaction_notes \
+            WHERE transaction_id = $1 ORDER BY datetime, id",
+        )
+        .bind(trans_id)
+        .fetch_all(&self.db)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| TransactionNote {
+                id: row.get("id"),
+                datetime: row.get("datetime"),
+                text: row.get("text"),
+            })
+            .collect())
+    }
+
+    /// Appends a note to the transaction, timestamped now
+    pub async fn add_note(
+        &self,
+        trans_id: i32,
+        text: &str,
+    ) -> Result<TransactionNote, StorageRunError> {
+        let datetime = now_utc();
+        let row = sqlx::query(
+            "INSERT INTO transaction_notes (transaction_id, datetime, text) \
+            VALUES ($1, $2, $3) RETURNING id",
+        )
+        .bind(trans_id)
+        .bind(datetime.assume_utc().unix_timestamp())
+        .bind(text)
+        .fetch_one(&self.db)
+        .await?;
+        Ok(TransactionNote {
+            id: row.get("id"),
+            datetime,
+            text: text.to_owned(),
+        })
+    }
+}
@@ -0,0 +1,176 @@
+use sqlx::{QueryBuilder, Row, SqlitePool};
+use strum::{Display, EnumCount, EnumIter, FromRepr, IntoEnumIterator, VariantNames};
+
+use super::{push_filters, Storage, StorageRunError, TransactionFilter};
+
+mapped_enum! {
+    /// Reason codes every database starts with, which can't be renamed or removed
+    #[derive(
+        VariantNames, EnumCount, EnumIter, Clone, Copy, PartialEq, Eq, Debug, Display, FromRepr,
+    )]
+    #[repr(i32)]
+    pub enum BuiltinReason {
+        Mission = 1,
+        Downtime,
+        Purchase,
+        Sale,
+    }
+
+    /// Mapping of [`BuiltinReason`]
+    #[derive(Clone)]
+    pub struct BuiltinReasonMap;
+}
+
+/// A structured reason a transaction happened, beyond its free text message
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ReasonCode {
+    pub id: i32,
+    pub name: String,
+}
+
+/// How many of a user's transactions have a reason, and what they add up to
+pub struct ReasonTotal {
+    /// None for transactions without a reason
+    pub reason: Option<ReasonCode>,
+    pub count: i64,
+    pub total: i64,
+}
+
+impl ReasonCode {
+    /// Ids below this are kept for [`BuiltinReason`]s, so new built-ins never clash with user-defined codes
+    pub const CUSTOM_START: i32 = 100;
+
+    /// Whether this is one of the [`BuiltinReason`]s
+    pub fn is_builtin(&self) -> bool {
+        self.id < Self::CUSTOM_START
+    }
+}
+
+/// Adds any [`BuiltinReason`] missing from the reason codes table
+pub(super) async fn seed_builtin_reasons(db: &SqlitePool) -> Result<(), sqlx::Error> {
+    for reason in BuiltinReason::iter() {
+        sqlx::query("INSERT OR IGNORE INTO reason_codes (id, name) VALUES ($1, $2)")
+            .bind(reason as i32)
+            .bind(reason.to_string())
+            .execute(db)
+            .await?;
+    }
+    Ok(())
+}
+
+impl Storage {
+    /// Gets every reason code, built-ins first
+    pub async fn reason_codes(&self) -> Result<Vec<ReasonCode>, StorageRunError> {
+        let rows = sqlx::query("SELECT id, name FROM reason_codes ORDER BY id")
+            .fetch_all(&self.db)
+            .await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| ReasonCode {
+                id: row.get("id"),
+                name: row.get("name"),
+            })
+            .collect())
+    }
+
+    /// Adds a user-defined reason code, returning its id. Names must be unique
+    pub async fn add_reason_code(&self, name: &str) -> Result<i32, StorageRunError> {
+        Ok(sqlx::query_scalar(
+            "INSERT INTO reason_codes (id, name) \
+            VALUES (MAX(COALESCE((SELECT MAX(id) FROM reason_codes), 0) + 1, $1), $2) \
+            RETURNING id",
+        )
+        .bind(ReasonCode::CUSTOM_START)
+        .bind(name)
+        .fetch_one(&self.db)
+        .await?)
+    }
+
+    /// Removes a user-defined reason code, leaving the transactions that had it without a reason
+    pub async fn remove_reason_code(&self, id: i32) -> Result<(), StorageRunError> {
+        if id < ReasonCode::CUSTOM_START {
+            return Err(StorageRunError::BuiltinReason);
+        }
+        let mut tx = self.db.begin().await?;
+        sqlx::query("UPDATE transactions SET reason_id = NULL WHERE reason_id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        let result = sqlx::query("DELETE FROM reason_codes WHERE id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(StorageRunError::RecordMissing);
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Sets the reason of every transaction with one of the given ids, returning how many were changed
+    pub async fn set_reason(
+        &self,
+        ids: &[i32],
+        reason_id: Option<i32>,
+    ) -> Result<u64, StorageRunError> {
+        if ids.is_empty() {
+            return Ok(0);
+        }
+        let mut query_builder = QueryBuilder::new("UPDATE transactions SET reason_id = ");
+        query_builder.push_bind(reason_id);
+        query_builder.push(" WHERE id IN (");
+        let mut separated = query_builder.separated(", ");
+        for id in ids {
+            separated.push_bind(*id);
+        }
+        query_builder.push(")");
+
+        Ok(query_builder
+            .build()
+            .execute(&self.db)
+            .await?
+            .rows_affected())
+    }
+
+    /// Sets the reason of every transaction matching the filters in one go, returning how many were changed
+    pub async fn set_reason_matching(
+        &self,
+        filters: &[TransactionFilter],
+        reason_id: Option<i32>,
+    ) -> Result<u64, StorageRunError> {
+        let mut query_builder = QueryBuilder::new("UPDATE transactions SET reason_id = ");
+        query_builder.push_bind(reason_id);
+        query_builder.push(" WHERE ");
+        push_filters(&mut query_builder, filters);
+
+        Ok(query_builder
+            .build()
+            .execute(&self.db)
+            .await?
+            .rows_affected())
+    }
+
+    /// Groups a user's transactions by reason, with those without one first
+    pub async fn totals_by_reason(&self, user: i32) -> Result<Vec<ReasonTotal>, StorageRunError> {
+        let rows = sqlx::query(
+            "SELECT reason_codes.id, reason_codes.name, COUNT(*) AS count, SUM(value) AS total \
+            FROM transactions LEFT JOIN reason_codes ON reason_codes.id = transactions.reason_id \
+            WHERE user_id = $1 AND deleted_at IS NULL \
+            GROUP BY reason_codes.id ORDER BY reason_codes.id",
+        )
+        .bind(user)
+        .fetch_all(&self.db)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| ReasonTotal {
+                reason: row.get::<Option<i32>, _>("id").map(|id| ReasonCode {
+                    id,
+                    name: row.get("name"),
+                }),
+                count: row.get("count"),
+                total: row.get("total"),
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,179 @@
+use sqlx::{Row, Type};
+use strum::{Display, EnumCount, FromRepr, VariantNames};
+use time::{util::days_in_month, PrimitiveDateTime};
+
+use super::{insert_transaction_query, Storage, StorageRunError, TransactionType};
+
+/// A rule for a transaction that repeats on a fixed cadence
+pub struct RecurringRule {
+    pub rule_id: i32,
+    pub user_id: i32,
+    pub value: i32,
+    pub transaction_type: TransactionType,
+    pub msg: String,
+    pub cadence: Cadence,
+    pub start: PrimitiveDateTime,
+    pub last_processed: PrimitiveDateTime,
+}
+
+/// A missed occurrence of a [`RecurringRule`] that has yet to be inserted
+pub struct PendingRecurring {
+    pub rule_id: i32,
+    pub user_id: i32,
+    pub value: i32,
+    pub transaction_type: TransactionType,
+    pub msg: String,
+    pub datetime: PrimitiveDateTime,
+}
+
+/// How often a [`RecurringRule`] repeats
+#[derive(Clone, Copy, PartialEq, Eq, Display, VariantNames, EnumCount, FromRepr, Type)]
+#[repr(i32)]
+pub enum Cadence {
+    Weekly = 0,
+    Monthly,
+}
+
+impl Cadence {
+    /// Returns the datetime of the nth occurrence after `start`
+    pub fn nth_after(self, start: PrimitiveDateTime, n: u32) -> PrimitiveDateTime {
+        match self {
+            Cadence::Weekly => start + time::Duration::weeks(n.into()),
+            Cadence::Monthly => {
+                // months are counted from the start so short months don't drift the day
+                let months = start.month() as i32 - 1 + n as i32;
+                let year = start.year() + months.div_euclid(12);
+                let month = time::Month::try_from(months.rem_euclid(12) as u8 + 1)
+                    .expect("Month is always within 1-12");
+                let day = start.day().min(days_in_month(month, year));
+                start
+                    .replace_day(1)
+                    .and_then(|date| date.replace_year(year))
+                    .and_then(|date| date.replace_month(month))
+                    .and_then(|date| date.replace_day(day))
+                    .expect("Day is clamped to the length of the month")
+            }
+        }
+    }
+}
+
+impl Storage {
+    /// Adds a new recurring rule, treating `start` as the first occurrence that has already been processed
+    pub async fn add_recurring_rule(
+        &self,
+        user: i32,
+        amount: i32,
+        transaction_type: TransactionType,
+        msg: &str,
+        cadence: Cadence,
+        start: PrimitiveDateTime,
+    ) -> Result<(), StorageRunError> {
+        let start = start.assume_utc().unix_timestamp();
+        sqlx::query(
+            "INSERT INTO recurring_rules (\
+                user_id, value,\
+                type, message,\
+                cadence, start,\
+                last_processed\
+            ) VALUES ($1, $2, $3, $4, $5, $6, $6)",
+        )
+        .bind(user)
+        .bind(amount)
+        .bind(transaction_type as i32)
+        .bind(msg)
+        .bind(cadence as i32)
+        .bind(start)
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    /// Gets all recurring rules belonging to a user
+    pub async fn get_recurring_rules(
+        &self,
+        user: i32,
+    ) -> Result<Vec<RecurringRule>, StorageRunError> {
+        let rows = sqlx::query(
+            "SELECT id, user_id, value, type, message, cadence, start, last_processed \
+            FROM recurring_rules WHERE user_id = $1",
+        )
+        .bind(user)
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| RecurringRule {
+                rule_id: row.get("id"),
+                user_id: row.get("user_id"),
+                value: row.get("value"),
+                transaction_type: row.get("type"),
+                msg: row.get("message"),
+                cadence: row.get("cadence"),
+                start: row.get("start"),
+                last_processed: row.get("last_processed"),
+            })
+            .collect())
+    }
+
+    /// Finds every occurrence of a user's recurring rules that fell between the last time it was processed and `now`
+    pub async fn pending_recurring(
+        &self,
+        user: i32,
+        now: PrimitiveDateTime,
+    ) -> Result<Vec<PendingRecurring>, StorageRunError> {
+        let mut pending = vec![];
+        for rule in self.get_recurring_rules(user).await? {
+            let occurrences = (1..)
+                .map(|n| rule.cadence.nth_after(rule.start, n))
+                .skip_while(|datetime| *datetime <= rule.last_processed)
+                .take_while(|datetime| *datetime <= now);
+            pending.extend(occurrences.map(|datetime| PendingRecurring {
+                rule_id: rule.rule_id,
+                user_id: rule.user_id,
+                value: rule.value,
+                transaction_type: rule.transaction_type,
+                msg: rule.msg.clone(),
+                datetime,
+            }));
+        }
+        pending.sort_by_key(|entry| entry.datetime);
+        Ok(pending)
+    }
+
+    /// Inserts the pending entries with their backdated datetimes and marks their rules as processed.
+    /// All entries are committed together, and any entry whose rule has already been processed past it is skipped
+    /// so that a second running instance can't apply the same entries twice. Returns how many were inserted.
+    pub async fn apply_recurring(
+        &self,
+        pending: &[PendingRecurring],
+    ) -> Result<usize, StorageRunError> {
+        let mut tx = self.db.begin().await?;
+        let mut applied = 0;
+        for entry in pending {
+            let claimed = sqlx::query(
+                "UPDATE recurring_rules SET last_processed = $1 WHERE id = $2 AND last_processed < $1",
+            )
+            .bind(entry.datetime.assume_utc().unix_timestamp())
+            .bind(entry.rule_id)
+            .execute(&mut *tx)
+            .await?;
+            if claimed.rows_affected() == 0 {
+                continue;
+            }
+
+            insert_transaction_query(
+                entry.user_id,
+                entry.value,
+                entry.transaction_type,
+                &entry.msg,
+                entry.datetime,
+            )
+            .execute(&mut *tx)
+            .await?;
+            applied += 1;
+        }
+        tx.commit().await?;
+        Ok(applied)
+    }
+}
@@ -0,0 +1,81 @@
+use sqlx::{QueryBuilder, Row};
+use time::PrimitiveDateTime;
+
+use super::{now_utc, Storage, StorageRunError};
+
+/// A stretch of play started and ended by the user, tagging the transactions added during it
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Session {
+    pub id: i32,
+    /// Counts up from 1 for each user, for showing instead of the id
+    pub number: i32,
+    pub started_at: PrimitiveDateTime,
+}
+
+impl Storage {
+    /// The user's session that has been started but not ended, if there is one
+    pub async fn active_session(&self, user: i32) -> Result<Option<Session>, StorageRunError> {
+        let row = sqlx::query(
+            "SELECT id, number, started_at FROM sessions \
+            WHERE user_id = $1 AND ended_at IS NULL ORDER BY id DESC LIMIT 1",
+        )
+        .bind(user)
+        .fetch_optional(&self.db)
+        .await?;
+        Ok(row.map(|row| Session {
+            id: row.get("id"),
+            number: row.get("number"),
+            started_at: row.get("started_at"),
+        }))
+    }
+
+    /// Starts a new session for the user now, numbered after their previous ones
+    pub async fn start_session(&self, user: i32) -> Result<Session, StorageRunError> {
+        let started_at = now_utc();
+        let row = sqlx::query(
+            "INSERT INTO sessions (user_id, number, started_at) \
+            VALUES ($1, COALESCE((SELECT MAX(number) FROM sessions WHERE user_id = $1), 0) + 1, $2) \
+            RETURNING id, number",
+        )
+        .bind(user)
+        .bind(started_at.assume_utc().unix_timestamp())
+        .fetch_one(&self.db)
+        .await?;
+        Ok(Session {
+            id: row.get("id"),
+            number: row.get("number"),
+            started_at,
+        })
+    }
+
+    /// Ends the session now, so nothing else is tagged with it
+    pub async fn end_session(&self, session_id: i32) -> Result<(), StorageRunError> {
+        sqlx::query("UPDATE sessions SET ended_at = $1 WHERE id = $2 AND ended_at IS NULL")
+            .bind(now_utc().assume_utc().unix_timestamp())
+            .bind(session_id)
+            .execute(&self.db)
+            .await?;
+        Ok(())
+    }
+
+    /// Tags every transaction with one of the given ids as part of the session, returning how many were changed
+    pub async fn set_session(&self, ids: &[i32], session_id: i32) -> Result<u64, StorageRunError> {
+        if ids.is_empty() {
+            return Ok(0);
+        }
+        let mut query_builder = QueryBuilder::new("UPDATE transactions SET session_id = ");
+        query_builder.push_bind(session_id);
+        query_builder.push(" WHERE id IN (");
+        let mut separated = query_builder.separated(", ");
+        for id in ids {
+            separated.push_bind(*id);
+        }
+        query_builder.push(")");
+
+        Ok(query_builder
+            .build()
+            .execute(&self.db)
+            .await?
+            .rows_affected())
+    }
+}
@@ -0,0 +1,91 @@
+use super::{
+    insert_transaction_query, now_utc, shared_transfers_query, Storage, StorageRunError,
+    Transaction, TransactionFilter, TransactionType, User,
+};
+
+/// Amount moved from one user to another, kept as a transaction on each side sharing a transfer id
+pub struct Transfer {
+    pub id: i32,
+    /// The sending side, taking the amount from its user. Missing if it's been deleted
+    pub sent: Option<Transaction>,
+    /// The receiving side, giving the amount to its user. Missing if it's been deleted
+    pub received: Option<Transaction>,
+}
+
+impl Storage {
+    /// Moves the amount from one user to another now, adding a transaction for each with the same message.
+    /// Returns the transfer id both sides are tagged with
+    pub async fn add_transfer(
+        &self,
+        from: i32,
+        to: i32,
+        amount: i32,
+        msg: &str,
+    ) -> Result<i32, StorageRunError> {
+        if from == to {
+            return Err(StorageRunError::TransferToSelf);
+        }
+        let now = now_utc();
+        let mut tx = self.db.begin().await?;
+        let sent = insert_transaction_query(from, -amount, TransactionType::Other, msg, now)
+            .execute(&mut *tx)
+            .await?
+            .last_insert_rowid();
+        let received = insert_transaction_query(to, amount, TransactionType::Other, msg, now)
+            .execute(&mut *tx)
+            .await?
+            .last_insert_rowid();
+        // the sending side's id is unique, so it doubles as the transfer's
+        sqlx::query("UPDATE transactions SET transfer_id = $1 WHERE id = $1 OR id = $2")
+            .bind(sent)
+            .bind(received)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(sent as i32)
+    }
+
+    /// Whether the two users have transferred to one another in either direction, counting deleted transfers
+    pub async fn share_transfers(&self, user: i32, other: i32) -> Result<bool, StorageRunError> {
+        Ok(shared_transfers_query(user, other)
+            .fetch_one(&self.db)
+            .await?)
+    }
+
+    /// Transfers between the two users in either direction, newest first, with both sides of each together
+    pub async fn transfers_between(
+        &self,
+        user: &User,
+        other: &User,
+    ) -> Result<Vec<Transfer>, StorageRunError> {
+        let filter = TransactionFilter::TransferBetween {
+            users: [user.get_id(), other.get_id()],
+            names: [user.get_name().to_owned(), other.get_name().to_owned()],
+        };
+        let rows = self.get_transactions(vec![filter], None).await?;
+        // the rows come grouped by transfer, so each side joins the transfer before it if they share an id
+        let mut transfers: Vec<Transfer> = Vec::new();
+        for transaction in rows.transactions {
+            let Some(id) = transaction.transfer_id else {
+                continue;
+            };
+            let transfer = match transfers.last_mut() {
+                Some(transfer) if transfer.id == id => transfer,
+                _ => {
+                    transfers.push(Transfer {
+                        id,
+                        sent: None,
+                        received: None,
+                    });
+                    transfers.last_mut().expect("just pushed")
+                }
+            };
+            if transaction.value < 0 {
+                transfer.sent = Some(transaction);
+            } else {
+                transfer.received = Some(transaction);
+            }
+        }
+        Ok(transfers)
+    }
+}
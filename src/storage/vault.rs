@@ -0,0 +1,43 @@
+use std::path::PathBuf;
+
+use super::{Storage, StorageLoadError};
+
+/// Directory in the data dir holding the databases of named vaults
+const VAULT_DIR: &str = "vaults";
+
+/// Where a vault's database lives, None being the default vault kept in `log.db` as before vaults existed
+pub(super) fn vault_path(vault: Option<&str>) -> Result<PathBuf, StorageLoadError> {
+    let dirs = crate::dirs::app_dirs();
+    let Some(name) = vault else {
+        return Ok(dirs.place_data_file("log.db")?);
+    };
+    // names become file names, so keep them to characters that can't escape the vault directory
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(StorageLoadError::VaultName(name.to_owned()));
+    }
+    Ok(dirs.place_data_file(format!("{VAULT_DIR}/{name}.db"))?)
+}
+
+impl Storage {
+    /// Names of the vaults that have been created, sorted, not including the default vault
+    pub fn vaults() -> Result<Vec<String>, StorageLoadError> {
+        let mut names: Vec<String> = crate::dirs::app_dirs()
+            .list_data_files(VAULT_DIR)
+            .into_iter()
+            .filter(|path| path.extension().is_some_and(|extension| extension == "db"))
+            .filter_map(|path| Some(path.file_stem()?.to_string_lossy().into_owned()))
+            .collect();
+        names.sort();
+        names.dedup();
+        Ok(names)
+    }
+
+    /// Name of the vault this was loaded from, None for the default vault
+    pub fn vault(&self) -> Option<&str> {
+        self.vault.as_deref()
+    }
+}
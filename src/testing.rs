@@ -0,0 +1,52 @@
+//! Helpers shared by the tests, which keep everything in a temporary home rather than the user's own
+
+use std::{
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use async_std::sync::{Mutex, MutexGuard};
+
+use crate::{
+    dirs::{self, AppDirs},
+    storage::Storage,
+};
+
+/// Points mantra at an emptied temporary home the first time it's called, shared by every test in the run.
+/// The directories are only chosen once per run, so tests keep apart by each using a vault of their own
+pub(crate) fn test_home() -> &'static Path {
+    static HOME: OnceLock<PathBuf> = OnceLock::new();
+    HOME.get_or_init(|| {
+        let home = std::env::temp_dir().join("mantra-tests");
+        let _ = std::fs::remove_dir_all(&home);
+        std::fs::create_dir_all(&home).expect("the temporary home can be created");
+        std::env::set_var(dirs::HOME_VAR, &home);
+        // a test that chose the directories without coming through here would be using the real ones
+        assert!(
+            matches!(dirs::app_dirs(), AppDirs::Home(dir) if *dir == home),
+            "the directories were chosen before the temporary home was set"
+        );
+        home
+    })
+}
+
+/// Held by tests that write the config file, which there's only the one of in the shared home
+pub(crate) async fn lock_config() -> MutexGuard<'static, ()> {
+    static CONFIG: Mutex<()> = Mutex::new(());
+    test_home();
+    CONFIG.lock().await
+}
+
+/// Opens an empty database in a vault named for the test, with a pilot called "pilot", whose id is returned with it
+pub(crate) async fn temp_storage(vault: &str) -> (Storage, i32) {
+    test_home();
+    let storage = Storage::load_or_create(Some(vault))
+        .await
+        .expect("the test vault can be created");
+    storage
+        .create_user("pilot")
+        .await
+        .expect("the test pilot can be created");
+    let pilot = storage.get_user("pilot").await.unwrap().get_id();
+    (storage, pilot)
+}